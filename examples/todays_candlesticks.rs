@@ -22,26 +22,19 @@ fn main() {
     // create a new client
     let client = Client::new(&url, &key);
 
-    // Get the first set of candles for today for this instrument
-    let mut results = client.pricing_for("EUR_USD".to_string(), start_of_day)
-        .with_include_first(false)
-        .execute();
+    // Iterate over every candle for today for this instrument, the candles are
+    // loaded in batches as they are needed
+    let query = client.pricing_for("EUR_USD".parse().unwrap(), start_of_day);
 
-    while results.candles.len() > 0 {
-        // For Each Candle in our new Set Print the Open price and the Timestamp
-        for candle in &results.candles {
-            println!("Timestamp: {}", candle.time);
-            println!("-------------------------------------------------------");
-            println!(" Open: {}", candle.mid.as_ref().unwrap().o);
-            println!(" High: {}", candle.mid.as_ref().unwrap().h);
-            println!("  Low: {}", candle.mid.as_ref().unwrap().l);
-            println!("Close: {}", candle.mid.as_ref().unwrap().c);
-            println!("#######################################################");
-        }
-
-        // Load the next set of Candles
-        results = client.pricing_for("EUR_USD".to_string(), results.candles.last().unwrap().time)
-            .with_include_first(false)
-            .execute();
+    for candle in query.iter() {
+        let candle = candle.unwrap();
+        // For Each Candle Print the Open price and the Timestamp
+        println!("Timestamp: {}", candle.time);
+        println!("-------------------------------------------------------");
+        println!(" Open: {}", candle.mid.as_ref().unwrap().o);
+        println!(" High: {}", candle.mid.as_ref().unwrap().h);
+        println!("  Low: {}", candle.mid.as_ref().unwrap().l);
+        println!("Close: {}", candle.mid.as_ref().unwrap().c);
+        println!("#######################################################");
     }
 }
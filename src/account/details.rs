@@ -1,14 +1,17 @@
 use chrono::datetime::DateTime;
 use chrono::UTC;
 
-#[derive(Deserialize)]
+use primitives::{AccountUnits, Currency, DecimalNumber, InstrumentName, PriceValue};
+use super::summary::Summary;
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
 pub enum TradeState {
     OPEN,
     CLOSED,
     CLOSE_WHEN_TRADEABLE
 }
 
-#[derive(Deserialize)]
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
 pub enum OrderState {
     PENDING,
     FILLED,
@@ -16,7 +19,7 @@ pub enum OrderState {
     CANCELLED
 }
 
-#[derive(Deserialize)]
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
 pub struct ClientExtensions {
     /// The Client ID of the Order/Trade
     pub id: String,
@@ -26,104 +29,109 @@ pub struct ClientExtensions {
     pub comment: String
 }
 
-#[derive(Deserialize)]
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
 #[serde(rename_all = "camelCase")]
 pub struct TradeSummary {
     /// The Trade’s identifier, unique within the Trade’s Account.
     pub id: String,
     /// The Trade’s Instrument.
-    pub instrument: String,
+    pub instrument: InstrumentName,
     /// The execution price of the Trade.
-    pub price: f32,
+    pub price: PriceValue,
     /// The date/time when the Trade was opened.
     pub open_time: DateTime<UTC>,
     /// The current state of the Trade.
     pub state: TradeState,
     /// The initial size of the Trade. Negative values indicate a short Trade,
     /// and positive values indicate a long Trade.
-    pub initial_units: f32,
+    pub initial_units: DecimalNumber,
     /// The number of units currently open for the Trade. This value is reduced
     /// to 0.0 as the Trade is closed.
-    pub current_units: f32,
+    pub current_units: DecimalNumber,
     /// The total profit/loss realized on the closed portion of the Trade.
     #[serde(rename = "realizedPL")]
-    pub realized_pl: f32,
+    pub realized_pl: AccountUnits,
     /// The unrealized profit/loss on the open portion of the Trade.
     #[serde(rename = "unrealizedPL")]
-    pub unrealized_pl: f32,
+    pub unrealized_pl: AccountUnits,
     /// The average closing price of the Trade. Only present if the Trade has
     /// been closed or reduced at least once.
-    pub average_close_price: Option<f32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub average_close_price: Option<PriceValue>,
     /// The IDs of the Transactions that have closed portions of this Trade.
     #[serde(rename = "closingTransactionIDs")]
     pub closing_transaction_ids: Vec<String>,
     /// The financing paid/collected for this Trade.
-    pub financing: f32,
+    pub financing: AccountUnits,
     /// The date/time when the Trade was fully closed. Only provided for Trades
     /// whose state is CLOSED.
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub close_time: Option<DateTime<UTC>>,
     /// The client extensions of the Trade.
     pub client_extensions: ClientExtensions,
     /// ID of the Trade’s Take Profit Order, only provided if such an Order
     /// exists.
     #[serde(rename = "takeProfitOrderID")]
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub take_profit_order_id: Option<String>,
     /// ID of the Trade’s Stop Loss Order, only provided if such an Order exists.
     #[serde(rename = "stopLossOrderID")]
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub stop_loss_order_id: Option<String>,
     /// ID of the Trade’s Trailing Stop Loss Order, only provided if such an
     /// Order exists.
     #[serde(rename = "trailingStopLossOrderID")]
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub trailing_stop_loss_order_id: Option<String>
 }
 
-#[derive(Deserialize)]
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
 #[serde(rename_all = "camelCase")]
 pub struct PositionSide {
     /// Number of units in the position (negative value indicates short position,
     /// positive indicates long position).
-    pub units: f32,
+    pub units: DecimalNumber,
     /// Volume-weighted average of the underlying Trade open prices for the
     /// Position.
-    pub average_price: f32,
+    pub average_price: PriceValue,
     /// List of the open Trade IDs which contribute to the open Position.
     #[serde(rename = "tradeIDs")]
     pub trade_ids: Vec<String>,
     /// Profit/loss realized by the PositionSide over the lifetime of the
     /// Account.
-    pub pl: f32,
+    pub pl: AccountUnits,
     /// The unrealized profit/loss of all open Trades that contribute to this
     /// PositionSide.
     #[serde(rename = "unrealizedPL")]
-    pub unrealized_pl: f32,
+    pub unrealized_pl: AccountUnits,
     /// Profit/loss realized by the PositionSide since the Account’s resettablePL
     /// was last reset by the client.
     #[serde(rename = "resettablePL")]
-    pub resettable_pl: f32
+    pub resettable_pl: AccountUnits
 }
 
-#[derive(Deserialize)]
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
 #[serde(rename_all = "camelCase")]
 pub struct Position {
     /// The Position’s Instrument.
-    pub instrument: String,
+    pub instrument: InstrumentName,
     /// Profit/loss realized by the Position over the lifetime of the Account.
-    pub pl: f32,
+    pub pl: AccountUnits,
     /// The unrealized profit/loss of all open Trades that contribute to this
     /// Position.
     #[serde(rename = "unrealizedPL")]
-    pub unrealized_pl: f32,
+    pub unrealized_pl: AccountUnits,
     /// Profit/loss realized by the Position since the Account’s resettablePL was
     /// last reset by the client.
     #[serde(rename = "resettablePL")]
-    pub resettable_pl: f32,
+    pub resettable_pl: AccountUnits,
     /// The details of the long side of the Position.
     pub long: PositionSide,
     /// The details of the short side of the Position.
     pub short: PositionSide
 }
 
-#[derive(Deserialize)]
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
 #[serde(rename_all = "camelCase")]
 pub struct Order {
     /// The Order’s identifier, unique within the Order’s Account.
@@ -137,19 +145,20 @@ pub struct Order {
     pub client_extensions: ClientExtensions
 }
 
-#[derive(Deserialize)]
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
 #[serde(rename_all = "camelCase")]
 pub struct Details {
     /// The Account’s identifier
     pub id: String,
     /// Client-assigned alias for the Account. Only provided if the Account has
     /// an alias set
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub alias: Option<String>,
     /// The home currency of the Account
-    pub currency: String,
+    pub currency: Currency,
     /// The current balance of the Account. Represented in the Account’s home
     /// currency.
-    pub balance: f32,
+    pub balance: AccountUnits,
     /// ID of the user that created the Account.
     #[serde(rename = "createdByUserID")]
     pub created_by_user_id: i32,
@@ -157,25 +166,30 @@ pub struct Details {
     pub created_time: DateTime<UTC>,
     /// The total profit/loss realized over the lifetime of the Account.
     /// Represented in the Account’s home currency.
-    pub pl: f32,
+    pub pl: AccountUnits,
     /// The total realized profit/loss for the Account since it was last reset by
     /// the client. Represented in the Account’s home currency.
     #[serde(rename = "resettablePL")]
-    pub resettable_pl: f32,
+    pub resettable_pl: AccountUnits,
     /// The date/time that the Account’s resettablePL was last reset.
     #[serde(rename = "resettablePLTime")]
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub resettabled_pl_time: Option<DateTime<UTC>>,
     /// Client-provided margin rate override for the Account. The effective
     /// margin rate of the Account is the lesser of this value and the OANDA
     /// margin rate for the Account’s division. This value is only provided if a
     /// margin rate override exists for the Account.
-    pub margin_rate: Option<f32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub margin_rate: Option<DecimalNumber>,
     /// The date/time when the Account entered a margin call state. Only provided
     /// if the Account is in a margin call.
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub margin_call_enter_time: Option<DateTime<UTC>>,
     /// The number of times that the Account’s current margin call was extended.
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub margin_call_extension_count: Option<i32>,
     /// The date/time of the Account’s last margin call extension.
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub last_margin_call_extension_time: Option<DateTime<UTC>>,
     /// The number of Trades currently open in the Account.
     pub open_trade_count: i32,
@@ -188,41 +202,41 @@ pub struct Details {
     /// The total unrealized profit/loss for all Trades currently open in the
     /// Account. Represented in the Account’s home currency.
     #[serde(rename = "unrealizedPL")]
-    pub unrealized_pl: f32,
+    pub unrealized_pl: AccountUnits,
     /// The net asset value of the Account. Equal to Account balance +
     /// unrealizedPL. Represented in the Account’s home currency.
     #[serde(rename = "NAV")]
-    pub nav: f32,
+    pub nav: AccountUnits,
     /// Margin currently used for the Account. Represented in the Account’s home
     /// currency.
-    pub margin_used: f32,
+    pub margin_used: AccountUnits,
     /// Margin available for Account. Represented in the Account’s home currency.
-    pub margin_available: f32,
+    pub margin_available: AccountUnits,
     /// The value of the Account’s open positions represented in the Account’s
     /// home currency.
-    pub position_value: f32,
+    pub position_value: AccountUnits,
     /// The Account’s margin closeout unrealized PL.
     #[serde(rename = "marginCloseoutUnrealizedPL")]
-    pub margin_closeout_unrealized_pl: f32,
+    pub margin_closeout_unrealized_pl: AccountUnits,
     /// The Account’s margin closeout NAV.
     #[serde(rename = "marginCloseoutNAV")]
-    pub margin_closeout_nav: f32,
+    pub margin_closeout_nav: AccountUnits,
     /// The Account’s margin closeout margin used.
-    pub margin_closeout_margin_used: f32,
+    pub margin_closeout_margin_used: AccountUnits,
     /// The Account’s margin closeout percentage. When this value is 1.0 or above
     /// the Account is in a margin closeout situation.
-    pub margin_closeout_percent: f32,
+    pub margin_closeout_percent: DecimalNumber,
     /// The value of the Account’s open positions as used for margin closeout
     /// calculations represented in the Account’s home currency.
-    pub margin_closeout_position_value: f32,
+    pub margin_closeout_position_value: AccountUnits,
     /// The current WithdrawalLimit for the account which will be zero or a
     /// positive value indicating how much can be withdrawn from the account.
-    pub withdrawal_limit: f32,
+    pub withdrawal_limit: AccountUnits,
     /// The Account’s margin call margin used.
-    pub margin_call_margin_used: f32,
+    pub margin_call_margin_used: AccountUnits,
     /// The Account’s margin call percentage. When this value is 1.0 or above the
     /// Account is in a margin call situation.
-    pub margin_call_percent: f32,
+    pub margin_call_percent: DecimalNumber,
     /// The ID of the last Transaction created for the Account.
     #[serde(rename = "lastTransactionID")]
     pub last_transaction_id: String,
@@ -234,7 +248,46 @@ pub struct Details {
     pub orders: Vec<Order>
 }
 
-#[derive(Deserialize)]
+impl Details {
+    /// The Account’s properties without its Trades, Positions and Orders
+    pub fn summary(&self) -> Summary {
+        Summary {
+            id: self.id.clone(),
+            alias: self.alias.clone(),
+            currency: self.currency,
+            balance: self.balance,
+            created_by_user_id: self.created_by_user_id,
+            created_time: self.created_time,
+            pl: self.pl,
+            resettable_pl: self.resettable_pl,
+            resettabled_pl_time: self.resettabled_pl_time,
+            margin_rate: self.margin_rate,
+            margin_call_enter_time: self.margin_call_enter_time,
+            margin_call_extension_count: self.margin_call_extension_count,
+            last_margin_call_extension_time: self.last_margin_call_extension_time,
+            open_trade_count: self.open_trade_count,
+            open_position_count: self.open_position_count,
+            pending_order_count: self.pending_order_count,
+            hedging_enabled: self.hedging_enabled,
+            unrealized_pl: self.unrealized_pl,
+            nav: self.nav,
+            margin_used: self.margin_used,
+            margin_available: self.margin_available,
+            position_value: self.position_value,
+            margin_closeout_unrealized_pl: self.margin_closeout_unrealized_pl,
+            margin_closeout_nav: self.margin_closeout_nav,
+            margin_closeout_margin_used: self.margin_closeout_margin_used,
+            margin_closeout_percent: self.margin_closeout_percent,
+            margin_closeout_position_value: self.margin_closeout_position_value,
+            withdrawal_limit: self.withdrawal_limit,
+            margin_call_margin_used: self.margin_call_margin_used,
+            margin_call_percent: self.margin_call_percent,
+            last_transaction_id: self.last_transaction_id.clone()
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
 #[serde(rename_all = "camelCase")]
 pub struct AccountDetails {
     /// The full details of the requested Account.
@@ -243,3 +296,51 @@ pub struct AccountDetails {
     #[serde(rename = "lastTransactionID")]
     pub last_transaction_id: String
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::{self, Value};
+
+    #[test]
+    fn it_serializes_details_as_oanda_does() {
+        let input = r#"{
+            "account": {
+                "id": "101-004-1435156-001", "currency": "USD", "balance": "100000.0000",
+                "createdByUserID": 1435156, "createdTime": "2017-06-01T14:22:36.123456789Z",
+                "pl": "12.5000", "resettablePL": "12.5000",
+                "openTradeCount": 1, "openPositionCount": 1, "pendingOrderCount": 0, "hedgingEnabled": false,
+                "unrealizedPL": "-0.7000", "NAV": "99999.3000", "marginUsed": "22.2900",
+                "marginAvailable": "99977.0100", "positionValue": "1114.5000",
+                "marginCloseoutUnrealizedPL": "-0.6500", "marginCloseoutNAV": "99999.3500",
+                "marginCloseoutMarginUsed": "22.2900", "marginCloseoutPercent": "0.00011",
+                "marginCloseoutPositionValue": "1114.5000", "withdrawalLimit": "99977.0100",
+                "marginCallMarginUsed": "22.2900", "marginCallPercent": "0.00022",
+                "lastTransactionID": "6",
+                "trades": [{
+                    "id": "6", "instrument": "EUR_USD", "price": "1.11450",
+                    "openTime": "2017-06-21T12:00:00.987654321Z", "state": "OPEN",
+                    "initialUnits": "1000", "currentUnits": "1000", "realizedPL": "0.0000",
+                    "unrealizedPL": "-0.7000", "closingTransactionIDs": [], "financing": "0.0000",
+                    "clientExtensions": {"id": "my-trade", "tag": "strategy-1", "comment": "entry"},
+                    "stopLossOrderID": "7"
+                }],
+                "positions": [{
+                    "instrument": "EUR_USD", "pl": "12.5000", "unrealizedPL": "-0.7000", "resettablePL": "12.5000",
+                    "long": {"units": "1000", "averagePrice": "1.11450", "tradeIDs": ["6"], "pl": "12.5000",
+                             "unrealizedPL": "-0.7000", "resettablePL": "12.5000"},
+                    "short": {"units": "0", "averagePrice": "0.00000", "tradeIDs": [], "pl": "0.0000",
+                              "unrealizedPL": "0.0000", "resettablePL": "0.0000"}
+                }],
+                "orders": []
+            },
+            "lastTransactionID": "6"
+        }"#;
+        let details: AccountDetails = serde_json::from_str(input).unwrap();
+        let output = serde_json::to_string(&details).unwrap();
+
+        assert_eq!(serde_json::from_str::<Value>(&output).unwrap(), serde_json::from_str::<Value>(input).unwrap());
+        assert_eq!(serde_json::from_str::<AccountDetails>(&output).unwrap(), details);
+        assert_eq!(details.account.trades[0].state, TradeState::OPEN);
+    }
+}
@@ -1,18 +1,102 @@
-#[derive(Deserialize)]
-pub enum InstrumentType {
-    /// Currency
-    CURRENCY,
-    ///Contract For Difference
-    CFD,
-    /// METAL
-    METAL
+use std::error;
+use std::fmt;
+
+use primitives::{AccountUnits, Decimal, DecimalNumber, InstrumentName, PriceValue, RoundingStrategy};
+
+open_enum! {
+    pub enum InstrumentType {
+        /// Currency
+        CURRENCY,
+        ///Contract For Difference
+        CFD,
+        /// METAL
+        METAL
+    }
+}
+
+open_enum! {
+    /// The overall behaviour of the Account regarding guaranteed Stop Loss
+    /// Orders for a specific Instrument.
+    pub enum GuaranteedStopLossOrderModeForInstrument {
+        /// The Account is not permitted to create guaranteed Stop Loss Orders
+        /// for this Instrument.
+        DISABLED,
+        /// The Account is able, but not required to have guaranteed Stop Loss
+        /// Orders for open Trades for this Instrument.
+        ALLOWED,
+        /// The Account is required to have guaranteed Stop Loss Orders for all
+        /// open Trades for this Instrument.
+        REQUIRED
+    }
+}
+
+open_enum! {
+    /// The days of the week
+    pub enum DayOfWeek {
+        SUNDAY,
+        MONDAY,
+        TUESDAY,
+        WEDNESDAY,
+        THURSDAY,
+        FRIDAY,
+        SATURDAY
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct InstrumentCommission {
+    /// The commission amount (in the Account’s home currency) charged per
+    /// unitsTraded of the Instrument
+    pub commission: DecimalNumber,
+    /// The number of units traded that the commission amount is based on.
+    pub units_traded: DecimalNumber,
+    /// The minimum commission amount (in the Account’s home currency) that is
+    /// charged when an Order is filled for this Instrument.
+    pub minimum_commission: DecimalNumber
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct FinancingDayOfWeek {
+    /// The day of the week to charge the financing.
+    pub day_of_week: DayOfWeek,
+    /// The number of days worth of financing to be charged on dayOfWeek.
+    pub days_charged: i32
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct InstrumentFinancing {
+    /// The financing rate to be used for a long position for the instrument.
+    /// The value is in decimal rather than percentage points, i.e. 5% is
+    /// represented as 0.05.
+    pub long_rate: DecimalNumber,
+    /// The financing rate to be used for a short position for the instrument.
+    /// The value is in decimal rather than percentage points, i.e. 5% is
+    /// represented as 0.05.
+    pub short_rate: DecimalNumber,
+    /// The days of the week to debit or credit financing charges; the exact
+    /// time of day at which to charge the financing is set in the
+    /// DivisionTradingGroup for the client’s account.
+    #[serde(default)]
+    pub financing_days_of_week: Vec<FinancingDayOfWeek>
 }
 
-#[derive(Deserialize)]
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct Tag {
+    /// The type of tag.
+    #[serde(rename = "type")]
+    pub tag_type: String,
+    /// The name of the tag.
+    pub name: String
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
 #[serde(rename_all = "camelCase")]
 pub struct Instrument {
     /// The name of the Instrument
-    pub name: String,
+    pub name: InstrumentName,
     /// The type of the Instrument
     #[serde(rename = "type")]
     pub instrument_type: InstrumentType,
@@ -30,24 +114,191 @@ pub struct Instrument {
     /// number of units traded for this instrument.
     pub trade_units_precision: i32,
     /// The smallest number of units allowed to be traded for this instrument.
-    pub minimum_trade_size: f32,
+    pub minimum_trade_size: DecimalNumber,
     /// The maximum trailing stop distance allowed for a trailing stop loss
     /// created for this instrument. Specified in price units.
-    pub maximum_trailing_stop_distance: f32,
+    pub maximum_trailing_stop_distance: DecimalNumber,
     /// The minimum trailing stop distance allowed for a trailing stop loss
     /// created for this instrument. Specified in price units.
-    pub minimum_trailing_stop_distance: f32,
+    pub minimum_trailing_stop_distance: DecimalNumber,
     /// The maximum position size allowed for this instrument. Specified in
     /// units.
-    pub maximum_position_size: f32,
+    pub maximum_position_size: DecimalNumber,
     /// The maximum units allowed for an Order placed for this instrument.
     /// Specified in units.
-    pub maximum_order_units: f32,
+    pub maximum_order_units: DecimalNumber,
     /// The margin rate for this instrument.
-    pub margin_rate: f32
+    pub margin_rate: DecimalNumber,
+    /// The commission structure for this instrument.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub commission: Option<InstrumentCommission>,
+    /// The current Guaranteed Stop Loss Order mode of the Account for this
+    /// Instrument.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub guaranteed_stop_loss_order_mode: Option<GuaranteedStopLossOrderModeForInstrument>,
+    /// The amount that is charged to the account if a guaranteed Stop Loss
+    /// Order is triggered and filled. The value is in price units and is
+    /// charged for each unit of the Trade.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub guaranteed_stop_loss_order_execution_premium: Option<DecimalNumber>,
+    /// The minimum distance allowed between the Trade’s fill price and the
+    /// configured price for guaranteed Stop Loss Orders created for this
+    /// instrument. Specified in price units.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub minimum_guaranteed_stop_loss_distance: Option<DecimalNumber>,
+    /// Financing data for this instrument.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub financing: Option<InstrumentFinancing>,
+    /// The tags associated with this instrument.
+    #[serde(default)]
+    pub tags: Vec<Tag>
+}
+
+/// The reasons a number of units cannot be traded for an Instrument
+#[derive(Debug, PartialEq)]
+pub enum UnitsError {
+    /// The units are below the Instrument’s minimum trade size
+    BelowMinimumTradeSize(DecimalNumber),
+    /// The units are above the maximum units allowed for an Order
+    AboveMaximumOrderUnits(DecimalNumber),
+    /// The units have more decimal places than the Instrument allows
+    TooPrecise(i32)
 }
 
-#[derive(Deserialize)]
+impl fmt::Display for UnitsError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            UnitsError::BelowMinimumTradeSize(ref min) => write!(f, "units are below the minimum trade size of {}", min),
+            UnitsError::AboveMaximumOrderUnits(ref max) => write!(f, "units are above the maximum order units of {}", max),
+            UnitsError::TooPrecise(precision) => write!(f, "units may have at most {} decimal places", precision)
+        }
+    }
+}
+
+impl error::Error for UnitsError {}
+
+impl Instrument {
+    /// A currency pair named `name`, e.g. EUR_USD, with a margin rate of 2%
+    /// and a pip at the fourth decimal place, or the second when the quote
+    /// currency is the yen. For paper trading without an Account's
+    /// instruments.
+    pub fn currency_pair(name: InstrumentName) -> Instrument {
+        let jpy = name.quote_currency().as_str() == "JPY";
+
+        Instrument {
+            display_name: name.as_str().replace('_', "/"),
+            name: name,
+            instrument_type: InstrumentType::CURRENCY,
+            pip_location: if jpy { -2 } else { -4 },
+            display_precision: if jpy { 3 } else { 5 },
+            trade_units_precision: 0,
+            minimum_trade_size: DecimalNumber::from(Decimal::from(1)),
+            maximum_trailing_stop_distance: DecimalNumber::from(Decimal::from(1)),
+            minimum_trailing_stop_distance: if jpy { "0.050".parse().unwrap() } else { "0.00050".parse().unwrap() },
+            maximum_position_size: DecimalNumber::default(),
+            maximum_order_units: DecimalNumber::from(Decimal::from(100000000)),
+            margin_rate: "0.02".parse().unwrap(),
+            commission: None,
+            guaranteed_stop_loss_order_mode: None,
+            guaranteed_stop_loss_order_execution_premium: None,
+            minimum_guaranteed_stop_loss_distance: None,
+            financing: None,
+            tags: Vec::new()
+        }
+    }
+
+    /// The annual financing rate for a position of `units`, the long rate for
+    /// positive units and the short rate for negative units
+    pub fn financing_rate(&self, units: DecimalNumber) -> Option<DecimalNumber> {
+        self.financing.as_ref().map(|financing| {
+            if units.0.is_sign_negative() { financing.short_rate } else { financing.long_rate }
+        })
+    }
+
+    /// The number of days of financing charged on `day`, none if Oanda lists
+    /// financing days for the instrument but not this one. `None` if Oanda
+    /// gave no financing days at all.
+    pub fn days_charged_on(&self, day: &DayOfWeek) -> Option<i32> {
+        let days = match self.financing {
+            Some(ref financing) if !financing.financing_days_of_week.is_empty() => &financing.financing_days_of_week,
+            _ => return None
+        };
+
+        Some(days.iter().find(|d| d.day_of_week == *day).map_or(0, |d| d.days_charged))
+    }
+
+    /// Estimate the financing for holding a position of `units` for
+    /// `days_charged` days at `price`. `home_conversion` converts the quote
+    /// currency into the Account’s home currency. Negative values are a cost.
+    pub fn estimate_financing(
+        &self,
+        units: DecimalNumber,
+        price: PriceValue,
+        home_conversion: Decimal,
+        days_charged: i32
+    ) -> Option<AccountUnits> {
+        self.financing_rate(units).map(|rate| {
+            let notional = units.0.abs() * price.0 * home_conversion;
+
+            AccountUnits(notional * rate.0 * Decimal::from(days_charged) / Decimal::from(365))
+        })
+    }
+
+    /// The size of one pip in price units, 10 ^ pipLocation
+    pub fn pip_size(&self) -> Decimal {
+        if self.pip_location < 0 {
+            Decimal::new(1, (-self.pip_location) as u32)
+        } else {
+            Decimal::from(10i64.pow(self.pip_location as u32))
+        }
+    }
+
+    /// Convert a distance in pips to a distance in price units
+    pub fn pips_to_price(&self, pips: Decimal) -> Decimal {
+        pips * self.pip_size()
+    }
+
+    /// Convert a distance in price units to a distance in pips
+    pub fn price_to_pips(&self, distance: Decimal) -> Decimal {
+        distance / self.pip_size()
+    }
+
+    /// Round a price to the Instrument’s display precision, halfway values
+    /// are rounded away from zero
+    pub fn round_price(&self, price: PriceValue) -> PriceValue {
+        PriceValue(
+            price.0.round_dp_with_strategy(self.display_precision.max(0) as u32, RoundingStrategy::MidpointAwayFromZero)
+        )
+    }
+
+    /// Round a number of units towards zero to the Instrument’s trade units
+    /// precision, so the result is never larger than what was asked for
+    pub fn round_units(&self, units: DecimalNumber) -> DecimalNumber {
+        DecimalNumber(
+            units.0.round_dp_with_strategy(self.trade_units_precision.max(0) as u32, RoundingStrategy::ToZero)
+        )
+    }
+
+    /// Check that a number of units can be used for an Order on this
+    /// Instrument. Negative units (short Orders) are checked by their size.
+    pub fn check_units(&self, units: DecimalNumber) -> Result<(), UnitsError> {
+        let size = units.0.abs();
+
+        if self.round_units(units) != units {
+            return Err(UnitsError::TooPrecise(self.trade_units_precision))
+        }
+        if size < self.minimum_trade_size.0 {
+            return Err(UnitsError::BelowMinimumTradeSize(self.minimum_trade_size))
+        }
+        if size > self.maximum_order_units.0 {
+            return Err(UnitsError::AboveMaximumOrderUnits(self.maximum_order_units))
+        }
+
+        Ok(())
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
 pub struct AccountInstruments {
     /// The requested list of instruments.
     pub instruments: Vec<Instrument>,
@@ -55,3 +306,103 @@ pub struct AccountInstruments {
     #[serde(rename = "lastTransactionID")]
     pub last_transaction_id: String
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::str::FromStr;
+    use serde_json;
+
+    fn usd_jpy() -> Instrument {
+        serde_json::from_str(r#"{
+            "name": "USD_JPY",
+            "type": "CURRENCY",
+            "displayName": "USD/JPY",
+            "pipLocation": -2,
+            "displayPrecision": 3,
+            "tradeUnitsPrecision": 0,
+            "minimumTradeSize": "1",
+            "maximumTrailingStopDistance": "100.000",
+            "minimumTrailingStopDistance": "0.050",
+            "maximumPositionSize": "0",
+            "maximumOrderUnits": "100000000",
+            "marginRate": "0.04"
+        }"#).unwrap()
+    }
+
+    fn dec(s: &str) -> Decimal {
+        Decimal::from_str(s).unwrap()
+    }
+
+    #[test]
+    fn it_reads_the_full_model() {
+        let instrument: Instrument = serde_json::from_str(r#"{
+            "name": "EUR_USD",
+            "type": "CURRENCY",
+            "displayName": "EUR/USD",
+            "pipLocation": -4,
+            "displayPrecision": 5,
+            "tradeUnitsPrecision": 0,
+            "minimumTradeSize": "1",
+            "maximumTrailingStopDistance": "1.00000",
+            "minimumTrailingStopDistance": "0.00050",
+            "maximumPositionSize": "0",
+            "maximumOrderUnits": "100000000",
+            "marginRate": "0.0333",
+            "commission": {"commission": "0", "unitsTraded": "1", "minimumCommission": "0"},
+            "guaranteedStopLossOrderMode": "SOMETHING_NEW",
+            "minimumGuaranteedStopLossDistance": "0.0010",
+            "financing": {
+                "longRate": "-0.0365",
+                "shortRate": "0.0073",
+                "financingDaysOfWeek": [
+                    {"dayOfWeek": "WEDNESDAY", "daysCharged": 3},
+                    {"dayOfWeek": "THURSDAY", "daysCharged": 1}
+                ]
+            },
+            "tags": [{"type": "ASSET_CLASS", "name": "CURRENCY"}]
+        }"#).unwrap();
+
+        assert_eq!(
+            instrument.guaranteed_stop_loss_order_mode,
+            Some(GuaranteedStopLossOrderModeForInstrument::Unknown("SOMETHING_NEW".to_string()))
+        );
+        assert_eq!(instrument.tags[0].name, "CURRENCY");
+        assert_eq!(instrument.days_charged_on(&DayOfWeek::WEDNESDAY), Some(3));
+        assert_eq!(instrument.days_charged_on(&DayOfWeek::SATURDAY), Some(0));
+        assert_eq!(usd_jpy().days_charged_on(&DayOfWeek::WEDNESDAY), None);
+        // 10,000 units at 1.1 for 3 days at -3.65% a year
+        assert_eq!(
+            instrument.estimate_financing(DecimalNumber(dec("10000")), PriceValue(dec("1.1")), dec("1"), 3),
+            Some(AccountUnits(dec("-3.3")))
+        );
+        assert_eq!(instrument.financing_rate(DecimalNumber(dec("-1"))), Some(DecimalNumber(dec("0.0073"))));
+    }
+
+    #[test]
+    fn it_converts_between_pips_and_price() {
+        let instrument = usd_jpy();
+
+        assert_eq!(instrument.pip_size(), dec("0.01"));
+        assert_eq!(instrument.pips_to_price(dec("15.5")), dec("0.155"));
+        assert_eq!(instrument.price_to_pips(dec("0.155")), dec("15.5"));
+    }
+
+    #[test]
+    fn it_rounds_prices_and_units() {
+        let instrument = usd_jpy();
+
+        assert_eq!(instrument.round_price(PriceValue(dec("112.0525"))), PriceValue(dec("112.053")));
+        assert_eq!(instrument.round_units(DecimalNumber(dec("-1000.9"))), DecimalNumber(dec("-1000")));
+    }
+
+    #[test]
+    fn it_checks_units() {
+        let instrument = usd_jpy();
+
+        assert_eq!(instrument.check_units(DecimalNumber(dec("-5000"))), Ok(()));
+        assert_eq!(instrument.check_units(DecimalNumber(dec("0"))), Err(UnitsError::BelowMinimumTradeSize(DecimalNumber(dec("1")))));
+        assert_eq!(instrument.check_units(DecimalNumber(dec("100000001"))), Err(UnitsError::AboveMaximumOrderUnits(DecimalNumber(dec("100000000")))));
+        assert_eq!(instrument.check_units(DecimalNumber(dec("10.5"))), Err(UnitsError::TooPrecise(0)));
+    }
+}
@@ -1,57 +1,220 @@
 pub mod details;
 pub mod instruments;
+pub mod orders;
+pub mod positions;
+pub mod registry;
 pub mod summary;
+pub mod trading;
+pub mod trades;
+pub mod transactions;
+
+use std::cell::{Ref, RefCell};
 
 use serde_json;
+use url::form_urlencoded::Serializer;
 
 use client::Client;
+use error::Result;
+use instrument::candlestick_granularity::CandlestickGranularity;
+use instrument::price_components::PriceComponents;
+use instrument::pricing::{LatestCandles, Pricing};
+use primitives::InstrumentName;
+use stream::{PriceStream, TransactionStream};
 use self::details::AccountDetails;
-use self::details::Details;
+use self::details::{Details, Position};
+use self::orders::{CancelOrderResponse, CreateOrderRequest, CreateOrderResponse, OrderRequest};
+use self::positions::{AccountPosition, ClosePositionRequest, ClosePositionResponse};
+use self::trades::{CloseTradeRequest, CloseTradeResponse};
 use self::summary::AccountSummary;
 use self::summary::Summary;
 use self::instruments::AccountInstruments;
 use self::instruments::Instrument;
+use self::registry::InstrumentRegistry;
 
 fn none() -> Option<&'static Client<'static>> { None }
 
-#[derive(Deserialize)]
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
 pub struct Accounts<'a> {
     pub accounts: Vec<Account<'a>>
 }
 
-#[derive(Deserialize)]
+#[derive(Serialize, Deserialize, Clone, Debug)]
 pub struct Account<'a> {
     pub id: String,
     pub tags: Vec<String>,
     #[serde(default = "none")]
-    #[serde(skip_deserializing)]
-    pub client: Option<&'a Client<'a>>
+    #[serde(skip_deserializing, skip_serializing)]
+    pub client: Option<&'a Client<'a>>,
+    /// The Account's instruments, loaded on first use
+    #[serde(skip_deserializing, skip_serializing)]
+    registry: RefCell<Option<Box<InstrumentRegistry<'a>>>>
+}
+
+/// Accounts are equal when their properties are, whichever client loaded them
+impl<'a> PartialEq for Account<'a> {
+    fn eq(&self, other: &Account<'a>) -> bool {
+        self.id == other.id && self.tags == other.tags
+    }
 }
 
 impl <'a>Account<'a> {
+    /// The Account with the given id, with no tags
+    pub fn new(id: &str, client: Option<&'a Client<'a>>) -> Account<'a> {
+        Account {
+            id: id.to_string(),
+            tags: Vec::new(),
+            client: client,
+            registry: RefCell::new(None)
+        }
+    }
+
     pub fn details(&self) -> Details {
-        let input = self.client().get(format!("accounts/{}", self.id).as_str());
-        let result: AccountDetails = serde_json::from_str(&input).unwrap();
+        self.try_details().unwrap()
+    }
+
+    pub fn try_details(&self) -> Result<Details> {
+        let input = self.client().try_get(format!("accounts/{}", self.id).as_str())?;
+        let result: AccountDetails = serde_json::from_str(&input)?;
 
-        result.account
+        Ok(result.account)
     }
 
     pub fn instruments(&self) -> Vec<Instrument> {
-        let input = self.client().get(
+        self.try_instruments().unwrap()
+    }
+
+    pub fn try_instruments(&self) -> Result<Vec<Instrument>> {
+        let input = self.client().try_get(
             format!("accounts/{}/instruments", self.id).as_str()
-        );
-        let result: AccountInstruments = serde_json::from_str(&input).unwrap();
+        )?;
+        let result: AccountInstruments = serde_json::from_str(&input)?;
+
+        Ok(result.instruments)
+    }
+
+    /// The Account's instruments in a registry that can be queried without
+    /// further requests. They are loaded on the first call and kept until
+    /// `refresh_instrument_registry`.
+    pub fn instrument_registry(&self) -> Ref<InstrumentRegistry<'a>> {
+        if self.registry.borrow().is_none() {
+            let registry = InstrumentRegistry::load(self);
+            *self.registry.borrow_mut() = Some(Box::new(registry));
+        }
+
+        Ref::map(self.registry.borrow(), |registry| &**registry.as_ref().unwrap())
+    }
+
+    /// Load the Account's instruments from Oanda again
+    pub fn refresh_instrument_registry(&self) {
+        if let Some(ref mut registry) = *self.registry.borrow_mut() {
+            return registry.refresh()
+        }
+
+        let registry = InstrumentRegistry::load(self);
+        *self.registry.borrow_mut() = Some(Box::new(registry));
+    }
+
+    /// Get the most recent candlesticks for each instrument, granularity and
+    /// price components in `specifications` with a single request. The
+    /// results are in the same order as the specifications.
+    pub fn latest_candles(
+        &self,
+        specifications: &[(InstrumentName, CandlestickGranularity, PriceComponents)]
+    ) -> Vec<Pricing> {
+        let input = self.client().get(&latest_candles_path(&self.id, specifications));
+        let result: LatestCandles = serde_json::from_str(&input).unwrap();
 
-        result.instruments
+        result.latest_candles
     }
 
     pub fn summary(&self) -> Summary {
-        let input = self.client().get(
+        self.try_summary().unwrap()
+    }
+
+    pub fn try_summary(&self) -> Result<Summary> {
+        let input = self.client().try_get(
             format!("accounts/{}/summary", self.id).as_str()
-        );
-        let result: AccountSummary = serde_json::from_str(&input).unwrap();
+        )?;
+        let result: AccountSummary = serde_json::from_str(&input)?;
+
+        Ok(result.account)
+    }
+
+    /// Get the Position for `instrument`
+    pub fn position(&self, instrument: &InstrumentName) -> Position {
+        self.try_position(instrument).unwrap()
+    }
+
+    pub fn try_position(&self, instrument: &InstrumentName) -> Result<Position> {
+        let input = self.client().try_get(&format!("accounts/{}/positions/{}", self.id, instrument))?;
+        let result: AccountPosition = serde_json::from_str(&input)?;
+
+        Ok(result.position)
+    }
+
+    /// Create an Order, e.g. a `MarketOrderRequest`
+    pub fn create_order<O: OrderRequest>(&self, request: &CreateOrderRequest<O>) -> CreateOrderResponse {
+        self.try_create_order(request).unwrap()
+    }
+
+    pub fn try_create_order<O: OrderRequest>(&self, request: &CreateOrderRequest<O>) -> Result<CreateOrderResponse> {
+        let body = serde_json::to_string(request)?;
+        let input = self.client().try_post(&format!("accounts/{}/orders", self.id), &body)?;
+
+        Ok(serde_json::from_str(&input)?)
+    }
+
+    /// Cancel a pending Order
+    pub fn cancel_order(&self, order_id: &str) -> CancelOrderResponse {
+        self.try_cancel_order(order_id).unwrap()
+    }
+
+    pub fn try_cancel_order(&self, order_id: &str) -> Result<CancelOrderResponse> {
+        let input = self.client().try_put(&format!("accounts/{}/orders/{}/cancel", self.id, order_id), None)?;
+
+        Ok(serde_json::from_str(&input)?)
+    }
+
+    /// Close (partially or fully) an open Trade
+    pub fn close_trade(&self, trade_id: &str, request: &CloseTradeRequest) -> CloseTradeResponse {
+        self.try_close_trade(trade_id, request).unwrap()
+    }
+
+    pub fn try_close_trade(&self, trade_id: &str, request: &CloseTradeRequest) -> Result<CloseTradeResponse> {
+        let body = serde_json::to_string(request)?;
+        let input = self.client().try_put(&format!("accounts/{}/trades/{}/close", self.id, trade_id), Some(&body))?;
+
+        Ok(serde_json::from_str(&input)?)
+    }
+
+    /// Closeout the open Position for `instrument`
+    pub fn close_position(&self, instrument: &InstrumentName, request: &ClosePositionRequest) -> ClosePositionResponse {
+        self.try_close_position(instrument, request).unwrap()
+    }
+
+    pub fn try_close_position(
+        &self,
+        instrument: &InstrumentName,
+        request: &ClosePositionRequest
+    ) -> Result<ClosePositionResponse> {
+        let body = serde_json::to_string(request)?;
+        let input = self.client().try_put(&format!("accounts/{}/positions/{}/close", self.id, instrument), Some(&body))?;
+
+        Ok(serde_json::from_str(&input)?)
+    }
+
+    /// Stream the Prices of `instruments` as they change
+    pub fn price_stream(&self, instruments: &[InstrumentName]) -> Result<PriceStream> {
+        let response = self.client().try_stream(&price_stream_path(&self.id, instruments))?;
+
+        Ok(PriceStream::new(response))
+    }
+
+    /// Stream the Transactions of the Account as they are created
+    pub fn transaction_stream(&self) -> Result<TransactionStream> {
+        let response = self.client().try_stream(&format!("accounts/{}/transactions/stream", self.id))?;
 
-        result.account
+        Ok(TransactionStream::new(response))
     }
 
     fn client(&self) -> &'a Client<'a> {
@@ -59,10 +222,78 @@ impl <'a>Account<'a> {
     }
 }
 
+fn price_stream_path(id: &str, instruments: &[InstrumentName]) -> String {
+    let instruments = instruments.iter().map(|i| i.as_str()).collect::<Vec<_>>().join(",");
+
+    format!(
+        "accounts/{}/pricing/stream?{}",
+        id,
+        Serializer::new(String::new()).append_pair("instruments", &instruments).finish()
+    )
+}
+
+fn latest_candles_path(
+    id: &str,
+    specifications: &[(InstrumentName, CandlestickGranularity, PriceComponents)]
+) -> String {
+    let specifications = specifications.iter()
+        .map(|&(ref instrument, granularity, price)| format!("{}:{}:{}", instrument, granularity, price))
+        .collect::<Vec<_>>()
+        .join(",");
+
+    format!(
+        "accounts/{}/candles/latest?{}",
+        id,
+        Serializer::new(String::new()).append_pair("candleSpecifications", &specifications).finish()
+    )
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
     use std::env;
+    use mock::{MockServer, MockState};
+
+    #[test]
+    fn it_builds_the_latest_candles_path() {
+        let specifications = vec![
+            ("EUR_USD".parse().unwrap(), CandlestickGranularity::M1, PriceComponents::MID),
+            ("USD_JPY".parse().unwrap(), CandlestickGranularity::S10, PriceComponents::BID | PriceComponents::ASK)
+        ];
+
+        assert_eq!(
+            latest_candles_path("101-001-1-001", &specifications),
+            "accounts/101-001-1-001/candles/latest?candleSpecifications=EUR_USD%3AM1%3AM%2CUSD_JPY%3AS10%3ABA"
+        );
+    }
+
+    #[test]
+    fn it_builds_the_price_stream_path() {
+        assert_eq!(
+            price_stream_path("101-001-1-001", &["EUR_USD".parse().unwrap(), "USD_JPY".parse().unwrap()]),
+            "accounts/101-001-1-001/pricing/stream?instruments=EUR_USD%2CUSD_JPY"
+        );
+    }
+
+    #[test]
+    fn it_keeps_the_instrument_registry_until_it_is_refreshed() {
+        let mut state = MockState::new();
+        state
+            .with_account("101-001-1-001", "USD".parse().unwrap(), "100000".parse().unwrap())
+            .with_instrument(Instrument::currency_pair("EUR_USD".parse().unwrap()));
+        let server = MockServer::start(state).unwrap();
+        let client = Client::new(server.url(), "key");
+        let accounts = client.accounts();
+
+        assert_eq!(accounts[0].instrument_registry().len(), 1);
+
+        server.state().with_instrument(Instrument::currency_pair("USD_JPY".parse().unwrap()));
+        assert_eq!(accounts[0].instrument_registry().len(), 1);
+
+        accounts[0].refresh_instrument_registry();
+        assert_eq!(accounts[0].instrument_registry().len(), 2);
+        assert!(accounts[0].instrument_registry().get("USD_JPY").is_some());
+    }
 
     /// # TODO: Move integration tests to `tests/`
     #[test]
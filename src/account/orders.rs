@@ -0,0 +1,388 @@
+use serde::Serialize;
+
+use primitives::{DecimalNumber, InstrumentName, PriceValue};
+use super::transactions::Transaction;
+
+open_enum! {
+    /// The type of an Order.
+    pub enum OrderType {
+        /// A Market Order
+        MARKET,
+        /// A Limit Order
+        LIMIT,
+        /// A Stop Order
+        STOP,
+        /// A Market-if-touched Order
+        MARKET_IF_TOUCHED,
+        /// A Take Profit Order
+        TAKE_PROFIT,
+        /// A Stop Loss Order
+        STOP_LOSS,
+        /// A Trailing Stop Loss Order
+        TRAILING_STOP_LOSS,
+        /// A Fixed Price Order
+        FIXED_PRICE
+    }
+}
+
+open_enum! {
+    /// The time-in-force of an Order. TimeInForce describes how long an Order
+    /// should remain pending before being automatically cancelled by the
+    /// execution system.
+    pub enum TimeInForce {
+        /// The Order is “Good unTil Cancelled”
+        GTC,
+        /// The Order is “Good unTil Date” and will be cancelled at the provided
+        /// time
+        GTD,
+        /// The Order is “Good For Day” and will be cancelled at 5pm New York
+        /// time
+        GFD,
+        /// The Order must be immediately “Filled Or Killed”
+        FOK,
+        /// The Order must be “Immediately partially filled Or Cancelled”
+        IOC
+    }
+}
+
+open_enum! {
+    /// Specification of how Positions in the Account are modified when the
+    /// Order is filled.
+    pub enum OrderPositionFill {
+        /// When the Order is filled, only allow Positions to be opened or
+        /// extended.
+        OPEN_ONLY,
+        /// When the Order is filled, always fully reduce an existing Position
+        /// before opening a new Position.
+        REDUCE_FIRST,
+        /// When the Order is filled, only reduce an existing Position.
+        REDUCE_ONLY,
+        /// When the Order is filled, use REDUCE_FIRST behaviour for
+        /// non-client hedging Accounts, and OPEN_ONLY behaviour for client
+        /// hedging Accounts.
+        DEFAULT
+    }
+}
+
+/// Specifies the details of a Take Profit Order to be created on behalf of a
+/// client when the Order that opens a Trade is filled.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct TakeProfitDetails {
+    /// The price that the Take Profit Order will be triggered at.
+    pub price: PriceValue,
+    /// The time in force for the created Take Profit Order. This may only be
+    /// GTC, GTD or GFD. [default=GTC]
+    pub time_in_force: TimeInForce
+}
+
+/// Specifies the details of a Stop Loss Order to be created on behalf of a
+/// client when the Order that opens a Trade is filled.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct StopLossDetails {
+    /// The price that the Stop Loss Order will be triggered at.
+    pub price: PriceValue,
+    /// The time in force for the created Stop Loss Order. This may only be
+    /// GTC, GTD or GFD. [default=GTC]
+    pub time_in_force: TimeInForce
+}
+
+/// The specification of an Order that can be created with
+/// `Account::create_order`
+pub trait OrderRequest: Serialize {}
+
+/// A MarketOrderRequest specifies the parameters that may be set when
+/// creating a Market Order.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct MarketOrderRequest {
+    /// The type of the Order to Create. Must be set to “MARKET” when creating
+    /// a Market Order.
+    #[serde(rename = "type")]
+    pub order_type: OrderType,
+    /// The Market Order’s Instrument.
+    pub instrument: InstrumentName,
+    /// The quantity requested to be filled by the Market Order. A positive
+    /// number of units results in a long Order, and a negative number of units
+    /// results in a short Order.
+    pub units: DecimalNumber,
+    /// The time-in-force requested for the Market Order. Restricted to FOK or
+    /// IOC for a MarketOrder. [default=FOK]
+    pub time_in_force: TimeInForce,
+    /// The worst price that the client is willing to have the Market Order
+    /// filled at.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub price_bound: Option<PriceValue>,
+    /// Specification of how Positions in the Account are modified when the
+    /// Order is filled. [default=DEFAULT]
+    pub position_fill: OrderPositionFill,
+    /// TakeProfitDetails specifies the details of a Take Profit Order to be
+    /// created on behalf of a client.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub take_profit_on_fill: Option<TakeProfitDetails>,
+    /// StopLossDetails specifies the details of a Stop Loss Order to be
+    /// created on behalf of a client.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub stop_loss_on_fill: Option<StopLossDetails>
+}
+
+impl MarketOrderRequest {
+    pub fn new(instrument: InstrumentName, units: DecimalNumber) -> MarketOrderRequest {
+        MarketOrderRequest {
+            order_type: OrderType::MARKET,
+            instrument: instrument,
+            units: units,
+            time_in_force: TimeInForce::FOK,
+            price_bound: None,
+            position_fill: OrderPositionFill::DEFAULT,
+            take_profit_on_fill: None,
+            stop_loss_on_fill: None
+        }
+    }
+
+    pub fn with_price_bound(&mut self, price_bound: PriceValue) -> &mut MarketOrderRequest {
+        self.price_bound = Some(price_bound);
+        self
+    }
+
+    pub fn with_position_fill(&mut self, position_fill: OrderPositionFill) -> &mut MarketOrderRequest {
+        self.position_fill = position_fill;
+        self
+    }
+
+    /// Create a Take Profit Order at `price` for the Trade that is opened
+    pub fn with_take_profit(&mut self, price: PriceValue) -> &mut MarketOrderRequest {
+        self.take_profit_on_fill = Some(TakeProfitDetails { price: price, time_in_force: TimeInForce::GTC });
+        self
+    }
+
+    /// Create a Stop Loss Order at `price` for the Trade that is opened
+    pub fn with_stop_loss(&mut self, price: PriceValue) -> &mut MarketOrderRequest {
+        self.stop_loss_on_fill = Some(StopLossDetails { price: price, time_in_force: TimeInForce::GTC });
+        self
+    }
+}
+
+impl OrderRequest for MarketOrderRequest {}
+
+/// A LimitOrderRequest specifies the parameters that may be set when
+/// creating a Limit Order.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct LimitOrderRequest {
+    /// The type of the Order to Create. Must be set to “LIMIT” when creating
+    /// a Limit Order.
+    #[serde(rename = "type")]
+    pub order_type: OrderType,
+    /// The Limit Order’s Instrument.
+    pub instrument: InstrumentName,
+    /// The quantity requested to be filled by the Limit Order. A positive
+    /// number of units results in a long Order, and a negative number of units
+    /// results in a short Order.
+    pub units: DecimalNumber,
+    /// The price threshold specified for the Limit Order. The Limit Order will
+    /// only be filled by a market price that is equal to or better than this
+    /// price.
+    pub price: PriceValue,
+    /// The time-in-force requested for the Limit Order. [default=GTC]
+    pub time_in_force: TimeInForce,
+    /// Specification of how Positions in the Account are modified when the
+    /// Order is filled. [default=DEFAULT]
+    pub position_fill: OrderPositionFill,
+    /// TakeProfitDetails specifies the details of a Take Profit Order to be
+    /// created on behalf of a client.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub take_profit_on_fill: Option<TakeProfitDetails>,
+    /// StopLossDetails specifies the details of a Stop Loss Order to be
+    /// created on behalf of a client.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub stop_loss_on_fill: Option<StopLossDetails>
+}
+
+impl LimitOrderRequest {
+    pub fn new(instrument: InstrumentName, units: DecimalNumber, price: PriceValue) -> LimitOrderRequest {
+        LimitOrderRequest {
+            order_type: OrderType::LIMIT,
+            instrument: instrument,
+            units: units,
+            price: price,
+            time_in_force: TimeInForce::GTC,
+            position_fill: OrderPositionFill::DEFAULT,
+            take_profit_on_fill: None,
+            stop_loss_on_fill: None
+        }
+    }
+
+    pub fn with_time_in_force(&mut self, time_in_force: TimeInForce) -> &mut LimitOrderRequest {
+        self.time_in_force = time_in_force;
+        self
+    }
+
+    pub fn with_position_fill(&mut self, position_fill: OrderPositionFill) -> &mut LimitOrderRequest {
+        self.position_fill = position_fill;
+        self
+    }
+
+    /// Create a Take Profit Order at `price` for the Trade that is opened
+    pub fn with_take_profit(&mut self, price: PriceValue) -> &mut LimitOrderRequest {
+        self.take_profit_on_fill = Some(TakeProfitDetails { price: price, time_in_force: TimeInForce::GTC });
+        self
+    }
+
+    /// Create a Stop Loss Order at `price` for the Trade that is opened
+    pub fn with_stop_loss(&mut self, price: PriceValue) -> &mut LimitOrderRequest {
+        self.stop_loss_on_fill = Some(StopLossDetails { price: price, time_in_force: TimeInForce::GTC });
+        self
+    }
+}
+
+impl OrderRequest for LimitOrderRequest {}
+
+/// A StopOrderRequest specifies the parameters that may be set when creating
+/// a Stop Order.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct StopOrderRequest {
+    /// The type of the Order to Create. Must be set to “STOP” when creating a
+    /// Stop Order.
+    #[serde(rename = "type")]
+    pub order_type: OrderType,
+    /// The Stop Order’s Instrument.
+    pub instrument: InstrumentName,
+    /// The quantity requested to be filled by the Stop Order. A positive
+    /// number of units results in a long Order, and a negative number of units
+    /// results in a short Order.
+    pub units: DecimalNumber,
+    /// The price threshold specified for the Stop Order. The Stop Order will
+    /// only be filled by a market price that is equal to or worse than this
+    /// price.
+    pub price: PriceValue,
+    /// The worst market price that may be used to fill this Stop Order. If the
+    /// market gaps and crosses through both the price and the priceBound, the
+    /// Stop Order will be cancelled instead of being filled.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub price_bound: Option<PriceValue>,
+    /// The time-in-force requested for the Stop Order. [default=GTC]
+    pub time_in_force: TimeInForce,
+    /// Specification of how Positions in the Account are modified when the
+    /// Order is filled. [default=DEFAULT]
+    pub position_fill: OrderPositionFill,
+    /// TakeProfitDetails specifies the details of a Take Profit Order to be
+    /// created on behalf of a client.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub take_profit_on_fill: Option<TakeProfitDetails>,
+    /// StopLossDetails specifies the details of a Stop Loss Order to be
+    /// created on behalf of a client.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub stop_loss_on_fill: Option<StopLossDetails>
+}
+
+impl StopOrderRequest {
+    pub fn new(instrument: InstrumentName, units: DecimalNumber, price: PriceValue) -> StopOrderRequest {
+        StopOrderRequest {
+            order_type: OrderType::STOP,
+            instrument: instrument,
+            units: units,
+            price: price,
+            price_bound: None,
+            time_in_force: TimeInForce::GTC,
+            position_fill: OrderPositionFill::DEFAULT,
+            take_profit_on_fill: None,
+            stop_loss_on_fill: None
+        }
+    }
+
+    pub fn with_price_bound(&mut self, price_bound: PriceValue) -> &mut StopOrderRequest {
+        self.price_bound = Some(price_bound);
+        self
+    }
+
+    pub fn with_time_in_force(&mut self, time_in_force: TimeInForce) -> &mut StopOrderRequest {
+        self.time_in_force = time_in_force;
+        self
+    }
+
+    pub fn with_position_fill(&mut self, position_fill: OrderPositionFill) -> &mut StopOrderRequest {
+        self.position_fill = position_fill;
+        self
+    }
+
+    /// Create a Take Profit Order at `price` for the Trade that is opened
+    pub fn with_take_profit(&mut self, price: PriceValue) -> &mut StopOrderRequest {
+        self.take_profit_on_fill = Some(TakeProfitDetails { price: price, time_in_force: TimeInForce::GTC });
+        self
+    }
+
+    /// Create a Stop Loss Order at `price` for the Trade that is opened
+    pub fn with_stop_loss(&mut self, price: PriceValue) -> &mut StopOrderRequest {
+        self.stop_loss_on_fill = Some(StopLossDetails { price: price, time_in_force: TimeInForce::GTC });
+        self
+    }
+}
+
+impl OrderRequest for StopOrderRequest {}
+
+/// The body of a request to create an Order
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct CreateOrderRequest<O> {
+    /// Specification of the Order to create
+    pub order: O
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct CreateOrderResponse {
+    /// The Transaction that created the Order specified by the request.
+    pub order_create_transaction: Transaction,
+    /// The Transaction that filled the newly created Order. Only provided
+    /// when the Order was immediately filled.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub order_fill_transaction: Option<Transaction>,
+    /// The Transaction that cancelled the newly created Order. Only provided
+    /// when the Order was immediately cancelled.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub order_cancel_transaction: Option<Transaction>,
+    /// The IDs of all Transactions that were created while satisfying the
+    /// request.
+    #[serde(rename = "relatedTransactionIDs")]
+    pub related_transaction_ids: Vec<String>,
+    /// The ID of the most recent Transaction created for the Account
+    #[serde(rename = "lastTransactionID")]
+    pub last_transaction_id: String
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct CancelOrderResponse {
+    /// The Transaction that cancelled the Order
+    pub order_cancel_transaction: Transaction,
+    /// The IDs of all Transactions that were created while satisfying the
+    /// request.
+    #[serde(rename = "relatedTransactionIDs")]
+    pub related_transaction_ids: Vec<String>,
+    /// The ID of the most recent Transaction created for the Account
+    #[serde(rename = "lastTransactionID")]
+    pub last_transaction_id: String
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json;
+
+    #[test]
+    fn it_writes_a_market_order_as_oanda_expects() {
+        let mut order = MarketOrderRequest::new("EUR_USD".parse().unwrap(), "-10000".parse().unwrap());
+        order.with_stop_loss("1.1200".parse().unwrap()).with_take_profit("1.1000".parse().unwrap());
+
+        assert_eq!(
+            serde_json::to_string(&CreateOrderRequest { order: order }).unwrap(),
+            concat!(
+                r#"{"order":{"type":"MARKET","instrument":"EUR_USD","units":"-10000","timeInForce":"FOK","#,
+                r#""positionFill":"DEFAULT","takeProfitOnFill":{"price":"1.1000","timeInForce":"GTC"},"#,
+                r#""stopLossOnFill":{"price":"1.1200","timeInForce":"GTC"}}}"#
+            )
+        );
+    }
+}
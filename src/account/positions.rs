@@ -0,0 +1,100 @@
+use primitives::DecimalNumber;
+use super::details::Position;
+use super::trades::CloseUnits;
+use super::transactions::Transaction;
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct AccountPosition {
+    /// The requested Position.
+    pub position: Position,
+    /// The ID of the most recent Transaction created for the Account
+    #[serde(rename = "lastTransactionID")]
+    pub last_transaction_id: String
+}
+
+/// The body of a request to close a Position
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct ClosePositionRequest {
+    /// Indication of how much of the long Position to closeout. Either the
+    /// string “ALL”, the string “NONE”, or a DecimalNumber representing how
+    /// many units of the long position to close using a PositionCloseout
+    /// MarketOrder. The units specified must always be positive.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub long_units: Option<CloseUnits>,
+    /// Indication of how much of the short Position to closeout. Either the
+    /// string “ALL”, the string “NONE”, or a DecimalNumber representing how
+    /// many units of the short position to close using a PositionCloseout
+    /// MarketOrder. The units specified must always be positive.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub short_units: Option<CloseUnits>
+}
+
+impl ClosePositionRequest {
+    /// Close every unit of each side of `position` that is open
+    pub fn all_of(position: &Position) -> ClosePositionRequest {
+        let side = |units: &DecimalNumber| {
+            if units.value().is_zero() { CloseUnits::None } else { CloseUnits::All }
+        };
+
+        ClosePositionRequest {
+            long_units: Some(side(&position.long.units)),
+            short_units: Some(side(&position.short.units))
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct ClosePositionResponse {
+    /// The MarketOrderTransaction created to close the long Position.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub long_order_create_transaction: Option<Transaction>,
+    /// OrderFill Transaction that closes the long Position
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub long_order_fill_transaction: Option<Transaction>,
+    /// OrderCancel Transaction that cancels the MarketOrder created to close
+    /// the long Position
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub long_order_cancel_transaction: Option<Transaction>,
+    /// The MarketOrderTransaction created to close the short Position.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub short_order_create_transaction: Option<Transaction>,
+    /// OrderFill Transaction that closes the short Position
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub short_order_fill_transaction: Option<Transaction>,
+    /// OrderCancel Transaction that cancels the MarketOrder created to close
+    /// the short Position
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub short_order_cancel_transaction: Option<Transaction>,
+    /// The IDs of all Transactions that were created while satisfying the
+    /// request.
+    #[serde(rename = "relatedTransactionIDs")]
+    pub related_transaction_ids: Vec<String>,
+    /// The ID of the most recent Transaction created for the Account
+    #[serde(rename = "lastTransactionID")]
+    pub last_transaction_id: String
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json;
+
+    #[test]
+    fn it_closes_only_the_open_sides() {
+        let position: Position = serde_json::from_str(r#"{
+            "instrument": "EUR_USD", "pl": "0.0000", "unrealizedPL": "-0.7000", "resettablePL": "0.0000",
+            "long": {"units": "10000", "averagePrice": "1.11450", "tradeIDs": ["7"], "pl": "0.0000",
+                     "unrealizedPL": "-0.7000", "resettablePL": "0.0000"},
+            "short": {"units": "0", "averagePrice": "0.00000", "tradeIDs": [], "pl": "0.0000",
+                      "unrealizedPL": "0.0000", "resettablePL": "0.0000"}
+        }"#).unwrap();
+
+        assert_eq!(
+            serde_json::to_string(&ClosePositionRequest::all_of(&position)).unwrap(),
+            r#"{"longUnits":"ALL","shortUnits":"NONE"}"#
+        );
+    }
+}
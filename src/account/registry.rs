@@ -0,0 +1,110 @@
+use std::collections::HashMap;
+use std::collections::hash_map::Values;
+
+use primitives::InstrumentName;
+
+use super::Account;
+use super::instruments::{Instrument, InstrumentType};
+
+/// The tradeable instruments of an Account, loaded once and kept in memory so
+/// they can be looked up by name without a request to Oanda
+#[derive(Clone, Debug)]
+pub struct InstrumentRegistry<'a> {
+    /// The Account the instruments belong to
+    account: Account<'a>,
+    /// The instruments, keyed by name
+    instruments: HashMap<InstrumentName, Instrument>
+}
+
+impl <'a>InstrumentRegistry<'a> {
+    /// Load the instruments of `account`
+    pub fn load(account: &Account<'a>) -> InstrumentRegistry<'a> {
+        let instruments = account.instruments();
+
+        InstrumentRegistry::from_instruments(account.clone(), instruments)
+    }
+
+    fn from_instruments(account: Account<'a>, instruments: Vec<Instrument>) -> InstrumentRegistry<'a> {
+        InstrumentRegistry {
+            account: account,
+            instruments: instruments.into_iter().map(|i| (i.name.clone(), i)).collect()
+        }
+    }
+
+    /// Load the instruments from Oanda again, replacing the ones held
+    pub fn refresh(&mut self) {
+        let instruments = self.account.instruments();
+
+        self.instruments = instruments.into_iter().map(|i| (i.name.clone(), i)).collect();
+    }
+
+    /// The instrument named `name`, e.g. “EUR_USD”
+    pub fn get(&self, name: &str) -> Option<&Instrument> {
+        self.instruments.get(name)
+    }
+
+    /// Every instrument of the given type, ordered by name
+    pub fn of_type(&self, instrument_type: InstrumentType) -> Vec<&Instrument> {
+        let mut result: Vec<&Instrument> = self.instruments
+            .values()
+            .filter(|i| i.instrument_type == instrument_type)
+            .collect();
+        result.sort_by(|a, b| a.name.cmp(&b.name));
+
+        result
+    }
+
+    /// Every instrument, in no particular order
+    pub fn iter(&self) -> Values<InstrumentName, Instrument> {
+        self.instruments.values()
+    }
+
+    pub fn len(&self) -> usize {
+        self.instruments.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.instruments.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json;
+
+    fn instrument(name: &str, instrument_type: &str) -> Instrument {
+        serde_json::from_str(&format!(r#"{{
+            "name": "{}",
+            "type": "{}",
+            "displayName": "{}",
+            "pipLocation": -4,
+            "displayPrecision": 5,
+            "tradeUnitsPrecision": 0,
+            "minimumTradeSize": "1",
+            "maximumTrailingStopDistance": "1.00000",
+            "minimumTrailingStopDistance": "0.00050",
+            "maximumPositionSize": "0",
+            "maximumOrderUnits": "100000000",
+            "marginRate": "0.05"
+        }}"#, name, instrument_type, name)).unwrap()
+    }
+
+    #[test]
+    fn it_looks_up_instruments() {
+        let account = Account::new("101-001-1-001", None);
+        let registry = InstrumentRegistry::from_instruments(account, vec![
+            instrument("XAU_USD", "METAL"),
+            instrument("USD_JPY", "CURRENCY"),
+            instrument("EUR_USD", "CURRENCY")
+        ]);
+
+        assert_eq!(registry.len(), 3);
+        assert_eq!(registry.get("USD_JPY").unwrap().name, "USD_JPY");
+        assert!(registry.get("GBP_USD").is_none());
+        assert_eq!(
+            registry.of_type(InstrumentType::CURRENCY).iter().map(|i| i.name.as_str()).collect::<Vec<_>>(),
+            vec!["EUR_USD", "USD_JPY"]
+        );
+    }
+}
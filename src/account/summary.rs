@@ -1,19 +1,22 @@
 use chrono::datetime::DateTime;
 use chrono::UTC;
 
-#[derive(Deserialize)]
+use primitives::{AccountUnits, Currency, DecimalNumber};
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
 #[serde(rename_all = "camelCase")]
 pub struct Summary {
     /// The Account’s identifier
     pub id: String,
     /// Client-assigned alias for the Account. Only provided if the Account has
     /// an alias set
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub alias: Option<String>,
     /// The home currency of the Account
-    pub currency: String,
+    pub currency: Currency,
     /// The current balance of the Account. Represented in the Account’s home
     /// currency.
-    pub balance: f32,
+    pub balance: AccountUnits,
     /// ID of the user that created the Account.
     #[serde(rename = "createdByUserID")]
     pub created_by_user_id: i32,
@@ -21,25 +24,30 @@ pub struct Summary {
     pub created_time: DateTime<UTC>,
     /// The total profit/loss realized over the lifetime of the Account.
     /// Represented in the Account’s home currency.
-    pub pl: f32,
+    pub pl: AccountUnits,
     /// The total realized profit/loss for the Account since it was last reset by
     /// the client. Represented in the Account’s home currency.
     #[serde(rename = "resettablePL")]
-    pub resettable_pl: f32,
+    pub resettable_pl: AccountUnits,
     /// The date/time that the Account’s resettablePL was last reset.
-    #[serde(rename = "resettabledPLTimelast")]
+    #[serde(rename = "resettablePLTime")]
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub resettabled_pl_time: Option<DateTime<UTC>>,
     /// Client-provided margin rate override for the Account. The effective
     /// margin rate of the Account is the lesser of this value and the OANDA
     /// margin rate for the Account’s division. This value is only provided if a
     /// margin rate override exists for the Account.
-    pub margin_rate: Option<f32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub margin_rate: Option<DecimalNumber>,
     /// The date/time when the Account entered a margin call state. Only provided
     /// if the Account is in a margin call.
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub margin_call_enter_time: Option<DateTime<UTC>>,
     /// The number of times that the Account’s current margin call was extended.
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub margin_call_extension_count: Option<i32>,
     /// The date/time of the Account’s last margin call extension.
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub last_margin_call_extension_time: Option<DateTime<UTC>>,
     /// The number of Trades currently open in the Account.
     pub open_trade_count: i32,
@@ -52,47 +60,47 @@ pub struct Summary {
     /// The total unrealized profit/loss for all Trades currently open in the
     /// Account. Represented in the Account’s home currency.
     #[serde(rename = "unrealizedPL")]
-    pub unrealized_pl: f32,
+    pub unrealized_pl: AccountUnits,
     /// The net asset value of the Account. Equal to Account balance +
     /// unrealizedPL. Represented in the Account’s home currency.
     #[serde(rename = "NAV")]
-    pub nav: f32,
+    pub nav: AccountUnits,
     /// Margin currently used for the Account. Represented in the Account’s home
     /// currency.
-    pub margin_used: f32,
+    pub margin_used: AccountUnits,
     /// Margin available for Account. Represented in the Account’s home currency.
-    pub margin_available: f32,
+    pub margin_available: AccountUnits,
     /// The value of the Account’s open positions represented in the Account’s
     /// home currency.
-    pub position_value: f32,
+    pub position_value: AccountUnits,
     /// The Account’s margin closeout unrealized PL.
     #[serde(rename = "marginCloseoutUnrealizedPL")]
-    pub margin_closeout_unrealized_pl: f32,
+    pub margin_closeout_unrealized_pl: AccountUnits,
     /// The Account’s margin closeout NAV.
     #[serde(rename = "marginCloseoutNAV")]
-    pub margin_closeout_nav: f32,
+    pub margin_closeout_nav: AccountUnits,
     /// The Account’s margin closeout margin used.
-    pub margin_closeout_margin_used: f32,
+    pub margin_closeout_margin_used: AccountUnits,
     /// The Account’s margin closeout percentage. When this value is 1.0 or above
     /// the Account is in a margin closeout situation.
-    pub margin_closeout_percent: f32,
+    pub margin_closeout_percent: DecimalNumber,
     /// The value of the Account’s open positions as used for margin closeout
     /// calculations represented in the Account’s home currency.
-    pub margin_closeout_position_value: f32,
+    pub margin_closeout_position_value: AccountUnits,
     /// The current WithdrawalLimit for the account which will be zero or a
     /// positive value indicating how much can be withdrawn from the account.
-    pub withdrawal_limit: f32,
+    pub withdrawal_limit: AccountUnits,
     /// The Account’s margin call margin used.
-    pub margin_call_margin_used: f32,
+    pub margin_call_margin_used: AccountUnits,
     /// The Account’s margin call percentage. When this value is 1.0 or above the
     /// Account is in a margin call situation.
-    pub margin_call_percent: f32,
+    pub margin_call_percent: DecimalNumber,
     /// The ID of the last Transaction created for the Account.
     #[serde(rename = "lastTransactionID")]
     pub last_transaction_id: String
 }
 
-#[derive(Deserialize)]
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
 pub struct AccountSummary {
     /// The full details of the requested Account.
     pub account: Summary,
@@ -100,3 +108,35 @@ pub struct AccountSummary {
     #[serde(rename = "lastTransactionID")]
     pub last_transaction_id: String
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json;
+
+    #[test]
+    fn it_reads_when_the_resettable_pl_was_reset() {
+        let input = r#"{
+            "account": {
+                "id": "101-004-1435156-001", "alias": "Testv20", "currency": "USD", "balance": "100000.0000",
+                "createdByUserID": 1435156, "createdTime": "2017-06-01T14:22:36.123456789Z",
+                "pl": "0.0000", "resettablePL": "0.0000", "resettablePLTime": "2017-06-02T09:00:00.123456789Z",
+                "openTradeCount": 0, "openPositionCount": 0, "pendingOrderCount": 0, "hedgingEnabled": false,
+                "unrealizedPL": "0.0000", "NAV": "100000.0000", "marginUsed": "0.0000",
+                "marginAvailable": "100000.0000", "positionValue": "0.0000",
+                "marginCloseoutUnrealizedPL": "0.0000", "marginCloseoutNAV": "100000.0000",
+                "marginCloseoutMarginUsed": "0.0000", "marginCloseoutPercent": "0.00000",
+                "marginCloseoutPositionValue": "0.0000", "withdrawalLimit": "100000.0000",
+                "marginCallMarginUsed": "0.0000", "marginCallPercent": "0.00000",
+                "lastTransactionID": "3"
+            },
+            "lastTransactionID": "3"
+        }"#;
+        let summary: AccountSummary = serde_json::from_str(input).unwrap();
+        let output = serde_json::to_string(&summary).unwrap();
+
+        assert!(summary.account.resettabled_pl_time.is_some());
+        assert!(output.contains(r#""resettablePLTime":"2017-06-02T09:00:00.123456789Z""#));
+        assert_eq!(serde_json::from_str::<AccountSummary>(&output).unwrap(), summary);
+    }
+}
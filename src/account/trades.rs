@@ -0,0 +1,108 @@
+use std::fmt;
+use std::str::FromStr;
+
+use serde::{de, Deserialize, Deserializer, Serialize, Serializer};
+
+use primitives::{DecimalNumber, ParseError};
+use super::transactions::Transaction;
+
+/// How much of a Trade or Position side to close
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum CloseUnits {
+    /// Close everything, “ALL”
+    All,
+    /// Close nothing, “NONE”. Only allowed for Positions.
+    None,
+    /// Close this many units, which must be positive
+    Units(DecimalNumber)
+}
+
+impl fmt::Display for CloseUnits {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            CloseUnits::All => f.write_str("ALL"),
+            CloseUnits::None => f.write_str("NONE"),
+            CloseUnits::Units(units) => units.fmt(f)
+        }
+    }
+}
+
+impl FromStr for CloseUnits {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<CloseUnits, ParseError> {
+        match s {
+            "ALL" => Ok(CloseUnits::All),
+            "NONE" => Ok(CloseUnits::None),
+            _ => s.parse()
+                .map(CloseUnits::Units)
+                .map_err(|_| ParseError { kind: "units to close", value: s.to_string() })
+        }
+    }
+}
+
+impl Serialize for CloseUnits {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+        where S: Serializer
+    {
+        serializer.serialize_str(&self.to_string())
+    }
+}
+
+impl Deserialize for CloseUnits {
+    fn deserialize<D>(deserializer: D) -> Result<CloseUnits, D::Error>
+        where D: Deserializer
+    {
+        let value = String::deserialize(deserializer)?;
+
+        value.parse().map_err(|e: ParseError| de::Error::custom(e.to_string()))
+    }
+}
+
+/// The body of a request to close a Trade
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct CloseTradeRequest {
+    /// Indication of how much of the Trade to close. Either the string “ALL”
+    /// (indicating that all of the Trade should be closed), or a
+    /// DecimalNumber representing the number of units of the open Trade to
+    /// Close using a TradeClose MarketOrder. [default=ALL]
+    pub units: CloseUnits
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct CloseTradeResponse {
+    /// The MarketOrder Transaction created to close the Trade.
+    pub order_create_transaction: Transaction,
+    /// The OrderFill Transaction that fills the Trade-closing MarketOrder and
+    /// closes the Trade.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub order_fill_transaction: Option<Transaction>,
+    /// The OrderCancel Transaction that immediately cancelled the
+    /// Trade-closing MarketOrder.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub order_cancel_transaction: Option<Transaction>,
+    /// The IDs of all Transactions that were created while satisfying the
+    /// request.
+    #[serde(rename = "relatedTransactionIDs")]
+    pub related_transaction_ids: Vec<String>,
+    /// The ID of the most recent Transaction created for the Account
+    #[serde(rename = "lastTransactionID")]
+    pub last_transaction_id: String
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json;
+
+    #[test]
+    fn it_writes_units_to_close_as_strings() {
+        let partial = CloseTradeRequest { units: "5000".parse().unwrap() };
+
+        assert_eq!(serde_json::to_string(&partial).unwrap(), r#"{"units":"5000"}"#);
+        assert_eq!(serde_json::to_string(&CloseTradeRequest { units: CloseUnits::All }).unwrap(), r#"{"units":"ALL"}"#);
+        assert_eq!(serde_json::from_str::<CloseTradeRequest>(r#"{"units":"5000"}"#).unwrap(), partial);
+        assert!("some".parse::<CloseUnits>().is_err());
+    }
+}
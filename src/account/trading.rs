@@ -0,0 +1,77 @@
+use error::Result;
+use primitives::InstrumentName;
+
+use super::Account;
+use super::details::{Details, Position};
+use super::orders::{CancelOrderResponse, CreateOrderRequest, CreateOrderResponse, OrderRequest};
+use super::positions::{ClosePositionRequest, ClosePositionResponse};
+use super::summary::Summary;
+use super::trades::{CloseTradeRequest, CloseTradeResponse};
+
+/// The trading operations of an Account, implemented by a real `Account` and
+/// by `PaperAccount`, so code written against it can trade either one
+pub trait TradingAccount {
+    /// The Account's identifier
+    fn id(&self) -> &str;
+
+    fn try_summary(&self) -> Result<Summary>;
+
+    fn try_details(&self) -> Result<Details>;
+
+    /// Get the Position for `instrument`
+    fn try_position(&self, instrument: &InstrumentName) -> Result<Position>;
+
+    /// Create an Order, e.g. a `MarketOrderRequest`
+    fn try_create_order<O: OrderRequest>(&self, request: &CreateOrderRequest<O>) -> Result<CreateOrderResponse>;
+
+    /// Cancel a pending Order
+    fn try_cancel_order(&self, order_id: &str) -> Result<CancelOrderResponse>;
+
+    /// Close (partially or fully) an open Trade
+    fn try_close_trade(&self, trade_id: &str, request: &CloseTradeRequest) -> Result<CloseTradeResponse>;
+
+    /// Closeout the open Position for `instrument`
+    fn try_close_position(
+        &self,
+        instrument: &InstrumentName,
+        request: &ClosePositionRequest
+    ) -> Result<ClosePositionResponse>;
+}
+
+impl<'a> TradingAccount for Account<'a> {
+    fn id(&self) -> &str {
+        &self.id
+    }
+
+    fn try_summary(&self) -> Result<Summary> {
+        Account::try_summary(self)
+    }
+
+    fn try_details(&self) -> Result<Details> {
+        Account::try_details(self)
+    }
+
+    fn try_position(&self, instrument: &InstrumentName) -> Result<Position> {
+        Account::try_position(self, instrument)
+    }
+
+    fn try_create_order<O: OrderRequest>(&self, request: &CreateOrderRequest<O>) -> Result<CreateOrderResponse> {
+        Account::try_create_order(self, request)
+    }
+
+    fn try_cancel_order(&self, order_id: &str) -> Result<CancelOrderResponse> {
+        Account::try_cancel_order(self, order_id)
+    }
+
+    fn try_close_trade(&self, trade_id: &str, request: &CloseTradeRequest) -> Result<CloseTradeResponse> {
+        Account::try_close_trade(self, trade_id, request)
+    }
+
+    fn try_close_position(
+        &self,
+        instrument: &InstrumentName,
+        request: &ClosePositionRequest
+    ) -> Result<ClosePositionResponse> {
+        Account::try_close_position(self, instrument, request)
+    }
+}
@@ -0,0 +1,205 @@
+use chrono::datetime::DateTime;
+use chrono::UTC;
+
+use primitives::{AccountUnits, DecimalNumber, InstrumentName, PriceValue};
+
+open_enum! {
+    /// The possible types of a Transaction
+    pub enum TransactionType {
+        /// Account Create Transaction
+        CREATE,
+        /// Account Close Transaction
+        CLOSE,
+        /// Account Reopen Transaction
+        REOPEN,
+        /// Client Configuration Transaction
+        CLIENT_CONFIGURE,
+        /// Client Configuration Reject Transaction
+        CLIENT_CONFIGURE_REJECT,
+        /// Transfer Funds Transaction
+        TRANSFER_FUNDS,
+        /// Transfer Funds Reject Transaction
+        TRANSFER_FUNDS_REJECT,
+        /// Market Order Transaction
+        MARKET_ORDER,
+        /// Market Order Reject Transaction
+        MARKET_ORDER_REJECT,
+        /// Fixed Price Order Transaction
+        FIXED_PRICE_ORDER,
+        /// Limit Order Transaction
+        LIMIT_ORDER,
+        /// Limit Order Reject Transaction
+        LIMIT_ORDER_REJECT,
+        /// Stop Order Transaction
+        STOP_ORDER,
+        /// Stop Order Reject Transaction
+        STOP_ORDER_REJECT,
+        /// Market if Touched Order Transaction
+        MARKET_IF_TOUCHED_ORDER,
+        /// Market if Touched Order Reject Transaction
+        MARKET_IF_TOUCHED_ORDER_REJECT,
+        /// Take Profit Order Transaction
+        TAKE_PROFIT_ORDER,
+        /// Take Profit Order Reject Transaction
+        TAKE_PROFIT_ORDER_REJECT,
+        /// Stop Loss Order Transaction
+        STOP_LOSS_ORDER,
+        /// Stop Loss Order Reject Transaction
+        STOP_LOSS_ORDER_REJECT,
+        /// Trailing Stop Loss Order Transaction
+        TRAILING_STOP_LOSS_ORDER,
+        /// Trailing Stop Loss Order Reject Transaction
+        TRAILING_STOP_LOSS_ORDER_REJECT,
+        /// Order Fill Transaction
+        ORDER_FILL,
+        /// Order Cancel Transaction
+        ORDER_CANCEL,
+        /// Order Cancel Reject Transaction
+        ORDER_CANCEL_REJECT,
+        /// Order Client Extensions Modify Transaction
+        ORDER_CLIENT_EXTENSIONS_MODIFY,
+        /// Order Client Extensions Modify Reject Transaction
+        ORDER_CLIENT_EXTENSIONS_MODIFY_REJECT,
+        /// Trade Client Extensions Modify Transaction
+        TRADE_CLIENT_EXTENSIONS_MODIFY,
+        /// Trade Client Extensions Modify Reject Transaction
+        TRADE_CLIENT_EXTENSIONS_MODIFY_REJECT,
+        /// Margin Call Enter Transaction
+        MARGIN_CALL_ENTER,
+        /// Margin Call Extend Transaction
+        MARGIN_CALL_EXTEND,
+        /// Margin Call Exit Transaction
+        MARGIN_CALL_EXIT,
+        /// Delayed Trade Closure Transaction
+        DELAYED_TRADE_CLOSURE,
+        /// Daily Financing Transaction
+        DAILY_FINANCING,
+        /// Reset Resettable PL Transaction
+        RESET_RESETTABLE_PL
+    }
+}
+
+/// A Trade opened by an Order fill
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct TradeOpen {
+    /// The ID of the Trade that was opened
+    #[serde(rename = "tradeID")]
+    pub trade_id: String,
+    /// The number of units opened by the Trade
+    pub units: DecimalNumber,
+    /// The average price that the units were opened at.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub price: Option<PriceValue>
+}
+
+/// A Trade closed or reduced by an Order fill
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct TradeReduce {
+    /// The ID of the Trade that was reduced or closed
+    #[serde(rename = "tradeID")]
+    pub trade_id: String,
+    /// The number of units that the Trade was reduced by
+    pub units: DecimalNumber,
+    /// The average price that the units were closed at.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub price: Option<PriceValue>,
+    /// The PL realized when reducing the Trade
+    #[serde(rename = "realizedPL")]
+    pub realized_pl: AccountUnits,
+    /// The financing paid/collected when reducing the Trade
+    pub financing: AccountUnits
+}
+
+/// A Transaction of any type.
+///
+/// Every Transaction has the fields up to `transaction_type`. The remaining
+/// fields are those most Transaction types that refer to an Order, Trade or
+/// balance change share, and are only provided by the types that have them.
+/// Fields only a few Transaction types have are ignored.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct Transaction {
+    /// The Transaction’s Identifier.
+    pub id: String,
+    /// The date/time when the Transaction was created.
+    pub time: DateTime<UTC>,
+    /// The ID of the user that initiated the creation of the Transaction.
+    #[serde(rename = "userID")]
+    pub user_id: i32,
+    /// The ID of the Account the Transaction was created for.
+    #[serde(rename = "accountID")]
+    pub account_id: String,
+    /// The ID of the “batch” that the Transaction belongs to. Transactions in
+    /// the same batch are applied to the Account simultaneously.
+    #[serde(rename = "batchID")]
+    pub batch_id: String,
+    /// The Request ID of the request which generated the transaction.
+    #[serde(rename = "requestID")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub request_id: Option<String>,
+    /// The Type of the Transaction.
+    #[serde(rename = "type")]
+    pub transaction_type: TransactionType,
+    /// The Instrument of the Order or Trade.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub instrument: Option<InstrumentName>,
+    /// The number of units of the Order or fill. Negative for a short Order.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub units: Option<DecimalNumber>,
+    /// The price of the Order or fill.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub price: Option<PriceValue>,
+    /// The reason that the Transaction was created.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub reason: Option<String>,
+    /// The ID of the Order the Transaction filled or cancelled.
+    #[serde(rename = "orderID")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub order_id: Option<String>,
+    /// The ID of the Trade an Order to close or protect a Trade refers to.
+    #[serde(rename = "tradeID")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub trade_id: Option<String>,
+    /// The profit or loss realized by the Transaction.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub pl: Option<AccountUnits>,
+    /// The financing paid or collected by the Transaction.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub financing: Option<AccountUnits>,
+    /// The Account’s balance after the Transaction was applied.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub account_balance: Option<AccountUnits>,
+    /// The Trade that was opened by a fill.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub trade_opened: Option<TradeOpen>,
+    /// The Trades that were closed by a fill.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub trades_closed: Option<Vec<TradeReduce>>,
+    /// The Trade that was reduced by a fill.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub trade_reduced: Option<TradeReduce>
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json;
+
+    #[test]
+    fn it_reads_an_order_fill() {
+        let fill: Transaction = serde_json::from_str(r#"{
+            "id": "7", "time": "2017-06-21T12:00:00.123456789Z", "userID": 1435156,
+            "accountID": "101-004-1435156-001", "batchID": "6", "requestID": "42",
+            "type": "ORDER_FILL", "orderID": "6", "instrument": "EUR_USD", "units": "10000",
+            "price": "1.11450", "reason": "MARKET_ORDER", "pl": "0.0000", "financing": "0.0000",
+            "accountBalance": "100000.0000", "halfSpreadCost": "0.7000",
+            "tradeOpened": {"tradeID": "7", "units": "10000", "price": "1.11450"}
+        }"#).unwrap();
+
+        assert_eq!(fill.transaction_type, TransactionType::ORDER_FILL);
+        assert_eq!(fill.trade_opened.as_ref().unwrap().trade_id, "7");
+        assert_eq!(serde_json::from_str::<Transaction>(&serde_json::to_string(&fill).unwrap()).unwrap(), fill);
+    }
+}
@@ -0,0 +1,259 @@
+//! Replay candlesticks into a strategy trading a `PaperAccount`.
+//!
+//! A `Backtest` merges the candlesticks of every instrument it is given by
+//! time, moves the paper Account through each one, so pending Orders fill
+//! against the candlestick's bid and ask, and then hands the closed
+//! candlestick to the `Strategy`. Market Orders the strategy creates fill at
+//! the candlestick's closing bid or ask. The strategy is also handed every
+//! Transaction the Account creates, and its timer ticks as the candlesticks
+//! pass them, so the same `Strategy` runs live with a `strategy::Runtime`.
+//! The candlesticks can come from a
+//! `PricingQuery`, a `CandleStore` or a file read with `export::read_csv`:
+//!
+//! ```no_run
+//! # use oandars::account::orders::{CreateOrderRequest, MarketOrderRequest};
+//! # use oandars::account::trading::TradingAccount;
+//! # use oandars::backtest::Backtest;
+//! # use oandars::client::Client;
+//! # use oandars::error::Result;
+//! # use oandars::instrument::candlestick::Candlestick;
+//! # use oandars::instrument::candlestick_granularity::CandlestickGranularity;
+//! # use oandars::instrument::price_components::PriceComponents;
+//! # use oandars::account::instruments::Instrument;
+//! # use oandars::paper::PaperAccount;
+//! # use oandars::primitives::InstrumentName;
+//! # use oandars::strategy::Strategy;
+//! # extern crate chrono;
+//! # extern crate oandars;
+//! # use chrono::{TimeZone, UTC};
+//! # fn main() {
+//! struct BuyAndHold;
+//!
+//! impl Strategy for BuyAndHold {
+//!     fn on_candle_close<A: TradingAccount>(&mut self, account: &A, instrument: &InstrumentName, _: &Candlestick) {
+//!         if account.try_summary().unwrap().open_trade_count == 0 {
+//!             let order = MarketOrderRequest::new(instrument.clone(), "1000".parse().unwrap());
+//!             account.try_create_order(&CreateOrderRequest { order: order }).unwrap();
+//!         }
+//!     }
+//! }
+//!
+//! let client = Client::new("https://api-fxpractice.oanda.com/v3", "key");
+//! let eur_usd: InstrumentName = "EUR_USD".parse().unwrap();
+//! let (from, to) = (UTC.ymd(2017, 1, 1).and_hms(0, 0, 0), UTC.ymd(2018, 1, 1).and_hms(0, 0, 0));
+//! let mut query = client.pricing_for(eur_usd.clone(), from);
+//! query.with_granularity(CandlestickGranularity::H1).with_price(PriceComponents::BID | PriceComponents::ASK);
+//!
+//! let mut account = PaperAccount::new("backtest", "USD".parse().unwrap(), "10000".parse().unwrap());
+//! account.with_instrument(Instrument::currency_pair(eur_usd.clone()));
+//!
+//! let report = Backtest::new(account, BuyAndHold)
+//!     .with_candles(eur_usd, query.candles_between(from, to).collect::<Result<Vec<_>>>().unwrap())
+//!     .run();
+//!
+//! println!("return {:.2}%, max drawdown {:.2}%", report.total_return() * 100.0, report.max_drawdown() * 100.0);
+//! # }
+//! ```
+
+mod report;
+
+use std::mem;
+
+use chrono::Duration;
+
+use instrument::candlestick::Candlestick;
+use paper::PaperAccount;
+use primitives::InstrumentName;
+use strategy::Strategy;
+
+pub use self::report::{EquityPoint, Report};
+
+/// A replay of candlesticks into a strategy
+pub struct Backtest<S> {
+    account: PaperAccount,
+    strategy: S,
+    candles: Vec<(InstrumentName, Candlestick)>,
+    timer: Option<Duration>,
+    /// How many of the Account's Transactions the strategy was handed
+    delivered: usize
+}
+
+impl<S: Strategy> Backtest<S> {
+    /// A backtest of `strategy` trading `account`, which must be allowed to
+    /// trade every instrument it is given candlesticks of
+    pub fn new(account: PaperAccount, strategy: S) -> Backtest<S> {
+        Backtest { account: account, strategy: strategy, candles: Vec::new(), timer: None, delivered: 0 }
+    }
+
+    /// Replay the complete candlesticks of `instrument`. Candlesticks with
+    /// bid and ask prices give fills the spread, others fill at their mid
+    /// prices.
+    pub fn with_candles<I>(&mut self, instrument: InstrumentName, candles: I) -> &mut Backtest<S>
+        where I: IntoIterator<Item = Candlestick>
+    {
+        self.candles.extend(candles.into_iter().filter(|c| c.complete).map(|c| (instrument.clone(), c)));
+        self
+    }
+
+    /// Call `Strategy::on_timer` every `interval` from the time of the first
+    /// candlestick, before the first candlestick starting at or after each
+    /// tick
+    pub fn with_timer(&mut self, interval: Duration) -> &mut Backtest<S> {
+        self.timer = Some(interval);
+        self
+    }
+
+    pub fn account(&self) -> &PaperAccount {
+        &self.account
+    }
+
+    pub fn strategy(&self) -> &S {
+        &self.strategy
+    }
+
+    /// Replay every candlestick given so far, oldest first, and report how
+    /// the Account did. The Account is valued after the candlesticks of all
+    /// instruments starting at the same time.
+    pub fn run(&mut self) -> Report {
+        let mut candles = mem::replace(&mut self.candles, Vec::new());
+        candles.sort_by_key(|&(_, ref candle)| candle.time);
+
+        let initial_balance = self.account.summary().balance.value();
+        let mut equity = Vec::new();
+        let mut due = candles.first().and_then(|&(_, ref first)| self.timer.map(|interval| first.time + interval));
+
+        for (index, &(ref instrument, ref candle)) in candles.iter().enumerate() {
+            while let (Some(time), Some(interval)) = (due, self.timer) {
+                if time > candle.time {
+                    break
+                }
+                self.strategy.on_timer(&self.account, time);
+                self.deliver_transactions();
+                due = Some(time + interval);
+            }
+
+            self.account.update_candle(instrument, candle);
+            self.deliver_transactions();
+            self.strategy.on_candle_close(&self.account, instrument, candle);
+            self.deliver_transactions();
+
+            if candles.get(index + 1).map_or(true, |&(_, ref next)| next.time != candle.time) {
+                let summary = self.account.summary();
+
+                equity.push(EquityPoint { time: candle.time, balance: summary.balance.value(), nav: summary.nav.value() });
+            }
+        }
+
+        let mut trades = self.account.closed_trades();
+        trades.extend(self.account.details().trades);
+
+        Report {
+            initial_balance: initial_balance,
+            equity: equity,
+            trades: trades,
+            transactions: self.account.transactions()
+        }
+    }
+
+    /// Hand the strategy the Transactions it has not seen yet, including those
+    /// created while it handles them
+    fn deliver_transactions(&mut self) {
+        loop {
+            let transactions = self.account.transactions();
+            if transactions.len() <= self.delivered {
+                return
+            }
+
+            for transaction in &transactions[self.delivered..] {
+                self.delivered += 1;
+                self.strategy.on_transaction(&self.account, transaction);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use account::orders::{CreateOrderRequest, MarketOrderRequest};
+    use account::trades::{CloseTradeRequest, CloseUnits};
+    use account::trading::TradingAccount;
+    use account::transactions::Transaction;
+    use chrono::{TimeZone, UTC};
+    use chrono::datetime::DateTime;
+    use instrument::candlestick_data::CandlestickData;
+    use account::instruments::Instrument;
+    use primitives::{Decimal, PriceValue};
+
+    /// Buys on the first candlestick and closes after `hold` candlesticks,
+    /// again and again
+    struct Flip {
+        hold: usize,
+        seen: usize,
+        transactions: usize,
+        timers: Vec<DateTime<UTC>>
+    }
+
+    impl Strategy for Flip {
+        fn on_candle_close<A: TradingAccount>(&mut self, account: &A, instrument: &InstrumentName, _: &Candlestick) {
+            if self.seen % self.hold == 0 {
+                for trade in account.try_details().unwrap().trades {
+                    account.try_close_trade(&trade.id, &CloseTradeRequest { units: CloseUnits::All }).unwrap();
+                }
+                let order = MarketOrderRequest::new(instrument.clone(), "10000".parse().unwrap());
+                account.try_create_order(&CreateOrderRequest { order: order }).unwrap();
+            }
+            self.seen += 1;
+        }
+
+        fn on_transaction<A: TradingAccount>(&mut self, _: &A, _: &Transaction) {
+            self.transactions += 1;
+        }
+
+        fn on_timer<A: TradingAccount>(&mut self, _: &A, time: DateTime<UTC>) {
+            self.timers.push(time);
+        }
+    }
+
+    fn candles(closes: &[&str]) -> Vec<Candlestick> {
+        closes.iter().enumerate().map(|(hour, close)| {
+            let bid: PriceValue = close.parse().unwrap();
+            let ask = PriceValue::from(bid.value() + "0.0002".parse::<Decimal>().unwrap());
+
+            Candlestick {
+                time: UTC.ymd(2017, 6, 21).and_hms(0, 0, 0) + Duration::hours(hour as i64),
+                bid: Some(CandlestickData { o: bid, h: bid, l: bid, c: bid }),
+                ask: Some(CandlestickData { o: ask, h: ask, l: ask, c: ask }),
+                mid: None,
+                volume: 1,
+                complete: true
+            }
+        }).collect()
+    }
+
+    #[test]
+    fn it_replays_candles_into_the_strategy() {
+        let eur_usd: InstrumentName = "EUR_USD".parse().unwrap();
+        let mut account = PaperAccount::new("backtest", "USD".parse().unwrap(), "10000".parse().unwrap());
+        account.with_instrument(Instrument::currency_pair(eur_usd.clone()));
+
+        let mut backtest = Backtest::new(account, Flip { hold: 2, seen: 0, transactions: 0, timers: Vec::new() });
+        backtest
+            .with_candles(eur_usd, candles(&["1.10000", "1.10100", "1.10200", "1.10000", "1.09900", "1.10000"]))
+            .with_timer(Duration::hours(2));
+        let report = backtest.run();
+
+        // Bought at 1.10020, 1.10220 and 1.09920, sold at 1.10200 and 1.09900
+        assert_eq!(report.equity.len(), 6);
+        assert_eq!(report.trades.len(), 3);
+        assert_eq!(report.trades[0].realized_pl, "18.0000".parse().unwrap());
+        assert_eq!(report.trades[1].realized_pl, "-32.0000".parse().unwrap());
+        assert_eq!(report.win_rate(), Some(0.5));
+        assert_eq!(report.equity[5].balance, "9986.0000".parse().unwrap());
+        assert_eq!(report.equity[5].nav, "9994.0000".parse().unwrap());
+        assert_eq!(backtest.strategy().seen, 6);
+        assert_eq!(backtest.strategy().transactions, report.transactions.len());
+        let start = UTC.ymd(2017, 6, 21).and_hms(0, 0, 0);
+        assert_eq!(backtest.strategy().timers, vec![start + Duration::hours(2), start + Duration::hours(4)]);
+    }
+}
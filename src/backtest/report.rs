@@ -0,0 +1,171 @@
+use chrono::datetime::DateTime;
+use chrono::UTC;
+
+use account::details::{TradeState, TradeSummary};
+use account::transactions::Transaction;
+use instrument::alignment::Alignment;
+use primitives::{Decimal, DecimalNumber};
+
+/// The number of trading days in a year, used to annualize the Sharpe ratio
+const TRADING_DAYS: f64 = 252.0;
+
+/// The balance and NAV of the Account after the candlesticks starting at
+/// `time`
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct EquityPoint {
+    pub time: DateTime<UTC>,
+    pub balance: Decimal,
+    pub nav: Decimal
+}
+
+/// The result of a backtest
+#[derive(Clone, Debug, PartialEq)]
+pub struct Report {
+    /// The balance of the Account before the first candlestick
+    pub initial_balance: Decimal,
+    /// The equity curve, one point per candlestick start time
+    pub equity: Vec<EquityPoint>,
+    /// The trade log: every Trade closed during the backtest in the order
+    /// they were closed, then the Trades still open at the end
+    pub trades: Vec<TradeSummary>,
+    /// Every Transaction of the Account
+    pub transactions: Vec<Transaction>
+}
+
+impl Report {
+    /// The NAV at the end of the backtest
+    pub fn final_nav(&self) -> Decimal {
+        self.equity.last().map_or(self.initial_balance, |point| point.nav)
+    }
+
+    /// The change of the NAV over the backtest, as a fraction of the initial
+    /// balance
+    pub fn total_return(&self) -> f64 {
+        ratio(self.final_nav() - self.initial_balance, self.initial_balance)
+    }
+
+    /// The fall of the NAV from its highest point so far at each point of the
+    /// equity curve, as a fraction of that highest point
+    pub fn drawdown(&self) -> Vec<f64> {
+        let mut peak = self.initial_balance;
+
+        self.equity.iter().map(|point| {
+            peak = peak.max(point.nav);
+
+            ratio(peak - point.nav, peak)
+        }).collect()
+    }
+
+    /// The largest drawdown
+    pub fn max_drawdown(&self) -> f64 {
+        self.drawdown().into_iter().fold(0.0, f64::max)
+    }
+
+    /// The return of each trading day, from the NAV at the end of the
+    /// previous day (or the initial balance) to the NAV at its end. Days
+    /// start at 5pm New York.
+    pub fn daily_returns(&self) -> Vec<f64> {
+        let alignment = Alignment::default();
+        let mut closes: Vec<(DateTime<UTC>, Decimal)> = Vec::new();
+
+        for point in &self.equity {
+            let day = alignment.start_of_day(point.time);
+
+            match closes.last_mut() {
+                Some(&mut (last_day, ref mut nav)) if last_day == day => *nav = point.nav,
+                _ => closes.push((day, point.nav))
+            }
+        }
+
+        let mut previous = self.initial_balance;
+
+        closes.into_iter().map(|(_, nav)| {
+            let daily = ratio(nav - previous, previous);
+            previous = nav;
+
+            daily
+        }).collect()
+    }
+
+    /// The annualized Sharpe ratio of the daily returns, with a risk-free
+    /// rate of zero. None with fewer than two days, or when the returns do
+    /// not vary.
+    pub fn sharpe_ratio(&self) -> Option<f64> {
+        let returns = self.daily_returns();
+
+        if returns.len() < 2 {
+            return None
+        }
+
+        let n = returns.len() as f64;
+        let mean = returns.iter().sum::<f64>() / n;
+        let variance = returns.iter().map(|r| (r - mean).powi(2)).sum::<f64>() / (n - 1.0);
+
+        if variance == 0.0 {
+            return None
+        }
+
+        Some(mean / variance.sqrt() * TRADING_DAYS.sqrt())
+    }
+
+    /// The fraction of closed Trades that made a profit after financing, None
+    /// when no Trade was closed
+    pub fn win_rate(&self) -> Option<f64> {
+        let closed: Vec<_> = self.trades.iter().filter(|t| t.state == TradeState::CLOSED).collect();
+
+        if closed.is_empty() {
+            return None
+        }
+
+        let wins = closed.iter()
+            .filter(|t| (t.realized_pl + t.financing).value() > Decimal::from(0))
+            .count();
+
+        Some(wins as f64 / closed.len() as f64)
+    }
+}
+
+/// `amount / of` as a floating point number, zero when `of` is zero
+fn ratio(amount: Decimal, of: Decimal) -> f64 {
+    if of.is_zero() {
+        0.0
+    } else {
+        DecimalNumber::from(amount / of).to_f64()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{Duration, TimeZone};
+
+    fn report(navs: &[&str]) -> Report {
+        // Daily points at noon UTC, each in its own 5pm New York day
+        let start = UTC.ymd(2017, 6, 19).and_hms(12, 0, 0);
+
+        Report {
+            initial_balance: "100".parse().unwrap(),
+            equity: navs.iter().enumerate().map(|(day, nav)| EquityPoint {
+                time: start + Duration::days(day as i64),
+                balance: "100".parse().unwrap(),
+                nav: nav.parse().unwrap()
+            }).collect(),
+            trades: Vec::new(),
+            transactions: Vec::new()
+        }
+    }
+
+    #[test]
+    fn it_measures_returns_and_drawdown() {
+        let report = report(&["110", "99", "121", "110"]);
+
+        assert_eq!(report.total_return(), 0.1);
+        assert_eq!(report.drawdown(), vec![0.0, 0.1, 0.0, 1.0 / 11.0]);
+        assert_eq!(report.max_drawdown(), 0.1);
+        assert_eq!(report.daily_returns(), vec![0.1, -0.1, 2.0 / 9.0, -1.0 / 11.0]);
+
+        let sharpe = report.sharpe_ratio().unwrap();
+        assert!((sharpe - 3.3331).abs() < 0.0001, "sharpe ratio {}", sharpe);
+        assert_eq!(report.win_rate(), None);
+    }
+}
@@ -0,0 +1,138 @@
+extern crate oandars;
+
+use std::env;
+use std::fs::File;
+use std::io::BufReader;
+use std::path::Path;
+use std::process;
+use std::thread;
+
+use oandars::account::instruments::Instrument;
+use oandars::export::{read_csv, read_jsonl};
+use oandars::instrument::candlestick::Candlestick;
+use oandars::instrument::candlestick_granularity::CandlestickGranularity;
+use oandars::mock::{MockServer, MockState};
+use oandars::primitives::InstrumentName;
+
+const USAGE: &'static str = "\
+Usage: mock-server [options] [candle files...]
+
+Serves a mock of the Oanda v20 REST API with a single account. Candle files are
+named <instrument>.<granularity>.csv or .jsonl, e.g. EUR_USD.M1.csv as written
+by `oanda candles`, and orders fill at the close of the latest candlestick.
+
+Options:
+  --port <port>       The port to listen on [default: 8080]
+  --account <id>      The ID of the account [default: 101-001-0000000-001]
+  --currency <code>   The currency of the account [default: USD]
+  --balance <amount>  The starting balance of the account [default: 100000]
+  --help              Show this message";
+
+struct Options {
+    port: u16,
+    account: String,
+    currency: String,
+    balance: String,
+    files: Vec<String>
+}
+
+fn main() {
+    let options = match parse(env::args().skip(1)) {
+        Ok(Some(options)) => options,
+        Ok(None) => {
+            println!("{}", USAGE);
+            return
+        },
+        Err(message) => {
+            eprintln!("mock-server: {}\n\n{}", message, USAGE);
+            process::exit(2)
+        }
+    };
+
+    let server = match state(&options).and_then(|state| {
+        MockServer::bind(&format!("127.0.0.1:{}", options.port), state).map_err(|e| e.to_string())
+    }) {
+        Ok(server) => server,
+        Err(message) => {
+            eprintln!("mock-server: {}", message);
+            process::exit(1)
+        }
+    };
+
+    println!("Serving account {} at {}", options.account, server.url());
+    println!("e.g. OANDA_API_URL={} OANDA_API_KEY=any oanda summary", server.url());
+
+    loop {
+        thread::park();
+    }
+}
+
+fn parse<I: Iterator<Item = String>>(args: I) -> Result<Option<Options>, String> {
+    let mut options = Options {
+        port: 8080,
+        account: "101-001-0000000-001".to_string(),
+        currency: "USD".to_string(),
+        balance: "100000".to_string(),
+        files: Vec::new()
+    };
+    let mut args = args;
+
+    while let Some(arg) = args.next() {
+        let mut value = |name: &str| args.next().ok_or_else(|| format!("{} needs a value", name));
+
+        match arg.as_str() {
+            "--help" => return Ok(None),
+            "--port" => options.port = value("--port")?.parse().map_err(|_| "--port: invalid port".to_string())?,
+            "--account" => options.account = value("--account")?,
+            "--currency" => options.currency = value("--currency")?,
+            "--balance" => options.balance = value("--balance")?,
+            _ if arg.starts_with("--") => return Err(format!("unknown option: {}", arg)),
+            _ => options.files.push(arg)
+        }
+    }
+
+    Ok(Some(options))
+}
+
+fn state(options: &Options) -> Result<MockState, String> {
+    let mut state = MockState::new();
+    state.with_account(
+        &options.account,
+        options.currency.parse().map_err(|e| format!("--currency: {}", e))?,
+        options.balance.parse().map_err(|e| format!("--balance: {}", e))?
+    );
+
+    for file in &options.files {
+        let (instrument, granularity, candles) = load(file).map_err(|e| format!("{}: {}", file, e))?;
+
+        println!("Loaded {} {} {} candlesticks", candles.len(), instrument, granularity);
+        state
+            .with_instrument(Instrument::currency_pair(instrument.clone()))
+            .with_candles(instrument, granularity, candles);
+    }
+
+    Ok(state)
+}
+
+/// The instrument and granularity in the name of a candle file, and its
+/// candlesticks
+fn load(file: &str) -> Result<(InstrumentName, CandlestickGranularity, Vec<Candlestick>), String> {
+    let path = Path::new(file);
+    let name = path.file_name().and_then(|n| n.to_str()).unwrap_or_default();
+    let parts: Vec<&str> = name.split('.').collect();
+
+    if parts.len() != 3 {
+        return Err("expected a name like EUR_USD.M1.csv".to_string())
+    }
+
+    let instrument: InstrumentName = parts[0].parse().map_err(|e| format!("{}", e))?;
+    let granularity: CandlestickGranularity = parts[1].parse().map_err(|e| format!("{}", e))?;
+    let reader = BufReader::new(File::open(path).map_err(|e| e.to_string())?);
+    let candles = match parts[2] {
+        "csv" => read_csv(reader),
+        "jsonl" => read_jsonl(reader),
+        extension => return Err(format!("unknown format {}, expected csv or jsonl", extension))
+    }.map_err(|e| e.to_string())?;
+
+    Ok((instrument, granularity, candles))
+}
@@ -0,0 +1,102 @@
+use std::collections::{HashMap, HashSet};
+use std::fmt::Display;
+use std::str::FromStr;
+
+/// Options that are switched on by being given, without a value
+const FLAGS: &'static [&'static str] = &["dry-run", "help", "json", "yes"];
+
+/// Options that take a value, as `--name value` or `--name=value`
+const OPTIONS: &'static [&'static str] = &["account", "from", "granularity", "output", "price", "sl", "to", "tp", "units"];
+
+/// The command line, split into the command, its positional arguments and
+/// its options
+#[derive(Debug, Default, PartialEq)]
+pub struct Args {
+    pub command: Option<String>,
+    pub positional: Vec<String>,
+    options: HashMap<String, String>,
+    flags: HashSet<String>
+}
+
+impl Args {
+    pub fn parse<I: IntoIterator<Item = String>>(args: I) -> Result<Args, String> {
+        let mut result = Args::default();
+        let mut args = args.into_iter();
+
+        while let Some(arg) = args.next() {
+            if !arg.starts_with("--") {
+                if result.command.is_none() {
+                    result.command = Some(arg);
+                } else {
+                    result.positional.push(arg);
+                }
+                continue
+            }
+
+            let (name, value) = match arg[2..].find('=') {
+                Some(i) => (arg[2..2 + i].to_string(), Some(arg[3 + i..].to_string())),
+                None => (arg[2..].to_string(), None)
+            };
+
+            if FLAGS.contains(&name.as_str()) && value.is_none() {
+                result.flags.insert(name);
+            } else if OPTIONS.contains(&name.as_str()) {
+                let value = match value.or_else(|| args.next()) {
+                    Some(value) => value,
+                    None => return Err(format!("--{} needs a value", name))
+                };
+                result.options.insert(name, value);
+            } else {
+                return Err(format!("unknown option: {}", arg))
+            }
+        }
+
+        Ok(result)
+    }
+
+    pub fn flag(&self, name: &str) -> bool {
+        self.flags.contains(name)
+    }
+
+    pub fn option(&self, name: &str) -> Option<&str> {
+        self.options.get(name).map(String::as_str)
+    }
+
+    /// The value of an option parsed as `T`, if it was given
+    pub fn parsed<T>(&self, name: &str) -> Result<Option<T>, String>
+        where T: FromStr, T::Err: Display
+    {
+        match self.option(name) {
+            Some(value) => value.parse().map(Some).map_err(|e| format!("--{}: {}", name, e)),
+            None => Ok(None)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(args: &[&str]) -> Result<Args, String> {
+        Args::parse(args.iter().map(|a| a.to_string()))
+    }
+
+    #[test]
+    fn it_splits_commands_options_and_flags() {
+        let args = parse(&["candles", "EUR_USD", "--granularity", "M1", "--from=2017-06-21", "--json"]).unwrap();
+
+        assert_eq!(args.command, Some("candles".to_string()));
+        assert_eq!(args.positional, vec!["EUR_USD".to_string()]);
+        assert_eq!(args.option("granularity"), Some("M1"));
+        assert_eq!(args.option("from"), Some("2017-06-21"));
+        assert!(args.flag("json"));
+        assert_eq!(args.parsed::<i32>("granularity"), Err("--granularity: invalid digit found in string".to_string()));
+        assert_eq!(args.parsed::<String>("to"), Ok(None));
+    }
+
+    #[test]
+    fn it_rejects_unknown_and_incomplete_options() {
+        assert_eq!(parse(&["accounts", "--verbose"]), Err("unknown option: --verbose".to_string()));
+        assert_eq!(parse(&["candles", "--from"]), Err("--from needs a value".to_string()));
+    }
+}
@@ -0,0 +1,354 @@
+use std::collections::{BTreeSet, HashMap};
+use std::io::{self, Write};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError, Sender};
+use std::sync::Arc;
+use std::thread;
+use std::time::{Duration, Instant};
+
+use chrono::datetime::DateTime;
+use chrono::UTC;
+
+use oandars::account::Account;
+use oandars::account::details::{Details, Order, TradeSummary};
+use oandars::account::summary::Summary;
+use oandars::account::transactions::Transaction;
+use oandars::instrument::price::ClientPrice;
+use oandars::primitives::{DecimalNumber, InstrumentName};
+use oandars::stream::{PriceMessage, TransactionMessage};
+
+use table::Table;
+
+/// How often in seconds the summary is reloaded when there are no
+/// Transactions, as margin used and available change with prices
+const REFRESH: u64 = 30;
+
+/// Move the cursor to the top left and clear the terminal
+const CLEAR: &'static str = "\x1b[H\x1b[2J";
+
+/// What the stream threads send to the dashboard
+enum Event {
+    Price(ClientPrice),
+    Transaction(Transaction),
+    Failed(String)
+}
+
+/// The state of an Account shown by the dashboard: its summary, open trades
+/// valued at the latest prices and pending orders
+pub struct Dashboard {
+    summary: Summary,
+    trades: Vec<TradeSummary>,
+    orders: Vec<Order>,
+    prices: HashMap<InstrumentName, ClientPrice>,
+    last_transaction: Option<Transaction>,
+    updated: DateTime<UTC>
+}
+
+impl Dashboard {
+    pub fn new(summary: Summary, details: Details) -> Dashboard {
+        Dashboard {
+            summary: summary,
+            trades: details.trades,
+            orders: details.orders,
+            prices: HashMap::new(),
+            last_transaction: None,
+            updated: UTC::now()
+        }
+    }
+
+    pub fn update_summary(&mut self, summary: Summary) {
+        self.summary = summary;
+        self.updated = UTC::now();
+    }
+
+    pub fn update_details(&mut self, details: Details) {
+        self.trades = details.trades;
+        self.orders = details.orders;
+        self.updated = UTC::now();
+    }
+
+    pub fn update_price(&mut self, price: ClientPrice) {
+        self.updated = price.time;
+        self.prices.insert(price.instrument.clone(), price);
+    }
+
+    pub fn update_transaction(&mut self, transaction: Transaction) {
+        self.updated = transaction.time;
+        self.last_transaction = Some(transaction);
+    }
+
+    /// The instruments that need prices to value the open trades
+    pub fn instruments(&self) -> BTreeSet<InstrumentName> {
+        self.trades.iter().map(|t| t.instrument.clone()).collect()
+    }
+
+    /// The unrealized P/L of `trade` in the Account's currency at the latest
+    /// price, if there has been one that can be converted
+    pub fn unrealized_pl(&self, trade: &TradeSummary) -> Option<DecimalNumber> {
+        let price = self.prices.get(&trade.instrument)?;
+        let pl = price.unrealized_pl(trade.current_units, trade.price);
+
+        match price.quote_home_conversion_factors {
+            Some(ref factors) => Some(factors.to_home(pl)),
+            None if trade.instrument.quote_currency() == self.summary.currency => Some(pl),
+            None => None
+        }
+    }
+
+    /// The balance plus the unrealized P/L of every trade at the latest
+    /// prices, or as last loaded for trades without a price. The summary's NAV
+    /// is not used as it is reloaded more often than the trades.
+    pub fn nav(&self) -> DecimalNumber {
+        self.trades.iter().fold(DecimalNumber(self.summary.balance.value()), |nav, trade| {
+            let pl = self.unrealized_pl(trade).map_or(trade.unrealized_pl.value(), |pl| pl.value());
+
+            DecimalNumber(nav.value() + pl)
+        })
+    }
+
+    pub fn render(&self) -> String {
+        let summary = &self.summary;
+        let round = |value: DecimalNumber| value.value().round_dp(2).to_string();
+
+        let mut account = Table::new(vec!["NAV", "Balance", "Margin Used", "Margin Available", "Margin Closeout"]);
+        account.row(vec![
+            round(self.nav()),
+            summary.balance.to_string(),
+            summary.margin_used.to_string(),
+            summary.margin_available.to_string(),
+            format!("{:.2}%", summary.margin_closeout_percent.to_f64() * 100.0)
+        ]);
+
+        let mut trades = Table::new(vec!["ID", "Instrument", "Units", "Price", "Current", "Unrealized P/L"]);
+        for trade in &self.trades {
+            let current = self.prices.get(&trade.instrument).map(|p| p.closeout_price(trade.current_units));
+
+            trades.row(vec![
+                trade.id.clone(),
+                trade.instrument.to_string(),
+                trade.current_units.to_string(),
+                trade.price.to_string(),
+                current.map(|p| p.to_string()).unwrap_or_default(),
+                round(self.unrealized_pl(trade).unwrap_or(DecimalNumber(trade.unrealized_pl.value())))
+            ]);
+        }
+
+        let mut orders = Table::new(vec!["ID", "State", "Created"]);
+        for order in &self.orders {
+            orders.row(vec![order.id.clone(), format!("{:?}", order.state), order.create_time.to_rfc3339()]);
+        }
+
+        let last = match self.last_transaction {
+            Some(ref t) => format!("Last transaction: {} {} at {}\n", t.id, t.transaction_type, t.time.to_rfc3339()),
+            None => String::new()
+        };
+
+        format!(
+            "{} ({})  updated {}\n{}\n{}\nOpen trades\n\n{}\nPending orders\n\n{}",
+            summary.alias.as_ref().unwrap_or(&summary.id),
+            summary.currency,
+            self.updated.format("%Y-%m-%d %H:%M:%S UTC"),
+            last,
+            account,
+            trades,
+            orders
+        )
+    }
+}
+
+/// Show the dashboard for `account` until it is interrupted or a stream fails
+pub fn run(account: &Account) -> Result<(), String> {
+    let mut dashboard = Dashboard::new(
+        account.try_summary().map_err(|e| e.to_string())?,
+        account.try_details().map_err(|e| e.to_string())?
+    );
+    let (sender, receiver) = mpsc::channel();
+    let generation = Arc::new(AtomicUsize::new(0));
+
+    stream_transactions(account, sender.clone())?;
+
+    let mut instruments = dashboard.instruments();
+    stream_prices(account, &instruments, &generation, sender.clone())?;
+
+    let mut reloaded = Instant::now();
+
+    loop {
+        draw(&dashboard)?;
+
+        let mut reload_details = false;
+        for event in next_events(&receiver)? {
+            match event {
+                Event::Price(price) => dashboard.update_price(price),
+                Event::Transaction(transaction) => {
+                    dashboard.update_transaction(transaction);
+                    reload_details = true;
+                },
+                Event::Failed(message) => return Err(message)
+            }
+        }
+
+        if reload_details {
+            dashboard.update_details(account.try_details().map_err(|e| e.to_string())?);
+        }
+        if reload_details || reloaded.elapsed() >= Duration::from_secs(REFRESH) {
+            dashboard.update_summary(account.try_summary().map_err(|e| e.to_string())?);
+            reloaded = Instant::now();
+        }
+
+        if dashboard.instruments() != instruments {
+            instruments = dashboard.instruments();
+            stream_prices(account, &instruments, &generation, sender.clone())?;
+        }
+    }
+}
+
+fn draw(dashboard: &Dashboard) -> Result<(), String> {
+    let stdout = io::stdout();
+    let mut stdout = stdout.lock();
+
+    write!(stdout, "{}{}", CLEAR, dashboard.render())
+        .and_then(|_| stdout.flush())
+        .map_err(|e| e.to_string())
+}
+
+/// Every event that is waiting once there is at least one, or none if there
+/// has not been one before the summary is due to be reloaded
+fn next_events(receiver: &Receiver<Event>) -> Result<Vec<Event>, String> {
+    let first = match receiver.recv_timeout(Duration::from_secs(REFRESH)) {
+        Ok(event) => event,
+        Err(RecvTimeoutError::Timeout) => return Ok(Vec::new()),
+        Err(RecvTimeoutError::Disconnected) => return Err("the streams were closed".to_string())
+    };
+
+    let mut events = vec![first];
+    events.extend(receiver.try_iter());
+
+    Ok(events)
+}
+
+fn stream_transactions(account: &Account, sender: Sender<Event>) -> Result<(), String> {
+    let stream = account.transaction_stream().map_err(|e| e.to_string())?;
+
+    thread::spawn(move || {
+        for message in stream {
+            let event = match message {
+                Ok(TransactionMessage::Transaction(transaction)) => Event::Transaction(transaction),
+                Ok(TransactionMessage::Heartbeat { .. }) => continue,
+                Err(err) => Event::Failed(format!("transaction stream: {}", err))
+            };
+
+            if sender.send(event).is_err() {
+                return
+            }
+        }
+
+        sender.send(Event::Failed("the transaction stream ended".to_string())).ok();
+    });
+
+    Ok(())
+}
+
+/// Stream the prices of `instruments`, replacing the previous price stream,
+/// which stops at its next message
+fn stream_prices(
+    account: &Account,
+    instruments: &BTreeSet<InstrumentName>,
+    generation: &Arc<AtomicUsize>,
+    sender: Sender<Event>
+) -> Result<(), String> {
+    let current = generation.fetch_add(1, Ordering::SeqCst) + 1;
+
+    if instruments.is_empty() {
+        return Ok(())
+    }
+
+    let instruments: Vec<InstrumentName> = instruments.iter().cloned().collect();
+    let stream = account.price_stream(&instruments).map_err(|e| e.to_string())?;
+    let generation = generation.clone();
+
+    thread::spawn(move || {
+        for message in stream {
+            if generation.load(Ordering::SeqCst) != current {
+                return
+            }
+
+            let event = match message {
+                Ok(PriceMessage::Price(price)) => Event::Price(price),
+                Ok(PriceMessage::Heartbeat(_)) => continue,
+                Err(err) => Event::Failed(format!("price stream: {}", err))
+            };
+
+            if sender.send(event).is_err() {
+                return
+            }
+        }
+
+        if generation.load(Ordering::SeqCst) == current {
+            sender.send(Event::Failed("the price stream ended".to_string())).ok();
+        }
+    });
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json;
+
+    const SUMMARY: &'static str = r#"{
+        "id": "101-004-1435156-001", "alias": "Testv20", "currency": "USD", "balance": "100000.0000",
+        "createdByUserID": 1435156, "createdTime": "2017-06-01T00:00:00.000000000Z", "pl": "0.0000",
+        "resettablePL": "0.0000", "openTradeCount": 1, "openPositionCount": 1, "pendingOrderCount": 0,
+        "hedgingEnabled": false, "unrealizedPL": "-2.0000", "NAV": "99998.0000", "marginUsed": "222.9000",
+        "marginAvailable": "99775.1000", "positionValue": "11145.0000", "marginCloseoutUnrealizedPL": "-1.0000",
+        "marginCloseoutNAV": "99999.0000", "marginCloseoutMarginUsed": "222.9000",
+        "marginCloseoutPercent": "0.00111", "marginCloseoutPositionValue": "11145.0000",
+        "withdrawalLimit": "99775.1000", "marginCallMarginUsed": "222.9000", "marginCallPercent": "0.00223",
+        "lastTransactionID": "7"
+    }"#;
+
+    const TRADE: &'static str = r#"{
+        "id": "7", "instrument": "EUR_USD", "price": "1.11450", "openTime": "2017-06-21T12:00:00.000000000Z",
+        "state": "OPEN", "initialUnits": "10000", "currentUnits": "10000", "realizedPL": "0.0000",
+        "unrealizedPL": "-2.0000", "closingTransactionIDs": [], "financing": "0.0000",
+        "clientExtensions": {"id": "", "tag": "", "comment": ""}
+    }"#;
+
+    fn dashboard() -> Dashboard {
+        let mut dashboard = Dashboard::new(
+            serde_json::from_str(SUMMARY).unwrap(),
+            serde_json::from_str(&SUMMARY.replace(
+                r#""lastTransactionID": "7""#,
+                &format!(r#""lastTransactionID": "7", "trades": [{}], "positions": [], "orders": []"#, TRADE)
+            )).unwrap()
+        );
+
+        dashboard.update_price(serde_json::from_str(r#"{
+            "instrument": "EUR_USD", "time": "2017-06-21T12:00:05.000000000Z", "tradeable": true,
+            "bids": [], "asks": [], "closeoutBid": "1.11500", "closeoutAsk": "1.11520"
+        }"#).unwrap());
+
+        dashboard
+    }
+
+    #[test]
+    fn it_values_trades_at_the_latest_price() {
+        let dashboard = dashboard();
+
+        assert_eq!(dashboard.unrealized_pl(&dashboard.trades[0]), Some("5.0000000".parse().unwrap()));
+        assert_eq!(dashboard.nav(), "100005.0000".parse().unwrap());
+        assert!(dashboard.render().contains("1.11500"));
+        assert!(dashboard.render().contains("0.11%"));
+    }
+
+    #[test]
+    fn it_keeps_the_nav_when_only_the_summary_is_reloaded() {
+        let mut dashboard = dashboard();
+        dashboard.update_summary(serde_json::from_str(
+            &SUMMARY.replace(r#""unrealizedPL": "-2.0000", "NAV": "99998.0000""#, r#""unrealizedPL": "3.0000", "NAV": "100003.0000""#)
+        ).unwrap());
+
+        assert_eq!(dashboard.nav(), "100005.0000".parse().unwrap());
+    }
+}
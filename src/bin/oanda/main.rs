@@ -0,0 +1,482 @@
+extern crate chrono;
+extern crate oandars;
+extern crate serde;
+extern crate serde_json;
+
+mod args;
+mod dashboard;
+mod table;
+
+use std::env;
+use std::fs::File;
+use std::io::{self, BufRead, Write};
+use std::fmt::Display;
+use std::process;
+use std::str::FromStr;
+
+use chrono::datetime::DateTime;
+use chrono::naive::date::NaiveDate;
+use chrono::UTC;
+use serde::Serialize;
+
+use oandars::account::Account;
+use oandars::account::orders::{CreateOrderRequest, MarketOrderRequest};
+use oandars::account::positions::ClosePositionRequest;
+use oandars::account::trades::{CloseTradeRequest, CloseUnits};
+use oandars::account::transactions::Transaction;
+use oandars::client::Client;
+use oandars::export::{Column, Exporter};
+use oandars::instrument::candlestick_granularity::CandlestickGranularity;
+use oandars::instrument::price_components::PriceComponents;
+use oandars::primitives::{DecimalNumber, InstrumentName};
+
+use args::Args;
+use table::Table;
+
+const USAGE: &'static str = "\
+Usage: oanda <command> [options]
+
+Commands:
+  accounts                    List the accounts of the API key
+  summary                     Show the summary of an account
+  details                     Show the open trades, positions and pending orders of an account
+  instruments                 List the instruments an account can trade
+  candles <instrument>        Download candlesticks as CSV
+  dashboard                   Watch the summary, open trades and pending orders of an account live
+  order market <instrument> <units>
+                              Place a market order, short if units is negative
+  order cancel <order id>     Cancel a pending order
+  trade close <trade id>      Close an open trade
+  position close <instrument> Close every open side of a position
+
+Options:
+  --account <id>              The account to use [default: the first account]
+  --json                      Print the response as JSON rather than a table
+  --granularity <granularity> The granularity of the candlesticks, e.g. M1 [default: S5]
+  --price <components>        Any of M (mid), B (bid) and A (ask) [default: M]
+  --from <time>               The start of the candlesticks, e.g. 2017-06-21 or 2017-06-21T12:00:00Z
+  --to <time>                 The end of the candlesticks [default: now]
+  --output <file>             Write the candlesticks to a file rather than standard output
+  --sl <price>                Add a stop loss to a market order
+  --tp <price>                Add a take profit to a market order
+  --units <units>             The units of a trade to close [default: ALL]
+  --dry-run                   Print the JSON body of a trading request instead of sending it
+  --yes                       Send a trading request without asking for confirmation
+  --help                      Show this message
+
+The API is configured with the OANDA_API_URL and OANDA_API_KEY environment
+variables, e.g. OANDA_API_URL=https://api-fxpractice.oanda.com/v3";
+
+fn main() {
+    let args = match Args::parse(env::args().skip(1)) {
+        Ok(args) => args,
+        Err(message) => exit_with_usage(&message)
+    };
+
+    if args.flag("help") || args.command.is_none() {
+        println!("{}", USAGE);
+        return
+    }
+
+    let (url, key) = match (env::var("OANDA_API_URL"), env::var("OANDA_API_KEY")) {
+        (Ok(url), Ok(key)) => (url, key),
+        _ => exit_with_usage("OANDA_API_URL and OANDA_API_KEY must be set")
+    };
+    let client = Client::new(&url, &key);
+
+    if let Err(message) = run(&client, &args) {
+        eprintln!("oanda: {}", message);
+        process::exit(1);
+    }
+}
+
+fn exit_with_usage(message: &str) -> ! {
+    eprintln!("oanda: {}\n\n{}", message, USAGE);
+    process::exit(2)
+}
+
+fn run(client: &Client, args: &Args) -> Result<(), String> {
+    match args.command.as_ref().map(String::as_str) {
+        Some("accounts") => accounts(client, args),
+        Some("summary") => summary(client, args),
+        Some("details") => details(client, args),
+        Some("instruments") => instruments(client, args),
+        Some("candles") => candles(client, args),
+        Some("dashboard") => dashboard::run(&account(client, args)?),
+        Some("order") => order(client, args),
+        Some("trade") => trade(client, args),
+        Some("position") => position(client, args),
+        Some(command) => Err(format!("unknown command: {}", command)),
+        None => Ok(())
+    }
+}
+
+fn accounts(client: &Client, args: &Args) -> Result<(), String> {
+    let accounts = client.try_accounts().map_err(|e| e.to_string())?;
+
+    if args.flag("json") {
+        return print_json(&accounts)
+    }
+
+    let mut table = Table::new(vec!["ID", "Tags"]);
+    for account in &accounts {
+        table.row(vec![account.id.clone(), account.tags.join(", ")]);
+    }
+    print!("{}", table);
+
+    Ok(())
+}
+
+fn summary(client: &Client, args: &Args) -> Result<(), String> {
+    let summary = account(client, args)?.try_summary().map_err(|e| e.to_string())?;
+
+    if args.flag("json") {
+        return print_json(&summary)
+    }
+
+    let mut table = Table::new(vec!["Field", "Value"]);
+    table
+        .row(vec!["ID".to_string(), summary.id.clone()])
+        .row(vec!["Alias".to_string(), summary.alias.clone().unwrap_or_default()])
+        .row(vec!["Currency".to_string(), summary.currency.to_string()])
+        .row(vec!["Balance".to_string(), summary.balance.to_string()])
+        .row(vec!["NAV".to_string(), summary.nav.to_string()])
+        .row(vec!["Unrealized P/L".to_string(), summary.unrealized_pl.to_string()])
+        .row(vec!["P/L".to_string(), summary.pl.to_string()])
+        .row(vec!["Margin Used".to_string(), summary.margin_used.to_string()])
+        .row(vec!["Margin Available".to_string(), summary.margin_available.to_string()])
+        .row(vec!["Open Trades".to_string(), summary.open_trade_count.to_string()])
+        .row(vec!["Open Positions".to_string(), summary.open_position_count.to_string()])
+        .row(vec!["Pending Orders".to_string(), summary.pending_order_count.to_string()])
+        .row(vec!["Last Transaction ID".to_string(), summary.last_transaction_id.clone()]);
+    print!("{}", table);
+
+    Ok(())
+}
+
+fn details(client: &Client, args: &Args) -> Result<(), String> {
+    let details = account(client, args)?.try_details().map_err(|e| e.to_string())?;
+
+    if args.flag("json") {
+        return print_json(&details)
+    }
+
+    let mut trades = Table::new(vec!["ID", "Instrument", "Units", "Price", "Unrealized P/L", "Opened"]);
+    for trade in &details.trades {
+        trades.row(vec![
+            trade.id.clone(),
+            trade.instrument.to_string(),
+            trade.current_units.to_string(),
+            trade.price.to_string(),
+            trade.unrealized_pl.to_string(),
+            trade.open_time.to_rfc3339()
+        ]);
+    }
+
+    let mut positions = Table::new(vec!["Instrument", "Long", "Short", "Unrealized P/L"]);
+    for position in details.positions.iter().filter(|p| !p.long.units.value().is_zero() || !p.short.units.value().is_zero()) {
+        positions.row(vec![
+            position.instrument.to_string(),
+            position.long.units.to_string(),
+            position.short.units.to_string(),
+            position.unrealized_pl.to_string()
+        ]);
+    }
+
+    let mut orders = Table::new(vec!["ID", "State", "Created"]);
+    for order in &details.orders {
+        orders.row(vec![order.id.clone(), format!("{:?}", order.state), order.create_time.to_rfc3339()]);
+    }
+
+    print!("Open trades\n\n{}\nOpen positions\n\n{}\nPending orders\n\n{}", trades, positions, orders);
+
+    Ok(())
+}
+
+fn instruments(client: &Client, args: &Args) -> Result<(), String> {
+    let mut instruments = account(client, args)?.try_instruments().map_err(|e| e.to_string())?;
+    instruments.sort_by(|a, b| a.name.cmp(&b.name));
+
+    if args.flag("json") {
+        return print_json(&instruments)
+    }
+
+    let mut table = Table::new(vec!["Name", "Type", "Display Name", "Pip Location", "Margin Rate"]);
+    for instrument in &instruments {
+        table.row(vec![
+            instrument.name.to_string(),
+            instrument.instrument_type.to_string(),
+            instrument.display_name.clone(),
+            instrument.pip_location.to_string(),
+            instrument.margin_rate.to_string()
+        ]);
+    }
+    print!("{}", table);
+
+    Ok(())
+}
+
+fn candles(client: &Client, args: &Args) -> Result<(), String> {
+    let instrument: InstrumentName = match args.positional.first() {
+        Some(instrument) => instrument.parse().map_err(|e| format!("{}", e))?,
+        None => return Err("candles needs an instrument, e.g. oanda candles EUR_USD".to_string())
+    };
+    let granularity = args.parsed("granularity")?.unwrap_or(CandlestickGranularity::S5);
+    let price = args.parsed("price")?.unwrap_or(PriceComponents::MID);
+    let from = parse_time("from", args.option("from"))?.ok_or_else(|| "--from is required".to_string())?;
+    let to = parse_time("to", args.option("to"))?.unwrap_or_else(UTC::now);
+
+    let mut query = client.pricing_for(instrument, from);
+    query.with_granularity(granularity).with_price(price);
+
+    let mut iterator = query.candles_between(from, to);
+    let mut candles = Vec::new();
+    while let Some(candle) = iterator.next_candle().map_err(|e| e.to_string())? {
+        candles.push(candle);
+    }
+
+    let exporter = Exporter::new(Column::for_price(price));
+    let written = match args.option("output") {
+        Some(path) => {
+            let file = File::create(path).map_err(|e| format!("{}: {}", path, e))?;
+            exporter.write_csv(&candles, file)
+        },
+        None => exporter.write_csv(&candles, io::stdout())
+    };
+    written.map_err(|e| e.to_string())?;
+    eprintln!("{} candles", candles.len());
+
+    Ok(())
+}
+
+fn order(client: &Client, args: &Args) -> Result<(), String> {
+    let account = account(client, args)?;
+
+    match args.positional.first().map(String::as_str) {
+        Some("market") => {
+            let instrument: InstrumentName = positional(args, 1, "an instrument")?;
+            let units: DecimalNumber = positional(args, 2, "units")?;
+            let mut order = MarketOrderRequest::new(instrument, units);
+            let mut description = format!("Place a market order for {} {}", units, order.instrument);
+
+            if let Some(stop_loss) = args.parsed("sl")? {
+                order.with_stop_loss(stop_loss);
+                description.push_str(&format!(" with a stop loss at {}", stop_loss));
+            }
+            if let Some(take_profit) = args.parsed("tp")? {
+                order.with_take_profit(take_profit);
+                description.push_str(&format!(" with a take profit at {}", take_profit));
+            }
+
+            let request = CreateOrderRequest { order: order };
+            if !confirm(args, &account, &description, Some(&to_json(&request)?))? {
+                return Ok(())
+            }
+
+            let response = account.try_create_order(&request).map_err(|e| e.to_string())?;
+            print_transactions(args, &response, &[
+                Some(&response.order_create_transaction),
+                response.order_fill_transaction.as_ref(),
+                response.order_cancel_transaction.as_ref()
+            ])
+        },
+        Some("cancel") => {
+            let order_id: String = positional(args, 1, "an order id")?;
+
+            if !confirm(args, &account, &format!("Cancel order {}", order_id), None)? {
+                return Ok(())
+            }
+
+            let response = account.try_cancel_order(&order_id).map_err(|e| e.to_string())?;
+            print_transactions(args, &response, &[Some(&response.order_cancel_transaction)])
+        },
+        _ => Err("order needs market or cancel, e.g. oanda order market EUR_USD 10000".to_string())
+    }
+}
+
+fn trade(client: &Client, args: &Args) -> Result<(), String> {
+    if args.positional.first().map(String::as_str) != Some("close") {
+        return Err("trade needs close, e.g. oanda trade close 123".to_string())
+    }
+
+    let account = account(client, args)?;
+    let trade_id: String = positional(args, 1, "a trade id")?;
+    let request = CloseTradeRequest { units: args.parsed("units")?.unwrap_or(CloseUnits::All) };
+    let description = match request.units {
+        CloseUnits::Units(units) => format!("Close {} units of trade {}", units, trade_id),
+        _ => format!("Close trade {}", trade_id)
+    };
+
+    if !confirm(args, &account, &description, Some(&to_json(&request)?))? {
+        return Ok(())
+    }
+
+    let response = account.try_close_trade(&trade_id, &request).map_err(|e| e.to_string())?;
+    print_transactions(args, &response, &[
+        Some(&response.order_create_transaction),
+        response.order_fill_transaction.as_ref(),
+        response.order_cancel_transaction.as_ref()
+    ])
+}
+
+fn position(client: &Client, args: &Args) -> Result<(), String> {
+    if args.positional.first().map(String::as_str) != Some("close") {
+        return Err("position needs close, e.g. oanda position close EUR_USD".to_string())
+    }
+
+    let account = account(client, args)?;
+    let instrument: InstrumentName = positional(args, 1, "an instrument")?;
+    let position = account.try_position(&instrument).map_err(|e| e.to_string())?;
+    let request = ClosePositionRequest::all_of(&position);
+
+    if request.long_units == Some(CloseUnits::None) && request.short_units == Some(CloseUnits::None) {
+        return Err(format!("there is no open {} position", instrument))
+    }
+
+    let description = format!(
+        "Close the {} position of {} long and {} short units",
+        instrument, position.long.units, position.short.units
+    );
+    if !confirm(args, &account, &description, Some(&to_json(&request)?))? {
+        return Ok(())
+    }
+
+    let response = account.try_close_position(&instrument, &request).map_err(|e| e.to_string())?;
+    print_transactions(args, &response, &[
+        response.long_order_create_transaction.as_ref(),
+        response.long_order_fill_transaction.as_ref(),
+        response.long_order_cancel_transaction.as_ref(),
+        response.short_order_create_transaction.as_ref(),
+        response.short_order_fill_transaction.as_ref(),
+        response.short_order_cancel_transaction.as_ref()
+    ])
+}
+
+/// Whether to send a trading request. With --dry-run the body is printed and
+/// nothing is sent, with --yes it is sent without asking.
+fn confirm(args: &Args, account: &Account, description: &str, body: Option<&str>) -> Result<bool, String> {
+    if args.flag("dry-run") {
+        eprintln!("{} on account {} (dry run)", description, account.id);
+        println!("{}", body.unwrap_or("(no body)"));
+        return Ok(false)
+    }
+    if args.flag("yes") {
+        return Ok(true)
+    }
+
+    eprint!("{} on account {}? [y/N] ", description, account.id);
+    io::stderr().flush().map_err(|e| e.to_string())?;
+
+    let mut answer = String::new();
+    let stdin = io::stdin();
+    stdin.lock().read_line(&mut answer).map_err(|e| e.to_string())?;
+
+    if !confirmed(&answer) {
+        eprintln!("Nothing was sent");
+        return Ok(false)
+    }
+
+    Ok(true)
+}
+
+/// Whether an answer to a [y/N] prompt is yes
+fn confirmed(answer: &str) -> bool {
+    match answer.trim().to_lowercase().as_str() {
+        "y" | "yes" => true,
+        _ => false
+    }
+}
+
+/// The response as JSON with --json, otherwise a line for each transaction
+fn print_transactions<T: Serialize>(args: &Args, response: &T, transactions: &[Option<&Transaction>]) -> Result<(), String> {
+    if args.flag("json") {
+        return print_json(response)
+    }
+
+    let mut table = Table::new(vec!["ID", "Type", "Instrument", "Units", "Price", "Reason"]);
+    for transaction in transactions.iter().filter_map(|t| *t) {
+        let text = |value: Option<String>| value.unwrap_or_default();
+
+        table.row(vec![
+            transaction.id.clone(),
+            transaction.transaction_type.to_string(),
+            text(transaction.instrument.as_ref().map(|i| i.to_string())),
+            text(transaction.units.map(|u| u.to_string())),
+            text(transaction.price.map(|p| p.to_string())),
+            text(transaction.reason.clone())
+        ]);
+    }
+    print!("{}", table);
+
+    Ok(())
+}
+
+/// The positional argument after the subcommand at `index`
+fn positional<T>(args: &Args, index: usize, name: &str) -> Result<T, String>
+    where T: FromStr, T::Err: Display
+{
+    match args.positional.get(index) {
+        Some(value) => value.parse().map_err(|e| format!("invalid {}: {}", name, e)),
+        None => Err(format!("{} {} needs {}", args.command.as_ref().map_or("", |c| c.as_str()), args.positional[0], name))
+    }
+}
+
+fn to_json<T: Serialize>(value: &T) -> Result<String, String> {
+    serde_json::to_string(value).map_err(|e| e.to_string())
+}
+
+/// The account given with --account, or the first account of the API key
+fn account<'a>(client: &'a Client<'a>, args: &Args) -> Result<Account<'a>, String> {
+    let accounts = client.try_accounts().map_err(|e| e.to_string())?;
+
+    match args.option("account") {
+        Some(id) => accounts.into_iter().find(|a| a.id == id).ok_or_else(|| format!("no account {}", id)),
+        None => accounts.into_iter().next().ok_or_else(|| "the API key has no accounts".to_string())
+    }
+}
+
+/// A time given as RFC 3339 or as a date, which is midnight UTC
+fn parse_time(name: &str, value: Option<&str>) -> Result<Option<DateTime<UTC>>, String> {
+    let value = match value {
+        Some(value) => value,
+        None => return Ok(None)
+    };
+
+    if let Ok(time) = value.parse::<DateTime<UTC>>() {
+        return Ok(Some(time))
+    }
+
+    NaiveDate::parse_from_str(value, "%Y-%m-%d")
+        .map(|date| Some(DateTime::from_utc(date.and_hms(0, 0, 0), UTC)))
+        .map_err(|_| format!("--{}: invalid time {:?}", name, value))
+}
+
+fn print_json<T: Serialize>(value: &T) -> Result<(), String> {
+    let json = serde_json::to_string_pretty(value).map_err(|e| e.to_string())?;
+    let stdout = io::stdout();
+
+    writeln!(stdout.lock(), "{}", json).map_err(|e| e.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    #[test]
+    fn it_parses_dates_and_times() {
+        assert_eq!(parse_time("from", Some("2017-06-21")), Ok(Some(UTC.ymd(2017, 6, 21).and_hms(0, 0, 0))));
+        assert_eq!(parse_time("from", Some("2017-06-21T12:30:00Z")), Ok(Some(UTC.ymd(2017, 6, 21).and_hms(12, 30, 0))));
+        assert_eq!(parse_time("to", None), Ok(None));
+        assert!(parse_time("to", Some("yesterday")).is_err());
+    }
+
+    #[test]
+    fn it_only_confirms_a_yes() {
+        assert!(confirmed("y\n"));
+        assert!(confirmed("Yes\n"));
+        assert!(!confirmed("\n"));
+        assert!(!confirmed("n\n"));
+        assert!(!confirmed("yep\n"));
+    }
+}
@@ -0,0 +1,61 @@
+use std::fmt;
+
+/// Rows of text printed in aligned columns under a header
+pub struct Table {
+    headers: Vec<&'static str>,
+    rows: Vec<Vec<String>>
+}
+
+impl Table {
+    pub fn new(headers: Vec<&'static str>) -> Table {
+        Table { headers: headers, rows: Vec::new() }
+    }
+
+    pub fn row(&mut self, values: Vec<String>) -> &mut Table {
+        self.rows.push(values);
+        self
+    }
+}
+
+impl fmt::Display for Table {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let mut widths: Vec<usize> = self.headers.iter().map(|h| h.chars().count()).collect();
+
+        for row in &self.rows {
+            for (width, value) in widths.iter_mut().zip(row) {
+                *width = (*width).max(value.chars().count());
+            }
+        }
+
+        let dashes: Vec<String> = widths.iter().map(|w| "-".repeat(*w)).collect();
+
+        writeln!(f, "{}", line(&self.headers, &widths))?;
+        writeln!(f, "{}", line(&dashes.iter().map(String::as_str).collect::<Vec<_>>(), &widths))?;
+
+        for row in &self.rows {
+            writeln!(f, "{}", line(&row.iter().map(String::as_str).collect::<Vec<_>>(), &widths))?;
+        }
+
+        Ok(())
+    }
+}
+
+/// The values padded to their column widths
+fn line(values: &[&str], widths: &[usize]) -> String {
+    let cells: Vec<String> = values.iter().zip(widths).map(|(v, w)| format!("{:1$}", v, w)).collect();
+
+    cells.join("  ").trim_end().to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn it_aligns_columns() {
+        let mut table = Table::new(vec!["ID", "Instrument"]);
+        table.row(vec!["6".to_string(), "EUR_USD".to_string()]).row(vec!["1234".to_string(), "USD_JPY".to_string()]);
+
+        assert_eq!(table.to_string(), "ID    Instrument\n----  ----------\n6     EUR_USD\n1234  USD_JPY\n");
+    }
+}
@@ -0,0 +1,232 @@
+//! When the forex market is open.
+//!
+//! The market closes for the weekend at the start of the trading day on the
+//! weekly alignment and opens two days later, which with the default
+//! alignment is Friday 17:00 until Sunday 17:00 America/New_York. Days start
+//! at the daily alignment, the same as candlesticks with daily alignment, so
+//! `Alignment` values used for a `PricingQuery` can be used here too.
+
+use chrono::datetime::DateTime;
+use chrono::naive::date::NaiveDate;
+use chrono::{Datelike, Duration, Timelike, UTC, Weekday};
+use chrono_tz::Tz;
+use chrono_tz::America::New_York;
+use chrono_tz::Asia::Tokyo;
+use chrono_tz::Australia::Sydney;
+use chrono_tz::Europe::London;
+
+use instrument::alignment::Alignment;
+use primitives::{Currency, InstrumentName};
+
+/// The number of days the market is closed each weekend
+const WEEKEND_DAYS: i64 = 2;
+
+/// A day the market is closed
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Holiday {
+    /// The date (in the alignment timezone) the closed trading day ends on,
+    /// e.g. 25 December closes the market from 17:00 on the 24th until 17:00
+    /// on the 25th with the default alignment
+    pub date: NaiveDate,
+    /// Only instruments with this currency are closed. All instruments are
+    /// closed if not provided.
+    pub currency: Option<Currency>
+}
+
+/// The major trading sessions
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Session {
+    Sydney,
+    Tokyo,
+    London,
+    NewYork
+}
+
+impl Session {
+    pub const ALL: [Session; 4] = [Session::Sydney, Session::Tokyo, Session::London, Session::NewYork];
+
+    /// The timezone the session hours are in
+    pub fn timezone(&self) -> Tz {
+        match *self {
+            Session::Sydney => Sydney,
+            Session::Tokyo => Tokyo,
+            Session::London => London,
+            Session::NewYork => New_York
+        }
+    }
+
+    /// The local hours the session opens and closes at on each weekday
+    pub fn hours(&self) -> (u32, u32) {
+        match *self {
+            Session::Sydney => (7, 16),
+            Session::Tokyo => (9, 18),
+            Session::London => (8, 17),
+            Session::NewYork => (8, 17)
+        }
+    }
+
+    /// Whether `time` is within the session hours of a local weekday. This
+    /// does not consider whether the market is open.
+    pub fn contains(&self, time: DateTime<UTC>) -> bool {
+        let local = time.with_timezone(&self.timezone());
+        let (open, close) = self.hours();
+
+        match local.weekday() {
+            Weekday::Sat | Weekday::Sun => false,
+            _ => local.hour() >= open && local.hour() < close
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct MarketCalendar {
+    /// How trading days and weeks are aligned [default=17:00 America/New_York, Friday]
+    alignment: Alignment,
+    holidays: Vec<Holiday>
+}
+
+impl Default for MarketCalendar {
+    fn default() -> MarketCalendar {
+        MarketCalendar::new(Alignment::default())
+    }
+}
+
+impl MarketCalendar {
+    pub fn new(alignment: Alignment) -> MarketCalendar {
+        MarketCalendar {
+            alignment: alignment,
+            holidays: Vec::new()
+        }
+    }
+
+    /// Close the market for every instrument on `date`
+    pub fn with_holiday(&mut self, date: NaiveDate) -> &mut MarketCalendar {
+        self.holidays.push(Holiday { date: date, currency: None });
+        self
+    }
+
+    /// Close the market for instruments with `currency` on `date`
+    pub fn with_currency_holiday(&mut self, currency: Currency, date: NaiveDate) -> &mut MarketCalendar {
+        self.holidays.push(Holiday { date: date, currency: Some(currency) });
+        self
+    }
+
+    pub fn alignment(&self) -> &Alignment {
+        &self.alignment
+    }
+
+    pub fn holidays(&self) -> &[Holiday] {
+        &self.holidays
+    }
+
+    /// Whether `instrument` can be traded at `time`
+    pub fn is_open(&self, instrument: &InstrumentName, time: DateTime<UTC>) -> bool {
+        self.closure(Some(instrument), time).is_none()
+    }
+
+    /// The first time at or after `time` the market is open for every
+    /// instrument without a currency holiday
+    pub fn next_open(&self, time: DateTime<UTC>) -> DateTime<UTC> {
+        self.next_open_for(None, time)
+    }
+
+    /// The first time at or after `time` the market is open for `instrument`,
+    /// or for every instrument without a currency holiday if not provided
+    pub fn next_open_for(&self, instrument: Option<&InstrumentName>, time: DateTime<UTC>) -> DateTime<UTC> {
+        let mut time = time;
+
+        while let Some((_, open)) = self.closure(instrument, time) {
+            time = open;
+        }
+
+        time
+    }
+
+    /// The sessions in progress at `time`, none while the market is closed
+    pub fn sessions(&self, time: DateTime<UTC>) -> Vec<Session> {
+        if self.closure(None, time).is_some() {
+            return Vec::new()
+        }
+
+        Session::ALL.iter().cloned().filter(|s| s.contains(time)).collect()
+    }
+
+    /// The close and open of the weekend or holiday containing `time`, if the
+    /// market is closed at `time`. Only holidays for every instrument are
+    /// considered if `instrument` is not provided.
+    pub fn closure(
+        &self,
+        instrument: Option<&InstrumentName>,
+        time: DateTime<UTC>
+    ) -> Option<(DateTime<UTC>, DateTime<UTC>)> {
+        let close = self.alignment.start_of_week(time);
+        let open = self.alignment.day_start(self.alignment.trading_date(close) + Duration::days(WEEKEND_DAYS));
+
+        if time < open {
+            return Some((close, open))
+        }
+
+        let start_of_day = self.alignment.start_of_day(time);
+        let ends_on = self.alignment.trading_date(start_of_day).succ();
+        let closed = self.holidays.iter().any(|h| h.date == ends_on && match (h.currency, instrument) {
+            (None, _) => true,
+            (Some(currency), Some(instrument)) => {
+                instrument.base_currency() == Some(currency) || instrument.quote_currency() == currency
+            },
+            (Some(_), None) => false
+        });
+
+        if closed {
+            Some((start_of_day, self.alignment.next_day(start_of_day)))
+        } else {
+            None
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    #[test]
+    fn it_closes_for_the_weekend_across_daylight_saving() {
+        let calendar = MarketCalendar::default();
+        let eur_usd = "EUR_USD".parse().unwrap();
+
+        // 17:00 in New York is 21:00 UTC in June and 22:00 UTC in December
+        assert!(calendar.is_open(&eur_usd, UTC.ymd(2017, 6, 23).and_hms(20, 59, 0)));
+        assert!(!calendar.is_open(&eur_usd, UTC.ymd(2017, 6, 23).and_hms(21, 0, 0)));
+        assert!(calendar.is_open(&eur_usd, UTC.ymd(2017, 12, 1).and_hms(21, 30, 0)));
+        assert_eq!(calendar.next_open(UTC.ymd(2017, 6, 24).and_hms(12, 0, 0)), UTC.ymd(2017, 6, 25).and_hms(21, 0, 0));
+        assert_eq!(calendar.next_open(UTC.ymd(2017, 6, 26).and_hms(12, 0, 0)), UTC.ymd(2017, 6, 26).and_hms(12, 0, 0));
+    }
+
+    #[test]
+    fn it_closes_on_holidays() {
+        let mut calendar = MarketCalendar::default();
+        calendar
+            .with_holiday(NaiveDate::from_ymd(2017, 12, 25))
+            .with_currency_holiday("JPY".parse().unwrap(), NaiveDate::from_ymd(2017, 5, 3));
+        let eur_usd = "EUR_USD".parse().unwrap();
+        let usd_jpy = "USD_JPY".parse().unwrap();
+
+        // Christmas follows the weekend, so the market opens on the evening of the 25th
+        assert_eq!(calendar.next_open(UTC.ymd(2017, 12, 23).and_hms(12, 0, 0)), UTC.ymd(2017, 12, 25).and_hms(22, 0, 0));
+        assert!(calendar.is_open(&eur_usd, UTC.ymd(2017, 5, 3).and_hms(12, 0, 0)));
+        assert!(!calendar.is_open(&usd_jpy, UTC.ymd(2017, 5, 3).and_hms(12, 0, 0)));
+        assert_eq!(
+            calendar.next_open_for(Some(&usd_jpy), UTC.ymd(2017, 5, 3).and_hms(12, 0, 0)),
+            UTC.ymd(2017, 5, 3).and_hms(21, 0, 0)
+        );
+    }
+
+    #[test]
+    fn it_finds_the_sessions_in_progress() {
+        let calendar = MarketCalendar::default();
+
+        assert_eq!(calendar.sessions(UTC.ymd(2017, 6, 21).and_hms(8, 0, 0)), vec![Session::Tokyo, Session::London]);
+        assert_eq!(calendar.sessions(UTC.ymd(2017, 6, 21).and_hms(13, 0, 0)), vec![Session::London, Session::NewYork]);
+        assert_eq!(calendar.sessions(UTC.ymd(2017, 6, 24).and_hms(13, 0, 0)), vec![]);
+    }
+}
@@ -1,5 +1,6 @@
 extern crate ratelimit;
 
+use std::fmt;
 use std::io::Read;
 use std::sync::mpsc;
 use std::thread;
@@ -10,22 +11,37 @@ use chrono::datetime::DateTime;
 use chrono::UTC;
 
 use hyper::Client as WebClient;
-use hyper::header::Headers;
+use hyper::client::Response;
+use hyper::header::{ContentType, Headers};
+use hyper::method::Method;
 use hyper::net::HttpsConnector;
 use hyper_native_tls::NativeTlsClient;
 
 use serde_json;
+use url::form_urlencoded::Serializer;
 
+use error::{Error, Result};
 use account::*;
 use account::details::*;
 use account::summary::*;
 
+use instrument::download::Download;
+use instrument::order_book::{InstrumentOrderBook, OrderBook};
+use instrument::position_book::{InstrumentPositionBook, PositionBook};
+use primitives::InstrumentName;
 use instrument::pricing_query::PricingQuery;
 
 header! { (Authorization, "Authorization") => [String] }
 header! { (AcceptDatetimeFormat, "AcceptDatetimeFormat") => [String] }
 header! { (Connection, "Connection") => [String] }
 
+/// The body Oanda responds with when a request is rejected
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ErrorResponse {
+    error_message: String
+}
+
 pub struct Client<'a> {
     url: &'a str,
     api_key: &'a str,
@@ -33,6 +49,13 @@ pub struct Client<'a> {
     sender: SyncSender<()>
 }
 
+/// The api key is left out so it is not written to logs
+impl<'a> fmt::Debug for Client<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("Client").field("url", &self.url).finish()
+    }
+}
+
 /// The Client facilitates all requests to the Oanda API
 impl<'a> Client<'a> {
     pub fn new(url: &'a str, api_key: &'a str) -> Client<'a> {
@@ -68,34 +91,130 @@ impl<'a> Client<'a> {
 
     /// Get Account list for current auth token
     pub fn accounts(&self) -> Vec<Account> {
-        let input = self.get("accounts");
-        let mut result: Accounts = serde_json::from_str(&input).unwrap();
+        self.try_accounts().unwrap()
+    }
+
+    /// Get Account list for current auth token, failing if the request could
+    /// not be made or Oanda rejected it
+    pub fn try_accounts(&self) -> Result<Vec<Account>> {
+        let input = self.try_get("accounts")?;
+        let mut result: Accounts = serde_json::from_str(&input)?;
 
         for x in result.accounts.iter_mut() {
             x.client = Some(&self);
         }
 
-        result.accounts
+        Ok(result.accounts)
     }
 
-    pub fn pricing_for(&self, instrument: String, from: DateTime<UTC>) -> PricingQuery {
+    pub fn pricing_for(&self, instrument: InstrumentName, from: DateTime<UTC>) -> PricingQuery {
         PricingQuery::new(&self, instrument, from)
     }
 
+    /// Query the most recent `count` candlesticks for an instrument
+    pub fn recent_pricing_for(&self, instrument: InstrumentName, count: i32) -> PricingQuery {
+        PricingQuery::recent(&self, instrument, count)
+    }
+
+    /// Get the order book for an instrument, the most recent snapshot unless
+    /// `time` is given
+    pub fn order_book_for(&self, instrument: &InstrumentName, time: Option<DateTime<UTC>>) -> OrderBook {
+        self.try_order_book_for(instrument, time).unwrap()
+    }
+
+    /// Get the order book for an instrument, failing if Oanda has no book for
+    /// the instrument or time
+    pub fn try_order_book_for(&self, instrument: &InstrumentName, time: Option<DateTime<UTC>>) -> Result<OrderBook> {
+        let input = self.try_get(&book_path(instrument, "orderBook", time))?;
+        let result: InstrumentOrderBook = serde_json::from_str(&input)?;
+
+        Ok(result.order_book)
+    }
+
+    /// Get the position book for an instrument, the most recent snapshot
+    /// unless `time` is given
+    pub fn position_book_for(&self, instrument: &InstrumentName, time: Option<DateTime<UTC>>) -> PositionBook {
+        self.try_position_book_for(instrument, time).unwrap()
+    }
+
+    /// Get the position book for an instrument, failing if Oanda has no book
+    /// for the instrument or time
+    pub fn try_position_book_for(&self, instrument: &InstrumentName, time: Option<DateTime<UTC>>) -> Result<PositionBook> {
+        let input = self.try_get(&book_path(instrument, "positionBook", time))?;
+        let result: InstrumentPositionBook = serde_json::from_str(&input)?;
+
+        Ok(result.position_book)
+    }
+
+    /// Download candlesticks for many instruments at once, see `Download`
+    pub fn download(&self, instruments: Vec<InstrumentName>, from: DateTime<UTC>, to: DateTime<UTC>) -> Download {
+        Download::new(&self, instruments, from, to)
+    }
+
     pub fn get(&self, params: &str) -> String {
-        self.sender.send(());
+        self.try_get(params).unwrap()
+    }
+
+    /// Get the body for `params`, failing if the request could not be made or
+    /// Oanda rejected it
+    pub fn try_get(&self, params: &str) -> Result<String> {
+        self.try_request(Method::Get, params, None)
+    }
+
+    /// Post a JSON `body` to `params`, returning the body of the response
+    pub fn try_post(&self, params: &str, body: &str) -> Result<String> {
+        self.try_request(Method::Post, params, Some(body))
+    }
+
+    /// Put a JSON `body` (if any) to `params`, returning the body of the
+    /// response
+    pub fn try_put(&self, params: &str, body: Option<&str>) -> Result<String> {
+        self.try_request(Method::Put, params, body)
+    }
+
+    /// Open the stream at `params`, e.g. a pricing stream. Streams are served
+    /// from the stream host that matches the API host, e.g.
+    /// stream-fxpractice.oanda.com for api-fxpractice.oanda.com
+    pub fn try_stream(&self, params: &str) -> Result<Response> {
+        self.send(Method::Get, &format!("{}/{}", stream_url(self.url), params), None)
+    }
 
+    fn try_request(&self, method: Method, params: &str, body: Option<&str>) -> Result<String> {
         let mut res = String::new();
+        let mut response = self.send(method, &format!("{}/{}", self.url, params), body)?;
 
-        self.web_client
-            .get(&format!("{}/{}", self.url, params))
-            .headers(self.headers())
-            .send()
-            .unwrap()
-            .read_to_string(&mut res)
-            .unwrap();
+        response.read_to_string(&mut res)?;
 
-        res
+        Ok(res)
+    }
+
+    /// Send a request, failing if it could not be made or Oanda rejected it
+    fn send(&self, method: Method, url: &str, body: Option<&str>) -> Result<Response> {
+        self.sender.send(()).ok();
+
+        let mut headers = self.headers();
+        let mut request = self.web_client.request(method, url);
+
+        if let Some(body) = body {
+            headers.set(ContentType::json());
+            request = request.body(body);
+        }
+
+        let mut response = request.headers(headers).send()?;
+
+        if !response.status.is_success() {
+            let mut res = String::new();
+            response.read_to_string(&mut res)?;
+
+            let message = match serde_json::from_str::<ErrorResponse>(&res) {
+                Ok(body) => body.error_message,
+                Err(_) => res
+            };
+
+            return Err(Error::Api { status: response.status.to_u16(), message: message })
+        }
+
+        Ok(response)
     }
 
     fn headers(&self) -> Headers {
@@ -109,10 +228,60 @@ impl<'a> Client<'a> {
     }
 }
 
+/// The URL of the stream host for the API at `url`. Other hosts, e.g. a local
+/// server, serve both.
+fn stream_url(url: &str) -> String {
+    url.replacen("://api-", "://stream-", 1)
+}
+
+fn book_path(instrument: &InstrumentName, book: &str, time: Option<DateTime<UTC>>) -> String {
+    match time {
+        Some(time) => format!(
+            "instruments/{}/{}?{}",
+            instrument,
+            book,
+            Serializer::new(String::new()).append_pair("time", &time.to_rfc3339()).finish()
+        ),
+        None => format!("instruments/{}/{}", instrument, book)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
     use std::env;
+    use mock::{MockServer, MockState};
+
+    #[test]
+    fn it_builds_book_paths() {
+        use chrono::TimeZone;
+
+        let eur_usd = "EUR_USD".parse().unwrap();
+        let time = UTC.ymd(2017, 6, 21).and_hms(12, 0, 0);
+
+        assert_eq!(book_path(&eur_usd, "orderBook", None), "instruments/EUR_USD/orderBook");
+        assert_eq!(
+            book_path(&eur_usd, "positionBook", Some(time)),
+            "instruments/EUR_USD/positionBook?time=2017-06-21T12%3A00%3A00%2B00%3A00"
+        );
+    }
+
+    #[test]
+    fn it_returns_an_error_for_a_missing_book() {
+        let server = MockServer::start(MockState::new()).unwrap();
+        let client = Client::new(server.url(), "key");
+        let eur_usd = "EUR_USD".parse().unwrap();
+
+        assert!(client.try_order_book_for(&eur_usd, None).is_err());
+        assert!(client.try_position_book_for(&eur_usd, None).is_err());
+    }
+
+    #[test]
+    fn it_streams_from_the_stream_host() {
+        assert_eq!(stream_url("https://api-fxpractice.oanda.com/v3"), "https://stream-fxpractice.oanda.com/v3");
+        assert_eq!(stream_url("https://api-fxtrade.oanda.com/v3"), "https://stream-fxtrade.oanda.com/v3");
+        assert_eq!(stream_url("http://localhost:8080/v3"), "http://localhost:8080/v3");
+    }
 
     /// # TODO: Move integration tests to `tests/`
     #[test]
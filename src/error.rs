@@ -0,0 +1,68 @@
+use std::error;
+use std::fmt;
+use std::io;
+use std::result;
+
+use hyper;
+use serde_json;
+
+/// The errors that can occur while talking to the Oanda API
+#[derive(Debug)]
+pub enum Error {
+    /// The request could not be sent or the response could not be read
+    Http(hyper::Error),
+    /// Reading or writing local data failed
+    Io(io::Error),
+    /// The response body could not be parsed
+    Json(serde_json::Error),
+    /// Local data was not in the expected format
+    Format(String),
+    /// Oanda rejected the request
+    Api {
+        /// The HTTP status code of the response
+        status: u16,
+        /// The human-readable description of the error provided by Oanda
+        message: String
+    }
+}
+
+pub type Result<T> = result::Result<T, Error>;
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Error::Http(ref err) => write!(f, "HTTP error: {}", err),
+            Error::Io(ref err) => write!(f, "IO error: {}", err),
+            Error::Json(ref err) => write!(f, "JSON error: {}", err),
+            Error::Format(ref message) => write!(f, "Format error: {}", message),
+            Error::Api { status, ref message } => write!(f, "API error ({}): {}", status, message)
+        }
+    }
+}
+
+impl error::Error for Error {}
+
+impl From<hyper::Error> for Error {
+    fn from(err: hyper::Error) -> Error {
+        Error::Http(err)
+    }
+}
+
+impl From<io::Error> for Error {
+    fn from(err: io::Error) -> Error {
+        Error::Io(err)
+    }
+}
+
+impl From<serde_json::Error> for Error {
+    fn from(err: serde_json::Error) -> Error {
+        Error::Json(err)
+    }
+}
+
+#[cfg(feature = "parquet")]
+impl From<::parquet::errors::ParquetError> for Error {
+    fn from(err: ::parquet::errors::ParquetError) -> Error {
+        Error::Format(err.to_string())
+    }
+}
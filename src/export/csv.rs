@@ -0,0 +1,95 @@
+use std::io::{BufRead, BufWriter, Write};
+
+use error::{Error, Result};
+use instrument::candlestick::Candlestick;
+use super::{CandleBuilder, Column};
+
+/// Write a header row of column names, then a row for each candlestick. A
+/// price component the candlestick does not have is left empty.
+pub fn write<W: Write>(columns: &[Column], candles: &[Candlestick], writer: W) -> Result<()> {
+    let mut writer = BufWriter::new(writer);
+    let names: Vec<&str> = columns.iter().map(|c| c.name()).collect();
+
+    writeln!(writer, "{}", names.join(","))?;
+
+    for candle in candles {
+        let values: Vec<String> = columns.iter().map(|c| c.text(candle).unwrap_or_default()).collect();
+
+        writeln!(writer, "{}", values.join(","))?;
+    }
+
+    writer.flush()?;
+    Ok(())
+}
+
+/// Read candlesticks from a header row and a row for each candlestick.
+/// Columns with names not known are ignored, e.g. an index added by pandas.
+pub fn read<R: BufRead>(reader: R) -> Result<Vec<Candlestick>> {
+    let mut lines = reader.lines();
+    let header = match lines.next() {
+        Some(header) => header?,
+        None => return Ok(Vec::new())
+    };
+    let columns: Vec<Option<Column>> = header.trim_end().split(',').map(Column::from_name).collect();
+
+    if !columns.contains(&Some(Column::Time)) {
+        return Err(Error::Format("missing time column".to_string()))
+    }
+
+    let mut candles = Vec::new();
+
+    for line in lines {
+        let line = line?;
+        let line = line.trim_end();
+
+        if line.is_empty() {
+            continue
+        }
+
+        let mut builder = CandleBuilder::default();
+        for (column, value) in columns.iter().zip(line.split(',')) {
+            if let Some(column) = *column {
+                builder.set(column, value)?;
+            }
+        }
+        candles.push(builder.build()?);
+    }
+
+    Ok(candles)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use export::test_support::candles;
+    use serde_json;
+
+    #[test]
+    fn it_writes_and_reads_back_candles() {
+        let candles = candles();
+        let mut file = Vec::new();
+        write(&Column::for_candles(&candles), &candles, &mut file).unwrap();
+        let text = String::from_utf8(file).unwrap();
+
+        assert_eq!(text.lines().collect::<Vec<_>>(), vec![
+            "time,bid_o,bid_h,bid_l,bid_c,mid_o,mid_h,mid_l,mid_c,volume,complete",
+            "2017-06-21T12:00:00+00:00,1.11395,1.11416,1.11389,1.11412,1.11402,1.11423,1.11396,1.11419,73,true",
+            "2017-06-21T12:01:00+00:00,,,,,1.11419,1.11420,1.11410,1.11415,12,false"
+        ]);
+        assert_eq!(
+            serde_json::to_string(&read(text.as_bytes()).unwrap()).unwrap(),
+            serde_json::to_string(&candles).unwrap()
+        );
+    }
+
+    #[test]
+    fn it_reads_chosen_columns_and_ignores_unknown_ones() {
+        let text = ",time,mid_c,mid_o,mid_h,mid_l\n0,2017-06-21T12:00:00Z,1.2,1.1,1.3,1.0\n";
+        let candles = read(text.as_bytes()).unwrap();
+
+        assert_eq!(candles[0].mid.as_ref().unwrap().c.to_string(), "1.2");
+        assert_eq!(candles[0].volume, 0);
+        assert!(!candles[0].complete);
+        assert!(read("time,mid_c\n2017-06-21T12:00:00Z,1.2\n".as_bytes()).is_err());
+    }
+}
@@ -0,0 +1,93 @@
+use std::io::{BufRead, BufWriter, Write};
+
+use serde_json::{self, Value};
+
+use error::{Error, Result};
+use instrument::candlestick::Candlestick;
+use super::{CandleBuilder, Column};
+
+/// Write an object for each candlestick on its own line, with times and
+/// prices as strings. A price component the candlestick does not have is
+/// null.
+pub fn write<W: Write>(columns: &[Column], candles: &[Candlestick], writer: W) -> Result<()> {
+    let mut writer = BufWriter::new(writer);
+
+    for candle in candles {
+        let fields: Vec<String> = columns.iter().map(|column| {
+            let value = match column.text(candle) {
+                Some(value) if *column == Column::Time || column.price().is_some() => format!("\"{}\"", value),
+                Some(value) => value,
+                None => "null".to_string()
+            };
+
+            format!("\"{}\":{}", column.name(), value)
+        }).collect();
+
+        writeln!(writer, "{{{}}}", fields.join(","))?;
+    }
+
+    writer.flush()?;
+    Ok(())
+}
+
+/// Read candlesticks from an object on each line. Fields with names not known
+/// are ignored.
+pub fn read<R: BufRead>(reader: R) -> Result<Vec<Candlestick>> {
+    let mut candles = Vec::new();
+
+    for line in reader.lines() {
+        let line = line?;
+
+        if line.trim().is_empty() {
+            continue
+        }
+
+        let fields = match serde_json::from_str(&line)? {
+            Value::Object(fields) => fields,
+            _ => return Err(Error::Format(format!("expected an object: {}", line)))
+        };
+
+        let mut builder = CandleBuilder::default();
+        for (name, value) in fields {
+            let column = match Column::from_name(&name) {
+                Some(column) => column,
+                None => continue
+            };
+            let text = match value {
+                Value::String(text) => text,
+                Value::Number(number) => number.to_string(),
+                Value::Bool(flag) => flag.to_string(),
+                Value::Null => continue,
+                _ => return Err(Error::Format(format!("invalid value for {}", name)))
+            };
+
+            builder.set(column, &text)?;
+        }
+        candles.push(builder.build()?);
+    }
+
+    Ok(candles)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use export::test_support::candles;
+    use serde_json;
+
+    #[test]
+    fn it_writes_and_reads_back_candles() {
+        let candles = candles();
+        let mut file = Vec::new();
+        write(&Column::for_candles(&candles), &candles, &mut file).unwrap();
+        let text = String::from_utf8(file).unwrap();
+
+        assert_eq!(
+            text.lines().nth(1).unwrap(),
+            r#"{"time":"2017-06-21T12:01:00+00:00","bid_o":null,"bid_h":null,"bid_l":null,"bid_c":null,"mid_o":"1.11419","mid_h":"1.11420","mid_l":"1.11410","mid_c":"1.11415","volume":12,"complete":false}"#
+        );
+        let read = read(text.as_bytes()).unwrap();
+        assert_eq!(serde_json::to_string(&read).unwrap(), serde_json::to_string(&candles).unwrap());
+        assert_eq!(read[1].mid.as_ref().unwrap().h.to_string(), "1.11420");
+    }
+}
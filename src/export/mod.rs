@@ -0,0 +1,320 @@
+//! Writing candlesticks to files other tools can read, and reading them back.
+//!
+//! CSV and JSON Lines are always available, Parquet with the `parquet`
+//! feature. Every format has one column (or field) per `Column`, named as
+//! Oanda names the fields, with the price components flattened, e.g. `mid_o`
+//! for the open of the midpoint candle:
+//!
+//! ```text
+//! time,mid_o,mid_h,mid_l,mid_c,volume,complete
+//! 2017-06-21T12:00:00+00:00,1.11402,1.11423,1.11396,1.11419,73,true
+//! ```
+//!
+//! Prices are written with the precision Oanda provides them in, and read
+//! back exactly, trailing zeros included. JSON Lines has them as strings, as
+//! the API does, and Parquet as decimals so tools like pandas load them as
+//! numbers.
+
+pub mod csv;
+pub mod jsonl;
+#[cfg(feature = "parquet")]
+pub mod parquet;
+
+use std::io::{BufRead, Write};
+
+use chrono::datetime::DateTime;
+use chrono::UTC;
+
+use error::{Error, Result};
+use instrument::candlestick::Candlestick;
+use instrument::candlestick_data::CandlestickData;
+use instrument::price_components::PriceComponents;
+use primitives::PriceValue;
+
+/// A value of a candlestick that can be exported
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Column {
+    Time,
+    BidOpen,
+    BidHigh,
+    BidLow,
+    BidClose,
+    AskOpen,
+    AskHigh,
+    AskLow,
+    AskClose,
+    MidOpen,
+    MidHigh,
+    MidLow,
+    MidClose,
+    Volume,
+    Complete
+}
+
+const PRICE_COLUMNS: [(PriceComponents, [Column; 4]); 3] = [
+    (PriceComponents::BID, [Column::BidOpen, Column::BidHigh, Column::BidLow, Column::BidClose]),
+    (PriceComponents::ASK, [Column::AskOpen, Column::AskHigh, Column::AskLow, Column::AskClose]),
+    (PriceComponents::MID, [Column::MidOpen, Column::MidHigh, Column::MidLow, Column::MidClose])
+];
+
+impl Column {
+    pub const ALL: [Column; 15] = [
+        Column::Time,
+        Column::BidOpen, Column::BidHigh, Column::BidLow, Column::BidClose,
+        Column::AskOpen, Column::AskHigh, Column::AskLow, Column::AskClose,
+        Column::MidOpen, Column::MidHigh, Column::MidLow, Column::MidClose,
+        Column::Volume,
+        Column::Complete
+    ];
+
+    /// The name of the column in an exported file
+    pub fn name(&self) -> &'static str {
+        match *self {
+            Column::Time => "time",
+            Column::BidOpen => "bid_o",
+            Column::BidHigh => "bid_h",
+            Column::BidLow => "bid_l",
+            Column::BidClose => "bid_c",
+            Column::AskOpen => "ask_o",
+            Column::AskHigh => "ask_h",
+            Column::AskLow => "ask_l",
+            Column::AskClose => "ask_c",
+            Column::MidOpen => "mid_o",
+            Column::MidHigh => "mid_h",
+            Column::MidLow => "mid_l",
+            Column::MidClose => "mid_c",
+            Column::Volume => "volume",
+            Column::Complete => "complete"
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Column> {
+        Column::ALL.iter().cloned().find(|c| c.name() == name)
+    }
+
+    /// The time, every price of `price` and the volume and complete flag
+    pub fn for_price(price: PriceComponents) -> Vec<Column> {
+        let mut columns = vec![Column::Time];
+
+        for &(component, ref prices) in PRICE_COLUMNS.iter() {
+            if price.contains(component) {
+                columns.extend_from_slice(prices);
+            }
+        }
+        columns.push(Column::Volume);
+        columns.push(Column::Complete);
+
+        columns
+    }
+
+    /// The columns for every price component the first candlestick has
+    pub fn for_candles(candles: &[Candlestick]) -> Vec<Column> {
+        let mut columns = vec![Column::Time];
+
+        if let Some(candle) = candles.first() {
+            for &(component, ref prices) in PRICE_COLUMNS.iter() {
+                if data_for(candle, component).is_some() {
+                    columns.extend_from_slice(prices);
+                }
+            }
+        }
+        columns.push(Column::Volume);
+        columns.push(Column::Complete);
+
+        columns
+    }
+
+    /// The price component and the index of the price (open, high, low,
+    /// close) of a price column
+    fn price(&self) -> Option<(PriceComponents, usize)> {
+        PRICE_COLUMNS.iter()
+            .filter_map(|&(component, ref prices)| prices.iter().position(|c| c == self).map(|i| (component, i)))
+            .next()
+    }
+
+    /// The value of the column as text, if the candlestick has it
+    fn text(&self, candle: &Candlestick) -> Option<String> {
+        match *self {
+            Column::Time => Some(candle.time.to_rfc3339()),
+            Column::Volume => Some(candle.volume.to_string()),
+            Column::Complete => Some(candle.complete.to_string()),
+            _ => self.price_of(candle).map(|price| price.to_string())
+        }
+    }
+
+    /// The price of a price column, if the candlestick has its component
+    fn price_of(&self, candle: &Candlestick) -> Option<PriceValue> {
+        self.price().and_then(|(component, index)| {
+            data_for(candle, component).as_ref().map(|d| [d.o, d.h, d.l, d.c][index])
+        })
+    }
+}
+
+fn data_for(candle: &Candlestick, component: PriceComponents) -> &Option<CandlestickData> {
+    if component == PriceComponents::BID {
+        &candle.bid
+    } else if component == PriceComponents::ASK {
+        &candle.ask
+    } else {
+        &candle.mid
+    }
+}
+
+/// Writes candlesticks with a chosen set of columns, e.g. the candles of a
+/// `Pricing`:
+///
+/// ```ignore
+/// let pricing = client.pricing_for("EUR_USD".parse().unwrap(), from).execute();
+/// let file = File::create("EUR_USD.csv").unwrap();
+///
+/// Exporter::for_candles(&pricing.candles).write_csv(&pricing.candles, file).unwrap();
+/// ```
+#[derive(Clone, Debug, PartialEq)]
+pub struct Exporter {
+    columns: Vec<Column>
+}
+
+impl Exporter {
+    pub fn new(columns: Vec<Column>) -> Exporter {
+        Exporter { columns: columns }
+    }
+
+    /// An exporter with the columns for every price component the first
+    /// candlestick has
+    pub fn for_candles(candles: &[Candlestick]) -> Exporter {
+        Exporter::new(Column::for_candles(candles))
+    }
+
+    pub fn columns(&self) -> &[Column] {
+        &self.columns
+    }
+
+    pub fn write_csv<W: Write>(&self, candles: &[Candlestick], writer: W) -> Result<()> {
+        csv::write(&self.columns, candles, writer)
+    }
+
+    pub fn write_jsonl<W: Write>(&self, candles: &[Candlestick], writer: W) -> Result<()> {
+        jsonl::write(&self.columns, candles, writer)
+    }
+
+    #[cfg(feature = "parquet")]
+    pub fn write_parquet<W: Write + Send>(&self, candles: &[Candlestick], writer: W) -> Result<()> {
+        parquet::write(&self.columns, candles, writer)
+    }
+}
+
+/// Read candlesticks written by `Exporter::write_csv`
+pub fn read_csv<R: BufRead>(reader: R) -> Result<Vec<Candlestick>> {
+    csv::read(reader)
+}
+
+/// Read candlesticks written by `Exporter::write_jsonl`
+pub fn read_jsonl<R: BufRead>(reader: R) -> Result<Vec<Candlestick>> {
+    jsonl::read(reader)
+}
+
+/// Read candlesticks written by `Exporter::write_parquet`, e.g. from a `File`
+#[cfg(feature = "parquet")]
+pub fn read_parquet<R: ::parquet::file::reader::ChunkReader + 'static>(reader: R) -> Result<Vec<Candlestick>> {
+    parquet::read(reader)
+}
+
+/// Builds a candlestick from the values of one row of an exported file
+#[derive(Default)]
+struct CandleBuilder {
+    time: Option<DateTime<UTC>>,
+    bid: [Option<PriceValue>; 4],
+    ask: [Option<PriceValue>; 4],
+    mid: [Option<PriceValue>; 4],
+    volume: Option<i32>,
+    complete: Option<bool>
+}
+
+impl CandleBuilder {
+    /// Set the value of `column` from its text. Empty values are ignored.
+    fn set(&mut self, column: Column, value: &str) -> Result<()> {
+        if value.is_empty() {
+            return Ok(())
+        }
+
+        let invalid = || Error::Format(format!("invalid value for {}: {}", column.name(), value));
+
+        match column {
+            Column::Time => self.time = Some(value.parse().map_err(|_| invalid())?),
+            Column::Volume => self.volume = Some(value.parse().map_err(|_| invalid())?),
+            Column::Complete => self.complete = Some(value.parse().map_err(|_| invalid())?),
+            _ => {
+                let (component, index) = column.price().expect("every other column is a price");
+                let prices = if component == PriceComponents::BID {
+                    &mut self.bid
+                } else if component == PriceComponents::ASK {
+                    &mut self.ask
+                } else {
+                    &mut self.mid
+                };
+
+                prices[index] = Some(value.parse().map_err(|_| invalid())?);
+            }
+        }
+
+        Ok(())
+    }
+
+    /// A candlestick with every price component that has all four prices.
+    /// The volume is 0 if it was not set, and the candlestick incomplete so it
+    /// is never taken for a final one.
+    fn build(&self) -> Result<Candlestick> {
+        Ok(Candlestick {
+            time: self.time.ok_or_else(|| Error::Format("missing time".to_string()))?,
+            bid: data(&self.bid, "bid")?,
+            ask: data(&self.ask, "ask")?,
+            mid: data(&self.mid, "mid")?,
+            volume: self.volume.unwrap_or(0),
+            complete: self.complete.unwrap_or(false)
+        })
+    }
+}
+
+fn data(prices: &[Option<PriceValue>; 4], component: &str) -> Result<Option<CandlestickData>> {
+    match *prices {
+        [Some(o), Some(h), Some(l), Some(c)] => Ok(Some(CandlestickData { o: o, h: h, l: l, c: c })),
+        [None, None, None, None] => Ok(None),
+        _ => Err(Error::Format(format!("missing {} prices", component)))
+    }
+}
+
+#[cfg(test)]
+pub mod test_support {
+    use instrument::candlestick::Candlestick;
+    use serde_json;
+
+    /// Two minute candles, the second incomplete and only the first with bid
+    /// prices
+    pub fn candles() -> Vec<Candlestick> {
+        serde_json::from_str(r#"[
+            {"time":"2017-06-21T12:00:00Z","volume":73,"complete":true,
+             "mid":{"o":"1.11402","h":"1.11423","l":"1.11396","c":"1.11419"},
+             "bid":{"o":"1.11395","h":"1.11416","l":"1.11389","c":"1.11412"}},
+            {"time":"2017-06-21T12:01:00Z","volume":12,"complete":false,
+             "mid":{"o":"1.11419","h":"1.11420","l":"1.11410","c":"1.11415"}}
+        ]"#).unwrap()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn it_chooses_columns_for_the_components_requested() {
+        assert_eq!(Column::for_price(PriceComponents::MID | PriceComponents::ASK), vec![
+            Column::Time,
+            Column::AskOpen, Column::AskHigh, Column::AskLow, Column::AskClose,
+            Column::MidOpen, Column::MidHigh, Column::MidLow, Column::MidClose,
+            Column::Volume,
+            Column::Complete
+        ]);
+        assert_eq!(Column::for_candles(&test_support::candles()), Column::for_price(PriceComponents::BID | PriceComponents::MID));
+        assert_eq!(Column::from_name("mid_c"), Some(Column::MidClose));
+    }
+}
@@ -0,0 +1,163 @@
+use std::io::Write;
+use std::sync::Arc;
+
+use chrono::{TimeZone, UTC};
+use parquet::data_type::{self, BoolType, Int32Type, Int64Type};
+use parquet::file::properties::WriterProperties;
+use parquet::file::reader::{ChunkReader, FileReader};
+use parquet::file::serialized_reader::SerializedFileReader;
+use parquet::file::writer::SerializedFileWriter;
+use parquet::record::Field;
+use parquet::schema::parser::parse_message_type;
+
+use error::{Error, Result};
+use instrument::candlestick::Candlestick;
+use primitives::Decimal;
+use super::{CandleBuilder, Column};
+
+/// The number of digits of every price column, the most a DECIMAL backed by
+/// an INT64 holds
+const PRICE_PRECISION: u32 = 18;
+
+/// The Parquet type of a column. Times are in milliseconds and prices
+/// decimals with the most decimal places of any price in the column, so they
+/// read back exactly. Prices are null when the candlestick does not have the
+/// component.
+fn schema_type(column: Column, candles: &[Candlestick]) -> String {
+    let field = match column {
+        Column::Time => "REQUIRED INT64 {} (TIMESTAMP_MILLIS);".to_string(),
+        Column::Volume => "REQUIRED INT32 {};".to_string(),
+        Column::Complete => "REQUIRED BOOLEAN {};".to_string(),
+        _ => format!("OPTIONAL INT64 {{}} (DECIMAL({}, {}));", PRICE_PRECISION, price_scale(column, candles))
+    };
+
+    field.replace("{}", column.name())
+}
+
+/// The most decimal places of any price in `column`
+fn price_scale(column: Column, candles: &[Candlestick]) -> u32 {
+    candles.iter().filter_map(|c| column.price_of(c)).map(|p| p.value().scale()).max().unwrap_or(0)
+}
+
+/// Write every candlestick to one row group
+pub fn write<W: Write + Send>(columns: &[Column], candles: &[Candlestick], writer: W) -> Result<()> {
+    let fields: Vec<String> = columns.iter().map(|c| schema_type(*c, candles)).collect();
+    let schema = parse_message_type(&format!("message candlestick {{ {} }}", fields.join(" ")))?;
+    let mut file = SerializedFileWriter::new(writer, Arc::new(schema), Arc::new(WriterProperties::builder().build()))?;
+    let mut row_group = file.next_row_group()?;
+
+    for column in columns {
+        let mut writer = row_group.next_column()?.expect("a writer for every column in the schema");
+
+        match *column {
+            Column::Time => {
+                let times: Vec<i64> = candles.iter()
+                    .map(|c| c.time.timestamp() * 1000 + c.time.timestamp_subsec_millis() as i64)
+                    .collect();
+                writer.typed::<Int64Type>().write_batch(&times, None, None)?;
+            },
+            Column::Volume => {
+                let volumes: Vec<i32> = candles.iter().map(|c| c.volume).collect();
+                writer.typed::<Int32Type>().write_batch(&volumes, None, None)?;
+            },
+            Column::Complete => {
+                let complete: Vec<bool> = candles.iter().map(|c| c.complete).collect();
+                writer.typed::<BoolType>().write_batch(&complete, None, None)?;
+            },
+            _ => {
+                let scale = price_scale(*column, candles);
+                let prices: Vec<Option<Decimal>> = candles.iter().map(|c| column.price_of(c).map(|p| p.value())).collect();
+                let mut values = Vec::new();
+                for price in prices.iter().filter_map(|p| *p) {
+                    let mut price = price;
+                    price.rescale(scale);
+                    values.push(price.mantissa() as i64);
+                }
+                let levels: Vec<i16> = prices.iter().map(|p| if p.is_some() { 1 } else { 0 }).collect();
+                writer.typed::<Int64Type>().write_batch(&values, Some(&levels), None)?;
+            }
+        }
+
+        writer.close()?;
+    }
+
+    row_group.close()?;
+    file.close()?;
+    Ok(())
+}
+
+/// Read candlesticks from every row group. Columns with names not known are
+/// ignored.
+pub fn read<R: ChunkReader + 'static>(reader: R) -> Result<Vec<Candlestick>> {
+    let reader = SerializedFileReader::new(reader)?;
+    let mut candles = Vec::new();
+
+    for row in reader.get_row_iter(None)? {
+        let row = row?;
+        let mut builder = CandleBuilder::default();
+
+        for (name, field) in row.get_column_iter() {
+            let column = match Column::from_name(name) {
+                Some(column) => column,
+                None => continue
+            };
+            let text = match *field {
+                Field::TimestampMillis(millis) => {
+                    let nanos = (millis.rem_euclid(1000) * 1_000_000) as u32;
+                    UTC.timestamp(millis.div_euclid(1000), nanos).to_rfc3339()
+                },
+                Field::Decimal(ref value) => decimal_text(value),
+                Field::Str(ref value) => value.clone(),
+                Field::Int(value) => value.to_string(),
+                Field::Bool(flag) => flag.to_string(),
+                Field::Null => continue,
+                _ => return Err(Error::Format(format!("invalid value for {}", name)))
+            };
+
+            builder.set(column, &text)?;
+        }
+        candles.push(builder.build()?);
+    }
+
+    Ok(candles)
+}
+
+/// A Parquet decimal as text, e.g. “1.11420” for 111420 with a scale of 5
+fn decimal_text(decimal: &data_type::Decimal) -> String {
+    // the unscaled value is big-endian two's complement
+    let unscaled = decimal.data().iter().fold(0i128, |value, byte| (value << 8) | *byte as i128);
+    let bits = decimal.data().len() as u32 * 8;
+    let unscaled = if bits < 128 && unscaled >> (bits - 1) == 1 { unscaled - (1i128 << bits) } else { unscaled };
+
+    Decimal::from_i128_with_scale(unscaled, decimal.scale() as u32).to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::env;
+    use std::fs::{self, File};
+    use std::process;
+    use export::test_support::candles;
+    use serde_json;
+
+    #[test]
+    fn it_writes_and_reads_back_candles() {
+        let candles = candles();
+        let path = env::temp_dir().join(format!("oandars-export-{}-{}.parquet", process::id(), UTC::now().timestamp_subsec_nanos()));
+        write(&Column::for_candles(&candles), &candles, File::create(&path).unwrap()).unwrap();
+        let read = read(File::open(&path).unwrap()).unwrap();
+        fs::remove_file(&path).unwrap();
+
+        assert_eq!(serde_json::to_string(&read).unwrap(), serde_json::to_string(&candles).unwrap());
+        assert!(read[1].bid.is_none());
+        assert_eq!(read[1].mid.as_ref().unwrap().h.to_string(), "1.11420");
+    }
+
+    #[test]
+    fn it_reads_negative_decimals() {
+        let decimal = data_type::Decimal::from_i64(-111420, 18, 5);
+
+        assert_eq!(decimal_text(&decimal), "-1.11420");
+    }
+}
@@ -0,0 +1,85 @@
+use super::{Bar, Indicator};
+use super::atr::true_range;
+use super::ema::Ema;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct AdxOutput {
+    /// The strength of the trend, from 0 to 100
+    pub adx: f64,
+    /// The positive directional indicator, from 0 to 100
+    pub plus_di: f64,
+    /// The negative directional indicator, from 0 to 100
+    pub minus_di: f64
+}
+
+/// Average directional index over `period` bars, using Wilder’s smoothing
+pub struct Adx {
+    plus_dm: Ema,
+    minus_dm: Ema,
+    true_range: Ema,
+    adx: Ema,
+    previous: Option<Bar>
+}
+
+impl Adx {
+    pub fn new(period: usize) -> Adx {
+        Adx {
+            plus_dm: Ema::wilder(period),
+            minus_dm: Ema::wilder(period),
+            true_range: Ema::wilder(period),
+            adx: Ema::wilder(period),
+            previous: None
+        }
+    }
+}
+
+impl Default for Adx {
+    /// The usual 14 bar period
+    fn default() -> Adx {
+        Adx::new(14)
+    }
+}
+
+impl Indicator for Adx {
+    type Output = AdxOutput;
+
+    fn next(&mut self, bar: &Bar) -> Option<AdxOutput> {
+        let previous = match self.previous.replace(*bar) {
+            Some(previous) => previous,
+            None => return None
+        };
+        let up = bar.high - previous.high;
+        let down = previous.low - bar.low;
+        let plus_dm = self.plus_dm.next_value(if up > down && up > 0.0 { up } else { 0.0 });
+        let minus_dm = self.minus_dm.next_value(if down > up && down > 0.0 { down } else { 0.0 });
+        let range = self.true_range.next_value(true_range(bar, Some(previous.close)));
+
+        let (plus_dm, minus_dm, range) = match (plus_dm, minus_dm, range) {
+            (Some(plus_dm), Some(minus_dm), Some(range)) => (plus_dm, minus_dm, range),
+            _ => return None
+        };
+        let (plus_di, minus_di) = if range > 0.0 {
+            (100.0 * plus_dm / range, 100.0 * minus_dm / range)
+        } else {
+            (0.0, 0.0)
+        };
+        let total = plus_di + minus_di;
+        let dx = if total > 0.0 { 100.0 * (plus_di - minus_di).abs() / total } else { 0.0 };
+
+        self.adx.next_value(dx).map(|adx| AdxOutput { adx: adx, plus_di: plus_di, minus_di: minus_di })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use indicators::test_support::bars;
+
+    #[test]
+    fn it_finds_a_strong_trend_in_rising_bars() {
+        let result = Adx::new(2).batch(&bars(&[(2.0, 1.0, 2.0), (3.0, 2.0, 3.0), (4.0, 3.0, 4.0), (5.0, 4.0, 5.0)]));
+
+        assert_eq!(result[..3], [None, None, None]);
+        assert_eq!(result[3], Some(AdxOutput { adx: 100.0, plus_di: 100.0, minus_di: 0.0 }));
+    }
+}
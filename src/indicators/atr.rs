@@ -0,0 +1,53 @@
+use super::{Bar, Indicator};
+use super::ema::Ema;
+
+/// Average true range over `period` bars, using Wilder’s smoothing
+pub struct Atr {
+    average: Ema,
+    previous_close: Option<f64>
+}
+
+impl Atr {
+    pub fn new(period: usize) -> Atr {
+        Atr { average: Ema::wilder(period), previous_close: None }
+    }
+}
+
+/// The greatest of the bar’s range and the distance from the previous close
+/// to the bar’s high and low
+pub fn true_range(bar: &Bar, previous_close: Option<f64>) -> f64 {
+    let range = bar.high - bar.low;
+
+    match previous_close {
+        Some(close) => range.max((bar.high - close).abs()).max((bar.low - close).abs()),
+        None => range
+    }
+}
+
+impl Indicator for Atr {
+    type Output = f64;
+
+    fn next(&mut self, bar: &Bar) -> Option<f64> {
+        let range = true_range(bar, self.previous_close);
+        self.previous_close = Some(bar.close);
+
+        self.average.next_value(range)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use indicators::test_support::{assert_close, bars};
+
+    #[test]
+    fn it_includes_gaps_from_the_previous_close() {
+        let result = Atr::new(2).batch(&bars(&[(2.0, 1.0, 1.5), (1.8, 1.6, 1.7), (3.0, 2.5, 2.8)]));
+
+        assert_eq!(result[0], None);
+        // ranges of 1.0 and 0.3
+        assert_close(result[1].unwrap(), 0.65);
+        // the gap up from 1.7 to 3.0 is a true range of 1.3
+        assert_close(result[2].unwrap(), 0.975);
+    }
+}
@@ -0,0 +1,77 @@
+use std::collections::VecDeque;
+
+use super::{Bar, Indicator};
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct BollingerOutput {
+    pub lower: f64,
+    /// The simple moving average
+    pub middle: f64,
+    pub upper: f64
+}
+
+/// Bands `deviations` standard deviations either side of the simple moving
+/// average of the close over `period` bars
+pub struct BollingerBands {
+    period: usize,
+    deviations: f64,
+    values: VecDeque<f64>
+}
+
+impl BollingerBands {
+    pub fn new(period: usize, deviations: f64) -> BollingerBands {
+        assert!(period > 0, "the period must be at least one bar");
+
+        BollingerBands { period: period, deviations: deviations, values: VecDeque::with_capacity(period + 1) }
+    }
+
+    /// Add the next value of any series
+    pub fn next_value(&mut self, value: f64) -> Option<BollingerOutput> {
+        self.values.push_back(value);
+
+        if self.values.len() > self.period {
+            self.values.pop_front();
+        }
+
+        if self.values.len() < self.period {
+            return None
+        }
+
+        let n = self.period as f64;
+        let mean = self.values.iter().sum::<f64>() / n;
+        let variance = self.values.iter().map(|v| (v - mean).powi(2)).sum::<f64>() / n;
+        let width = self.deviations * variance.sqrt();
+
+        Some(BollingerOutput { lower: mean - width, middle: mean, upper: mean + width })
+    }
+}
+
+impl Default for BollingerBands {
+    /// Two standard deviations around a 20 bar average
+    fn default() -> BollingerBands {
+        BollingerBands::new(20, 2.0)
+    }
+}
+
+impl Indicator for BollingerBands {
+    type Output = BollingerOutput;
+
+    fn next(&mut self, bar: &Bar) -> Option<BollingerOutput> {
+        self.next_value(bar.close)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use indicators::test_support::closes;
+
+    #[test]
+    fn it_uses_the_population_standard_deviation() {
+        let result = BollingerBands::new(4, 2.0).batch(&closes(&[2.0, 4.0, 4.0, 6.0]));
+
+        // a mean of 4 and a standard deviation of sqrt(2)
+        let width = 2.0 * 2f64.sqrt();
+        assert_eq!(result[3], Some(BollingerOutput { lower: 4.0 - width, middle: 4.0, upper: 4.0 + width }));
+    }
+}
@@ -0,0 +1,57 @@
+use super::{Bar, Indicator};
+use super::sma::Sma;
+
+/// Exponential moving average of the close over `period` bars, seeded with
+/// the simple moving average of the first `period` bars
+pub struct Ema {
+    alpha: f64,
+    seed: Sma,
+    value: Option<f64>
+}
+
+impl Ema {
+    pub fn new(period: usize) -> Ema {
+        Ema::with_alpha(period, 2.0 / (period as f64 + 1.0))
+    }
+
+    /// An average with a smoothing factor of 1 / `period`, as used by Wilder
+    pub fn wilder(period: usize) -> Ema {
+        Ema::with_alpha(period, 1.0 / period as f64)
+    }
+
+    fn with_alpha(period: usize, alpha: f64) -> Ema {
+        Ema { alpha: alpha, seed: Sma::new(period), value: None }
+    }
+
+    /// Add the next value of any series
+    pub fn next_value(&mut self, value: f64) -> Option<f64> {
+        self.value = match self.value {
+            Some(previous) => Some(previous + self.alpha * (value - previous)),
+            None => self.seed.next_value(value)
+        };
+
+        self.value
+    }
+}
+
+impl Indicator for Ema {
+    type Output = f64;
+
+    fn next(&mut self, bar: &Bar) -> Option<f64> {
+        self.next_value(bar.close)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use indicators::test_support::closes;
+
+    #[test]
+    fn it_weights_recent_bars_more() {
+        let result = Ema::new(3).batch(&closes(&[1.0, 2.0, 3.0, 4.0, 5.0]));
+
+        assert_eq!(result, vec![None, None, Some(2.0), Some(3.0), Some(4.0)]);
+        assert_eq!(Ema::new(3).batch(&closes(&[2.0, 2.0, 2.0, 6.0])).last().unwrap(), &Some(4.0));
+    }
+}
@@ -0,0 +1,74 @@
+use super::{Bar, Indicator};
+use super::ema::Ema;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct MacdOutput {
+    /// The fast average minus the slow average
+    pub macd: f64,
+    /// The average of the MACD line
+    pub signal: f64,
+    /// The MACD line minus the signal line
+    pub histogram: f64
+}
+
+/// Moving average convergence divergence of the close
+pub struct Macd {
+    fast: Ema,
+    slow: Ema,
+    signal: Ema
+}
+
+impl Macd {
+    pub fn new(fast: usize, slow: usize, signal: usize) -> Macd {
+        Macd { fast: Ema::new(fast), slow: Ema::new(slow), signal: Ema::new(signal) }
+    }
+
+    /// Add the next value of any series
+    pub fn next_value(&mut self, value: f64) -> Option<MacdOutput> {
+        let fast = self.fast.next_value(value);
+        let slow = self.slow.next_value(value);
+        let macd = match (fast, slow) {
+            (Some(fast), Some(slow)) => fast - slow,
+            _ => return None
+        };
+
+        self.signal.next_value(macd).map(|signal| MacdOutput {
+            macd: macd,
+            signal: signal,
+            histogram: macd - signal
+        })
+    }
+}
+
+impl Default for Macd {
+    /// The usual 12, 26 and 9 bar averages
+    fn default() -> Macd {
+        Macd::new(12, 26, 9)
+    }
+}
+
+impl Indicator for Macd {
+    type Output = MacdOutput;
+
+    fn next(&mut self, bar: &Bar) -> Option<MacdOutput> {
+        self.next_value(bar.close)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use indicators::test_support::{assert_close, closes};
+
+    #[test]
+    fn it_waits_for_the_signal_line() {
+        let prices: Vec<f64> = (0..40).map(|i| i as f64).collect();
+        let result = Macd::default().batch(&closes(&prices));
+
+        assert!(result[32].is_none());
+        // a steady trend keeps the averages a fixed distance apart
+        let output = result[33].unwrap();
+        assert_close(output.macd, 7.0);
+        assert_close(output.histogram, 0.0);
+    }
+}
@@ -0,0 +1,148 @@
+//! Technical indicators over candlesticks.
+//!
+//! Every indicator is updated one bar at a time with `Indicator::next`, so it
+//! can follow a live series, and `Indicator::batch` runs it over a whole
+//! series at once. An indicator returns `None` until it has seen enough bars.
+
+pub mod adx;
+pub mod atr;
+pub mod bollinger;
+pub mod ema;
+pub mod macd;
+pub mod rsi;
+pub mod sma;
+pub mod stochastic;
+pub mod vwap;
+pub mod wma;
+
+use chrono::datetime::DateTime;
+use chrono::UTC;
+
+use instrument::candlestick::Candlestick;
+use instrument::candlestick_data::CandlestickData;
+use primitives::{Decimal, PriceValue};
+
+pub use self::adx::{Adx, AdxOutput};
+pub use self::atr::Atr;
+pub use self::bollinger::{BollingerBands, BollingerOutput};
+pub use self::ema::Ema;
+pub use self::macd::{Macd, MacdOutput};
+pub use self::rsi::Rsi;
+pub use self::sma::Sma;
+pub use self::stochastic::{Stochastic, StochasticOutput};
+pub use self::vwap::Vwap;
+pub use self::wma::Wma;
+
+/// The prices of one candlestick as floating point numbers
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Bar {
+    /// The start time of the candlestick
+    pub time: DateTime<UTC>,
+    pub open: f64,
+    pub high: f64,
+    pub low: f64,
+    pub close: f64,
+    /// The number of prices created during the candlestick
+    pub volume: f64
+}
+
+impl Bar {
+    pub fn from_data(time: DateTime<UTC>, data: &CandlestickData, volume: i32) -> Bar {
+        Bar {
+            time: time,
+            open: data.o.to_f64(),
+            high: data.h.to_f64(),
+            low: data.l.to_f64(),
+            close: data.c.to_f64(),
+            volume: volume as f64
+        }
+    }
+
+    /// The midpoint prices of a candlestick, or the average of its bid and
+    /// ask prices if only those were requested
+    pub fn mid(candle: &Candlestick) -> Option<Bar> {
+        if let Some(ref mid) = candle.mid {
+            return Some(Bar::from_data(candle.time, mid, candle.volume))
+        }
+
+        match (candle.bid.as_ref(), candle.ask.as_ref()) {
+            (Some(bid), Some(ask)) => {
+                let average = |b: PriceValue, a: PriceValue| PriceValue::from((b.value() + a.value()) / Decimal::from(2));
+                let mid = CandlestickData {
+                    o: average(bid.o, ask.o),
+                    h: average(bid.h, ask.h),
+                    l: average(bid.l, ask.l),
+                    c: average(bid.c, ask.c)
+                };
+
+                Some(Bar::from_data(candle.time, &mid, candle.volume))
+            },
+            _ => None
+        }
+    }
+
+    /// The bid prices of a candlestick, if they were requested
+    pub fn bid(candle: &Candlestick) -> Option<Bar> {
+        candle.bid.as_ref().map(|d| Bar::from_data(candle.time, d, candle.volume))
+    }
+
+    /// The ask prices of a candlestick, if they were requested
+    pub fn ask(candle: &Candlestick) -> Option<Bar> {
+        candle.ask.as_ref().map(|d| Bar::from_data(candle.time, d, candle.volume))
+    }
+
+    /// The average of the high, low and close
+    pub fn typical_price(&self) -> f64 {
+        (self.high + self.low + self.close) / 3.0
+    }
+}
+
+pub trait Indicator {
+    type Output;
+
+    /// Add the next bar of the series
+    fn next(&mut self, bar: &Bar) -> Option<Self::Output>;
+
+    /// Add every bar of a series, returning the value after each one
+    fn batch(&mut self, bars: &[Bar]) -> Vec<Option<Self::Output>> {
+        bars.iter().map(|bar| self.next(bar)).collect()
+    }
+
+    /// Add every candlestick of a series using its midpoint prices, or the
+    /// average of its bid and ask. Candlesticks with only bid or only ask
+    /// prices are skipped, with `None` as their value.
+    fn batch_candles(&mut self, candles: &[Candlestick]) -> Vec<Option<Self::Output>> {
+        candles.iter()
+            .map(|candle| Bar::mid(candle).and_then(|bar| self.next(&bar)))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+pub mod test_support {
+    use super::Bar;
+    use chrono::{Duration, TimeZone, UTC};
+
+    /// A minute bar for each (high, low, close), opening at the close before
+    pub fn bars(prices: &[(f64, f64, f64)]) -> Vec<Bar> {
+        let start = UTC.ymd(2017, 6, 21).and_hms(12, 0, 0);
+
+        prices.iter().enumerate().map(|(i, &(high, low, close))| Bar {
+            time: start + Duration::minutes(i as i64),
+            open: if i == 0 { close } else { prices[i - 1].2 },
+            high: high,
+            low: low,
+            close: close,
+            volume: 10.0
+        }).collect()
+    }
+
+    /// A minute bar for each close price
+    pub fn closes(prices: &[f64]) -> Vec<Bar> {
+        bars(&prices.iter().map(|&c| (c, c, c)).collect::<Vec<_>>())
+    }
+
+    pub fn assert_close(actual: f64, expected: f64) {
+        assert!((actual - expected).abs() < 1e-6, "{} != {}", actual, expected);
+    }
+}
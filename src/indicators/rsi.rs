@@ -0,0 +1,59 @@
+use super::{Bar, Indicator};
+use super::ema::Ema;
+
+/// Relative strength index of the close over `period` bars, using Wilder’s
+/// smoothing of the average gain and loss
+pub struct Rsi {
+    gains: Ema,
+    losses: Ema,
+    previous: Option<f64>
+}
+
+impl Rsi {
+    pub fn new(period: usize) -> Rsi {
+        Rsi { gains: Ema::wilder(period), losses: Ema::wilder(period), previous: None }
+    }
+
+    /// Add the next value of any series
+    pub fn next_value(&mut self, value: f64) -> Option<f64> {
+        let previous = match self.previous.replace(value) {
+            Some(previous) => previous,
+            None => return None
+        };
+        let change = value - previous;
+        let gain = self.gains.next_value(change.max(0.0));
+        let loss = self.losses.next_value((-change).max(0.0));
+
+        match (gain, loss) {
+            (Some(_), Some(loss)) if loss == 0.0 => Some(100.0),
+            (Some(gain), Some(loss)) => Some(100.0 - 100.0 / (1.0 + gain / loss)),
+            _ => None
+        }
+    }
+}
+
+impl Indicator for Rsi {
+    type Output = f64;
+
+    fn next(&mut self, bar: &Bar) -> Option<f64> {
+        self.next_value(bar.close)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use indicators::test_support::{assert_close, closes};
+
+    #[test]
+    fn it_compares_average_gains_and_losses() {
+        let result = Rsi::new(2).batch(&closes(&[1.0, 2.0, 1.5, 2.5]));
+
+        assert_eq!(result[..2], [None, None]);
+        // average gain 0.5 and loss 0.25
+        assert_close(result[2].unwrap(), 100.0 - 100.0 / 3.0);
+        // average gain 0.75 and loss 0.125
+        assert_close(result[3].unwrap(), 100.0 - 100.0 / 7.0);
+        assert_eq!(Rsi::new(2).batch(&closes(&[1.0, 2.0, 3.0]))[2], Some(100.0));
+    }
+}
@@ -0,0 +1,93 @@
+use std::collections::VecDeque;
+
+use super::{Bar, Indicator};
+
+/// Simple moving average of the close over `period` bars
+pub struct Sma {
+    period: usize,
+    values: VecDeque<f64>,
+    sum: f64
+}
+
+impl Sma {
+    pub fn new(period: usize) -> Sma {
+        assert!(period > 0, "the period must be at least one bar");
+
+        Sma { period: period, values: VecDeque::with_capacity(period + 1), sum: 0.0 }
+    }
+
+    /// Add the next value of any series
+    pub fn next_value(&mut self, value: f64) -> Option<f64> {
+        self.values.push_back(value);
+        self.sum += value;
+
+        if self.values.len() > self.period {
+            self.sum -= self.values.pop_front().unwrap();
+        }
+
+        if self.values.len() == self.period {
+            Some(self.sum / self.period as f64)
+        } else {
+            None
+        }
+    }
+}
+
+impl Indicator for Sma {
+    type Output = f64;
+
+    fn next(&mut self, bar: &Bar) -> Option<f64> {
+        self.next_value(bar.close)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{Duration, TimeZone, UTC};
+    use indicators::test_support::{assert_close, closes};
+    use instrument::candlestick::Candlestick;
+    use serde_json;
+
+    /// A candlestick with bid prices and ask prices 2 pips above them
+    fn bid_ask_candle(minute: i64, bid: &str) -> Candlestick {
+        let time = UTC.ymd(2017, 6, 21).and_hms(12, 0, 0) + Duration::minutes(minute);
+        let ask = bid.parse::<f64>().unwrap() + 0.0002;
+
+        serde_json::from_str(&format!(
+            r#"{{"time":"{}","bid":{{"o":{b},"h":{b},"l":{b},"c":{b}}},"ask":{{"o":{a},"h":{a},"l":{a},"c":{a}}},"volume":10,"complete":true}}"#,
+            time.to_rfc3339(), b = bid, a = ask
+        )).unwrap()
+    }
+
+    #[test]
+    fn it_averages_the_last_bars() {
+        let result = Sma::new(3).batch(&closes(&[1.0, 2.0, 3.0, 4.0, 5.0]));
+
+        assert_eq!(result, vec![None, None, Some(2.0), Some(3.0), Some(4.0)]);
+    }
+
+    #[test]
+    fn it_averages_the_bid_and_ask_of_candles_without_midpoints() {
+        let candles = vec![bid_ask_candle(0, "1.11000"), bid_ask_candle(1, "1.11020"), bid_ask_candle(2, "1.11040")];
+
+        let result = Sma::new(3).batch_candles(&candles);
+
+        assert_eq!(result[..2], [None, None]);
+        assert_close(result[2].unwrap(), 1.11030);
+    }
+
+    #[test]
+    fn it_skips_candles_with_only_bid_prices() {
+        let mut bid_only = bid_ask_candle(2, "1.11060");
+        bid_only.ask = None;
+        let candles = vec![
+            bid_ask_candle(0, "1.11000"), bid_ask_candle(1, "1.11020"), bid_only, bid_ask_candle(3, "1.11040")
+        ];
+
+        let result = Sma::new(3).batch_candles(&candles);
+
+        assert_eq!(result[..3], [None, None, None]);
+        assert_close(result[3].unwrap(), 1.11030);
+    }
+}
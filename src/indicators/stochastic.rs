@@ -0,0 +1,74 @@
+use std::collections::VecDeque;
+
+use super::{Bar, Indicator};
+use super::sma::Sma;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct StochasticOutput {
+    /// Where the close lies in the range of the last `k_period` bars, from 0
+    /// at the lowest low to 100 at the highest high
+    pub k: f64,
+    /// The simple moving average of %K
+    pub d: f64
+}
+
+/// The stochastic oscillator, %K over `k_period` bars and %D over `d_period`
+/// values of %K
+pub struct Stochastic {
+    k_period: usize,
+    bars: VecDeque<(f64, f64)>,
+    d: Sma
+}
+
+impl Stochastic {
+    pub fn new(k_period: usize, d_period: usize) -> Stochastic {
+        assert!(k_period > 0, "the period must be at least one bar");
+
+        Stochastic { k_period: k_period, bars: VecDeque::with_capacity(k_period + 1), d: Sma::new(d_period) }
+    }
+}
+
+impl Default for Stochastic {
+    /// %K over 14 bars and %D over 3
+    fn default() -> Stochastic {
+        Stochastic::new(14, 3)
+    }
+}
+
+impl Indicator for Stochastic {
+    type Output = StochasticOutput;
+
+    fn next(&mut self, bar: &Bar) -> Option<StochasticOutput> {
+        self.bars.push_back((bar.high, bar.low));
+
+        if self.bars.len() > self.k_period {
+            self.bars.pop_front();
+        }
+
+        if self.bars.len() < self.k_period {
+            return None
+        }
+
+        let high = self.bars.iter().map(|b| b.0).fold(f64::MIN, f64::max);
+        let low = self.bars.iter().map(|b| b.1).fold(f64::MAX, f64::min);
+        // a range without movement has the close in the middle
+        let k = if high > low { 100.0 * (bar.close - low) / (high - low) } else { 50.0 };
+
+        self.d.next_value(k).map(|d| StochasticOutput { k: k, d: d })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use indicators::test_support::bars;
+
+    #[test]
+    fn it_places_the_close_in_the_range() {
+        let result = Stochastic::new(2, 2).batch(&bars(&[(2.0, 1.0, 1.5), (3.0, 2.0, 3.0), (4.0, 2.0, 2.5)]));
+
+        assert_eq!(result[1], None);
+        // %K of 100 then 25
+        assert_eq!(result[2], Some(StochasticOutput { k: 25.0, d: 62.5 }));
+    }
+}
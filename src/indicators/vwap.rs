@@ -0,0 +1,81 @@
+use chrono::datetime::DateTime;
+use chrono::UTC;
+
+use instrument::alignment::Alignment;
+use super::{Bar, Indicator};
+
+/// Volume weighted average of the typical price.
+///
+/// The average runs over every bar since it was created or last `reset`, or
+/// over each trading day when created with `Vwap::daily`. Oanda volumes count
+/// price changes rather than units traded, so this weights bars by activity.
+pub struct Vwap {
+    /// Reset the average at the start of each day with this alignment
+    alignment: Option<Alignment>,
+    /// The start of the day being averaged
+    day: Option<DateTime<UTC>>,
+    price_volume: f64,
+    volume: f64
+}
+
+impl Vwap {
+    pub fn new() -> Vwap {
+        Vwap { alignment: None, day: None, price_volume: 0.0, volume: 0.0 }
+    }
+
+    /// An average that restarts each day, as aligned by `alignment`
+    pub fn daily(alignment: Alignment) -> Vwap {
+        Vwap { alignment: Some(alignment), ..Vwap::new() }
+    }
+
+    /// Forget every bar added so far
+    pub fn reset(&mut self) {
+        self.price_volume = 0.0;
+        self.volume = 0.0;
+    }
+}
+
+impl Default for Vwap {
+    fn default() -> Vwap {
+        Vwap::new()
+    }
+}
+
+impl Indicator for Vwap {
+    type Output = f64;
+
+    fn next(&mut self, bar: &Bar) -> Option<f64> {
+        if let Some(ref alignment) = self.alignment {
+            let day = alignment.start_of_day(bar.time);
+
+            if self.day != Some(day) {
+                self.day = Some(day);
+                self.price_volume = 0.0;
+                self.volume = 0.0;
+            }
+        }
+
+        self.price_volume += bar.typical_price() * bar.volume;
+        self.volume += bar.volume;
+
+        if self.volume > 0.0 { Some(self.price_volume / self.volume) } else { None }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{Duration, TimeZone};
+    use indicators::test_support::closes;
+
+    #[test]
+    fn it_restarts_each_day() {
+        let mut bars = closes(&[1.0, 2.0, 4.0]);
+        bars[1].volume = 30.0;
+        // 17:00 in New York, the start of the next day
+        bars[2].time = UTC.ymd(2017, 6, 21).and_hms(21, 0, 0) + Duration::minutes(1);
+
+        assert_eq!(Vwap::new().batch(&bars), vec![Some(1.0), Some(1.75), Some(2.2)]);
+        assert_eq!(Vwap::daily(Alignment::default()).batch(&bars), vec![Some(1.0), Some(1.75), Some(4.0)]);
+    }
+}
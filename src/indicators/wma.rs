@@ -0,0 +1,61 @@
+use std::collections::VecDeque;
+
+use super::{Bar, Indicator};
+
+/// Linearly weighted moving average of the close over `period` bars, the
+/// most recent bar has a weight of `period` and the oldest a weight of one
+pub struct Wma {
+    period: usize,
+    values: VecDeque<f64>
+}
+
+impl Wma {
+    pub fn new(period: usize) -> Wma {
+        assert!(period > 0, "the period must be at least one bar");
+
+        Wma { period: period, values: VecDeque::with_capacity(period + 1) }
+    }
+
+    /// Add the next value of any series
+    pub fn next_value(&mut self, value: f64) -> Option<f64> {
+        self.values.push_back(value);
+
+        if self.values.len() > self.period {
+            self.values.pop_front();
+        }
+
+        if self.values.len() < self.period {
+            return None
+        }
+
+        let weights = (self.period * (self.period + 1) / 2) as f64;
+        let sum: f64 = self.values.iter()
+            .enumerate()
+            .map(|(i, value)| value * (i + 1) as f64)
+            .sum();
+
+        Some(sum / weights)
+    }
+}
+
+impl Indicator for Wma {
+    type Output = f64;
+
+    fn next(&mut self, bar: &Bar) -> Option<f64> {
+        self.next_value(bar.close)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use indicators::test_support::{assert_close, closes};
+
+    #[test]
+    fn it_weights_bars_linearly() {
+        let result = Wma::new(3).batch(&closes(&[1.0, 2.0, 6.0]));
+
+        // (1 * 1 + 2 * 2 + 6 * 3) / 6
+        assert_close(result[2].unwrap(), 23.0 / 6.0);
+    }
+}
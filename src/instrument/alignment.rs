@@ -0,0 +1,103 @@
+use chrono::datetime::DateTime;
+use chrono::naive::datetime::NaiveDateTime;
+use chrono::naive::date::NaiveDate;
+use chrono::{Datelike, Duration, TimeZone, Timelike, UTC, Weekday};
+use chrono_tz::Tz;
+use chrono_tz::America::New_York;
+
+/// How candlesticks with daily and weekly alignment are aligned, the same
+/// settings `PricingQuery` sends as dailyAlignment, alignmentTimezone and
+/// weeklyAlignment
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Alignment {
+    /// The hour of the day (in the alignment timezone) each day starts at.
+    /// [default=17, minimum=0, maximum=23]
+    pub daily_alignment: u32,
+    /// The timezone the daily alignment hour is in. [default=America/New_York]
+    pub alignment_timezone: Tz,
+    /// The day of the week each week starts on. [default=Friday]
+    pub weekly_alignment: Weekday
+}
+
+impl Default for Alignment {
+    fn default() -> Alignment {
+        Alignment {
+            daily_alignment: 17,
+            alignment_timezone: New_York,
+            weekly_alignment: Weekday::Fri
+        }
+    }
+}
+
+impl Alignment {
+    /// The start of the day containing `time`
+    pub fn start_of_day(&self, time: DateTime<UTC>) -> DateTime<UTC> {
+        let local = time.with_timezone(&self.alignment_timezone).naive_local();
+        let mut date = local.date();
+
+        if local.time().hour() < self.daily_alignment {
+            date = date.pred();
+        }
+
+        self.day_start(date)
+    }
+
+    /// The start of the day after the one starting at `start_of_day`
+    pub fn next_day(&self, start_of_day: DateTime<UTC>) -> DateTime<UTC> {
+        let date = self.trading_date(start_of_day);
+
+        self.day_start(date.succ())
+    }
+
+    /// The start of the week containing `time`
+    pub fn start_of_week(&self, time: DateTime<UTC>) -> DateTime<UTC> {
+        let mut date = self.trading_date(self.start_of_day(time));
+
+        while date.weekday() != self.weekly_alignment {
+            date = date.pred();
+        }
+
+        self.day_start(date)
+    }
+
+    /// The local date of the day starting at `start_of_day`
+    pub fn trading_date(&self, start_of_day: DateTime<UTC>) -> NaiveDate {
+        start_of_day.with_timezone(&self.alignment_timezone).naive_local().date()
+    }
+
+    /// The instant the day for `date` starts
+    pub fn day_start(&self, date: NaiveDate) -> DateTime<UTC> {
+        local_to_utc(&self.alignment_timezone, date.and_hms(self.daily_alignment, 0, 0))
+    }
+}
+
+/// The instant a local time happens in `tz`. A local time skipped by a
+/// daylight saving transition is moved forward past the transition.
+pub fn local_to_utc(tz: &Tz, local: NaiveDateTime) -> DateTime<UTC> {
+    let mut local = local;
+
+    loop {
+        if let Some(time) = tz.from_local_datetime(&local).earliest() {
+            return time.with_timezone(&UTC)
+        }
+        local = local + Duration::minutes(30);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn it_aligns_days_and_weeks_across_daylight_saving() {
+        let alignment = Alignment::default();
+        // 2017-03-12 is the start of daylight saving time in New York
+        let before = UTC.ymd(2017, 3, 10).and_hms(23, 0, 0);
+        let after = UTC.ymd(2017, 3, 14).and_hms(20, 0, 0);
+
+        assert_eq!(alignment.start_of_day(before), UTC.ymd(2017, 3, 10).and_hms(22, 0, 0));
+        assert_eq!(alignment.start_of_day(after), UTC.ymd(2017, 3, 13).and_hms(21, 0, 0));
+        assert_eq!(alignment.start_of_week(after), UTC.ymd(2017, 3, 10).and_hms(22, 0, 0));
+        assert_eq!(alignment.next_day(UTC.ymd(2017, 3, 10).and_hms(22, 0, 0)), UTC.ymd(2017, 3, 11).and_hms(22, 0, 0));
+    }
+}
@@ -0,0 +1,32 @@
+use primitives::{Decimal, DecimalNumber, PriceValue};
+
+/// A price bucket of an order book or position book
+pub trait Bucket {
+    /// The lowest price (inclusive) covered by the bucket. The bucket covers
+    /// the price range from the price to price + the book’s bucketWidth.
+    fn price(&self) -> PriceValue;
+    /// The percentage of the total number of orders or positions that are
+    /// long and whose price lies within the bucket.
+    fn long_count_percent(&self) -> DecimalNumber;
+    /// The percentage of the total number of orders or positions that are
+    /// short and whose price lies within the bucket.
+    fn short_count_percent(&self) -> DecimalNumber;
+}
+
+/// The bucket covering `price`, or if no bucket does, the bucket whose
+/// midpoint is closest to it. Each bucket covers `width` from its price.
+pub fn nearest_bucket<B: Bucket>(buckets: &[B], width: PriceValue, price: PriceValue) -> Option<&B> {
+    let half = width.0 / Decimal::from(2);
+
+    buckets.iter()
+        .find(|b| b.price() <= price && price.0 < b.price().0 + width.0)
+        .or_else(|| buckets.iter().min_by_key(|b| (b.price().0 + half - price.0).abs()))
+}
+
+/// The long percentage minus the short percentage over every bucket. A
+/// positive value means more of the book is long than short.
+pub fn net_sentiment<B: Bucket>(buckets: &[B]) -> Decimal {
+    buckets.iter()
+        .map(|b| b.long_count_percent().0 - b.short_count_percent().0)
+        .fold(Decimal::from(0), |sum, x| sum + x)
+}
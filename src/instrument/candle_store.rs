@@ -0,0 +1,444 @@
+use std::collections::BTreeMap;
+use std::fs::{self, File};
+use std::io::{BufReader, BufWriter, ErrorKind};
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+use serde_json;
+
+use chrono::datetime::DateTime;
+use chrono::{Datelike, Duration, TimeZone, UTC};
+
+use client::Client;
+use error::Result;
+use primitives::InstrumentName;
+
+use super::candlestick::Candlestick;
+use super::candlestick_granularity::CandlestickGranularity;
+use super::price_components::PriceComponents;
+
+/// A time range of candlestick start times, `from` inclusive and `to`
+/// exclusive
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq)]
+pub struct Coverage {
+    pub from: DateTime<UTC>,
+    pub to: DateTime<UTC>
+}
+
+/// How much of a series each file of the store holds. Short granularities
+/// are kept a day to a file, so adding to a series of years of seconds or
+/// minutes only rewrites the days that changed.
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Chunk {
+    Day,
+    Month,
+    Year
+}
+
+impl Chunk {
+    fn for_granularity(granularity: CandlestickGranularity) -> Chunk {
+        let duration = granularity.duration();
+
+        if duration < Duration::hours(1) {
+            Chunk::Day
+        } else if duration < Duration::days(1) {
+            Chunk::Month
+        } else {
+            Chunk::Year
+        }
+    }
+
+    /// The start of the chunk holding `time`
+    fn start(&self, time: DateTime<UTC>) -> DateTime<UTC> {
+        match *self {
+            Chunk::Day => UTC.ymd(time.year(), time.month(), time.day()).and_hms(0, 0, 0),
+            Chunk::Month => UTC.ymd(time.year(), time.month(), 1).and_hms(0, 0, 0),
+            Chunk::Year => UTC.ymd(time.year(), 1, 1).and_hms(0, 0, 0)
+        }
+    }
+
+    /// The start of the chunk after the one starting at `start`
+    fn next(&self, start: DateTime<UTC>) -> DateTime<UTC> {
+        match *self {
+            Chunk::Day => start + Duration::days(1),
+            Chunk::Month if start.month() == 12 => UTC.ymd(start.year() + 1, 1, 1).and_hms(0, 0, 0),
+            Chunk::Month => UTC.ymd(start.year(), start.month() + 1, 1).and_hms(0, 0, 0),
+            Chunk::Year => UTC.ymd(start.year() + 1, 1, 1).and_hms(0, 0, 0)
+        }
+    }
+
+    /// The name of the file of the chunk starting at `start`
+    fn file_name(&self, start: DateTime<UTC>) -> String {
+        let format = match *self {
+            Chunk::Day => "%Y-%m-%d",
+            Chunk::Month => "%Y-%m",
+            Chunk::Year => "%Y"
+        };
+
+        format!("{}.json", start.format(format))
+    }
+}
+
+/// A local cache of candlesticks. Each combination of instrument,
+/// granularity and price components is kept in its own directory inside the
+/// store's directory, with the time ranges it already covers in
+/// `coverage.json` and the candlesticks split into a file per day, month or
+/// year depending on the granularity. Only the missing ranges are ever
+/// requested from Oanda, and only the files they fall in are rewritten.
+///
+/// Incomplete candlesticks are returned to the caller but never stored.
+pub struct CandleStore<'a> {
+    /// The directory holding the store's files
+    dir: PathBuf,
+    /// the client
+    client: &'a Client<'a>
+}
+
+impl <'a>CandleStore<'a> {
+    pub fn new<P: AsRef<Path>>(client: &'a Client, dir: P) -> CandleStore<'a> {
+        CandleStore {
+            dir: dir.as_ref().to_path_buf(),
+            client: client
+        }
+    }
+
+    /// Every candlestick starting in the range from `from` up to `to`, loading
+    /// any part of the range the store does not have from Oanda
+    pub fn candles(
+        &self,
+        instrument: &InstrumentName,
+        granularity: CandlestickGranularity,
+        price: PriceComponents,
+        from: DateTime<UTC>,
+        to: DateTime<UTC>
+    ) -> Result<Vec<Candlestick>> {
+        self.sync(instrument, granularity, price, from, to, |from, to| {
+            let mut query = self.client.pricing_for(instrument.clone(), from);
+            query.with_granularity(granularity).with_price(price);
+
+            query.candles_between(from, to).collect()
+        })
+    }
+
+    /// The ranges already stored for the given instrument, granularity and
+    /// price components
+    pub fn coverage(
+        &self,
+        instrument: &InstrumentName,
+        granularity: CandlestickGranularity,
+        price: PriceComponents
+    ) -> Result<Vec<Coverage>> {
+        let dir = self.series_dir(instrument, granularity, price);
+
+        load(&dir.join(COVERAGE_FILE))
+    }
+
+    fn sync<F>(
+        &self,
+        instrument: &InstrumentName,
+        granularity: CandlestickGranularity,
+        price: PriceComponents,
+        from: DateTime<UTC>,
+        to: DateTime<UTC>,
+        fetch: F
+    ) -> Result<Vec<Candlestick>>
+        where F: Fn(DateTime<UTC>, DateTime<UTC>) -> Result<Vec<Candlestick>>
+    {
+        let dir = self.series_dir(instrument, granularity, price);
+        let chunk = Chunk::for_granularity(granularity);
+        let mut coverage: Vec<Coverage> = load(&dir.join(COVERAGE_FILE))?;
+        let mut fetched: BTreeMap<DateTime<UTC>, Vec<Candlestick>> = BTreeMap::new();
+        let mut incomplete = Vec::new();
+        let mut covered = false;
+        let mut failed = None;
+
+        for missing in missing(&coverage, from, to) {
+            // a candlestick that starts this long before the request is complete
+            let mut covered_to = earliest(missing.to, UTC::now() - granularity.duration());
+
+            let candles = match fetch(missing.from, missing.to) {
+                Ok(candles) => candles,
+                Err(err) => {
+                    // keep the ranges already fetched
+                    failed = Some(err);
+                    break
+                }
+            };
+
+            for candle in candles {
+                if candle.complete {
+                    fetched.entry(chunk.start(candle.time)).or_insert_with(Vec::new).push(candle);
+                } else {
+                    covered_to = earliest(covered_to, candle.time);
+                    incomplete.push(candle);
+                }
+            }
+
+            if covered_to > missing.from {
+                coverage.push(Coverage { from: missing.from, to: covered_to });
+                covered = true;
+            }
+        }
+
+        // the candlesticks are written before the coverage, so the coverage
+        // never claims a range that is not stored
+        for (start, candles) in fetched {
+            let path = dir.join(chunk.file_name(start));
+            let mut stored = by_time(load(&path)?);
+            stored.extend(candles.into_iter().map(|c| (c.time, c)));
+
+            save(&path, &stored.values().collect::<Vec<_>>())?;
+        }
+
+        if covered {
+            save(&dir.join(COVERAGE_FILE), &merge(coverage))?;
+        }
+
+        if let Some(err) = failed {
+            return Err(err)
+        }
+
+        let mut result = Vec::new();
+        let mut start = chunk.start(from);
+        while start < to {
+            let stored: Vec<Candlestick> = load(&dir.join(chunk.file_name(start)))?;
+            result.extend(stored.into_iter().filter(|c| c.time >= from && c.time < to));
+            start = chunk.next(start);
+        }
+        result.extend(incomplete.into_iter().filter(|c| c.time >= from && c.time < to));
+        result.sort_by_key(|c| c.time);
+
+        Ok(result)
+    }
+
+    /// The directory of the given instrument, granularity and price components
+    fn series_dir(&self, instrument: &InstrumentName, granularity: CandlestickGranularity, price: PriceComponents) -> PathBuf {
+        self.dir.join(format!("{}-{}-{}", instrument, granularity, price))
+    }
+}
+
+/// The file in a series' directory listing the ranges it covers
+const COVERAGE_FILE: &'static str = "coverage.json";
+
+fn by_time(candles: Vec<Candlestick>) -> BTreeMap<DateTime<UTC>, Candlestick> {
+    candles.into_iter().map(|c| (c.time, c)).collect()
+}
+
+/// The contents of a file of the store, or an empty list if there is no file
+fn load<T: Deserialize>(path: &Path) -> Result<Vec<T>> {
+    match File::open(path) {
+        Ok(file) => Ok(serde_json::from_reader(BufReader::new(file))?),
+        Err(ref err) if err.kind() == ErrorKind::NotFound => Ok(Vec::new()),
+        Err(err) => Err(err.into())
+    }
+}
+
+fn save<T: Serialize>(path: &Path, value: &T) -> Result<()> {
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)?;
+    }
+
+    // write to a temporary file first so a failure never corrupts the store
+    let temp = path.with_extension("json.tmp");
+    serde_json::to_writer(&mut BufWriter::new(File::create(&temp)?), value)?;
+    fs::rename(&temp, path)?;
+
+    Ok(())
+}
+
+fn earliest(a: DateTime<UTC>, b: DateTime<UTC>) -> DateTime<UTC> {
+    if a < b { a } else { b }
+}
+
+/// The parts of the range from `from` up to `to` not in `coverage`
+fn missing(coverage: &[Coverage], from: DateTime<UTC>, to: DateTime<UTC>) -> Vec<Coverage> {
+    let mut result = Vec::new();
+    let mut cursor = from;
+
+    for range in merge(coverage.to_vec()) {
+        if range.to <= cursor { continue }
+        if range.from >= to { break }
+
+        if range.from > cursor {
+            result.push(Coverage { from: cursor, to: range.from });
+        }
+        cursor = range.to;
+    }
+
+    if cursor < to {
+        result.push(Coverage { from: cursor, to: to });
+    }
+
+    result
+}
+
+/// Sort the ranges and join any that overlap or touch
+fn merge(mut coverage: Vec<Coverage>) -> Vec<Coverage> {
+    coverage.sort_by_key(|c| c.from);
+
+    let mut result: Vec<Coverage> = Vec::new();
+
+    for range in coverage {
+        if let Some(last) = result.last_mut() {
+            if range.from <= last.to {
+                if range.to > last.to { last.to = range.to; }
+                continue
+            }
+        }
+        result.push(range);
+    }
+
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cell::RefCell;
+    use std::env;
+    use std::process;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use chrono::prelude::*;
+    use error::Error;
+
+    static TEMP_DIRS: AtomicUsize = AtomicUsize::new(0);
+
+    /// A directory of its own for each test, removed when the test ends
+    struct TempDir(PathBuf);
+
+    impl TempDir {
+        fn new() -> TempDir {
+            let name = format!("oandars-candle-store-{}-{}", process::id(), TEMP_DIRS.fetch_add(1, Ordering::SeqCst));
+
+            TempDir(env::temp_dir().join(name))
+        }
+    }
+
+    impl Drop for TempDir {
+        fn drop(&mut self) {
+            let _ = fs::remove_dir_all(&self.0);
+        }
+    }
+
+    fn at(hour: u32) -> DateTime<UTC> {
+        UTC.ymd(2017, 6, 21).and_hms(hour, 0, 0)
+    }
+
+    fn candle(hour: u32, complete: bool) -> Candlestick {
+        candle_at(at(hour), complete)
+    }
+
+    fn candle_at(time: DateTime<UTC>, complete: bool) -> Candlestick {
+        serde_json::from_str(&format!(
+            r#"{{"time":"{}","mid":{{"o":1.1,"h":1.2,"l":1.0,"c":1.15}},"volume":10,"complete":{}}}"#,
+            time.to_rfc3339(),
+            complete
+        )).unwrap()
+    }
+
+    #[test]
+    fn it_finds_missing_ranges() {
+        let coverage = vec![
+            Coverage { from: at(4), to: at(6) },
+            Coverage { from: at(1), to: at(2) },
+            Coverage { from: at(2), to: at(3) }
+        ];
+
+        assert_eq!(
+            missing(&coverage, at(0), at(8)),
+            vec![
+                Coverage { from: at(0), to: at(1) },
+                Coverage { from: at(3), to: at(4) },
+                Coverage { from: at(6), to: at(8) }
+            ]
+        );
+        assert_eq!(missing(&coverage, at(1), at(3)), vec![]);
+    }
+
+    #[test]
+    fn it_only_fetches_missing_ranges_and_never_stores_incomplete_candles() {
+        let dir = TempDir::new();
+        let client = Client::new("", "");
+        let store = CandleStore::new(&client, &dir.0);
+        let requests = RefCell::new(Vec::new());
+        let fetch = |from: DateTime<UTC>, to: DateTime<UTC>| {
+            requests.borrow_mut().push(Coverage { from: from, to: to });
+            let hours = from.hour()..to.hour();
+
+            Ok(hours.map(|h| candle(h, h != 5)).collect())
+        };
+        let eur_usd = "EUR_USD".parse().unwrap();
+        let granularity = CandlestickGranularity::H1;
+        let price = PriceComponents::MID;
+
+        let first = store.sync(&eur_usd, granularity, price, at(0), at(3), &fetch).unwrap();
+        let second = store.sync(&eur_usd, granularity, price, at(1), at(7), &fetch).unwrap();
+
+        assert_eq!(first.len(), 3);
+        assert_eq!(second.iter().map(|c| c.time.hour()).collect::<Vec<_>>(), vec![1, 2, 3, 4, 5, 6]);
+        assert_eq!(
+            *requests.borrow(),
+            vec![Coverage { from: at(0), to: at(3) }, Coverage { from: at(3), to: at(7) }]
+        );
+        assert_eq!(
+            store.coverage(&eur_usd, granularity, price).unwrap(),
+            vec![Coverage { from: at(0), to: at(5) }]
+        );
+    }
+
+    #[test]
+    fn it_keeps_the_ranges_fetched_before_a_failure() {
+        let dir = TempDir::new();
+        let client = Client::new("", "");
+        let store = CandleStore::new(&client, &dir.0);
+        let eur_usd = "EUR_USD".parse().unwrap();
+        let granularity = CandlestickGranularity::H1;
+        let price = PriceComponents::MID;
+        let fetch = |from: DateTime<UTC>, to: DateTime<UTC>| {
+            Ok((from.hour()..to.hour()).map(|h| candle(h, true)).collect())
+        };
+        store.sync(&eur_usd, granularity, price, at(2), at(3), &fetch).unwrap();
+
+        // Fetches 0 to 2 and then fails on 3 to 5
+        let failing = |from: DateTime<UTC>, to: DateTime<UTC>| {
+            if from == at(3) { Err(Error::Format("rejected".to_string())) } else { fetch(from, to) }
+        };
+        assert!(store.sync(&eur_usd, granularity, price, at(0), at(5), &failing).is_err());
+        assert_eq!(
+            store.coverage(&eur_usd, granularity, price).unwrap(),
+            vec![Coverage { from: at(0), to: at(3) }]
+        );
+    }
+
+    #[test]
+    fn it_only_rewrites_the_chunks_a_sync_adds_to() {
+        let dir = TempDir::new();
+        let client = Client::new("", "");
+        let store = CandleStore::new(&client, &dir.0);
+        let eur_usd = "EUR_USD".parse().unwrap();
+        let granularity = CandlestickGranularity::H1;
+        let price = PriceComponents::MID;
+        let fetch = |from: DateTime<UTC>, to: DateTime<UTC>| {
+            let hours = to.signed_duration_since(from).num_hours();
+
+            Ok((0..hours).map(|h| candle_at(from + Duration::hours(h), true)).collect())
+        };
+        let june = UTC.ymd(2017, 6, 30).and_hms(22, 0, 0);
+        let series = dir.0.join("EUR_USD-H1-M");
+
+        let first = store.sync(&eur_usd, granularity, price, june, june + Duration::hours(4), &fetch).unwrap();
+        assert_eq!(first.len(), 4);
+        assert!(series.join("2017-06.json").exists());
+        assert!(series.join("2017-07.json").exists());
+
+        // Only July is written by a sync later in July
+        fs::write(series.join("2017-06.json"), "[]").unwrap();
+        let second = store.sync(&eur_usd, granularity, price, june + Duration::hours(4), june + Duration::hours(6), &fetch).unwrap();
+        assert_eq!(second.len(), 2);
+        assert_eq!(fs::read_to_string(series.join("2017-06.json")).unwrap(), "[]");
+        assert_eq!(
+            store.sync(&eur_usd, granularity, price, june, june + Duration::hours(6), &fetch).unwrap().len(),
+            4
+        );
+    }
+}
@@ -0,0 +1,216 @@
+use std::collections::VecDeque;
+
+use chrono::datetime::DateTime;
+use chrono::Duration;
+use chrono::UTC;
+
+use error::Result;
+
+use super::candlestick::Candlestick;
+use super::pricing_query::PricingQuery;
+
+/// The maximum number of candlesticks Oanda will return for a single request
+pub const MAX_CANDLES_PER_REQUEST: i64 = 5000;
+
+/// Loads up to `count` candlesticks starting at a time
+type Fetch<'a> = Box<dyn FnMut(DateTime<UTC>, i32) -> Result<Vec<Candlestick>> + 'a>;
+
+/// An iterator over every candlestick in a time range. Requests are issued
+/// lazily, at most 5000 candlesticks at a time, with each request starting
+/// from the time of the last candlestick received. A query Oanda limits with
+/// a count is loaded with a single request instead.
+///
+/// A failed request is returned as an error, after which the iterator ends.
+pub struct Candles<'a> {
+    /// Loads each batch of candlesticks
+    fetch: Fetch<'a>,
+    /// The start of the time range
+    from: DateTime<UTC>,
+    /// Candlesticks at or after this time are not returned
+    to: DateTime<UTC>,
+    /// The time-range covered by a single candlestick
+    step: Duration,
+    /// The time of the last candlestick loaded
+    last: Option<DateTime<UTC>>,
+    /// Candlesticks loaded but not yet returned
+    buffer: VecDeque<Candlestick>,
+    /// Set once the end of the range has been reached
+    done: bool,
+    /// Whether more requests follow the first, false for a single request
+    /// whose range is left to Oanda
+    paginate: bool
+}
+
+impl <'a>Candles<'a> {
+    pub fn new(query: PricingQuery<'a>, from: DateTime<UTC>, to: DateTime<UTC>, step: Duration) -> Candles<'a> {
+        let mut query = query;
+        let fetch = move |start: DateTime<UTC>, count: i32| {
+            query.with_from(start).with_count(count);
+            if start != from {
+                query.with_include_first(false);
+            }
+
+            query.try_execute().map(|pricing| pricing.candles)
+        };
+
+        Candles::with_fetch(Box::new(fetch), from, to, step)
+    }
+
+    /// The candlesticks returned by a single request for `query`, such as the
+    /// most recent `count` candlesticks up to its end
+    pub fn single(query: PricingQuery<'a>) -> Candles<'a> {
+        let now = UTC::now();
+        let fetch = move |_, _| query.try_execute().map(|pricing| pricing.candles);
+
+        Candles { paginate: false, ..Candles::with_fetch(Box::new(fetch), now, now, Duration::zero()) }
+    }
+
+    fn with_fetch(fetch: Fetch<'a>, from: DateTime<UTC>, to: DateTime<UTC>, step: Duration) -> Candles<'a> {
+        Candles {
+            fetch: fetch,
+            from: from,
+            to: to,
+            step: step,
+            last: None,
+            buffer: VecDeque::new(),
+            done: false,
+            paginate: true
+        }
+    }
+
+    /// The next candlestick in the range, failing if a request to load more
+    /// candlesticks fails
+    pub fn next_candle(&mut self) -> Result<Option<Candlestick>> {
+        loop {
+            if let Some(candle) = self.buffer.pop_front() {
+                if self.paginate && candle.time >= self.to {
+                    self.done = true;
+                    self.buffer.clear();
+
+                    return Ok(None)
+                }
+
+                return Ok(Some(candle))
+            }
+
+            if self.done { return Ok(None) }
+
+            if let Err(err) = self.load_next() {
+                self.done = true;
+
+                return Err(err)
+            }
+        }
+    }
+
+    fn load_next(&mut self) -> Result<()> {
+        if !self.paginate {
+            let candles = (self.fetch)(self.from, 0)?;
+            self.buffer.extend(candles);
+            self.done = true;
+
+            return Ok(())
+        }
+
+        let from = self.last.unwrap_or(self.from);
+        let count = batch_size(from, self.to, self.step);
+        let candles = (self.fetch)(from, count)?;
+
+        let last = self.last;
+        self.buffer.extend(
+            candles.into_iter().filter(|c| last.map_or(true, |l| c.time > l))
+        );
+
+        match self.buffer.back() {
+            Some(candle) => self.last = Some(candle.time),
+            None => self.done = true
+        }
+
+        Ok(())
+    }
+}
+
+impl <'a>Iterator for Candles<'a> {
+    type Item = Result<Candlestick>;
+
+    fn next(&mut self) -> Option<Result<Candlestick>> {
+        match self.next_candle() {
+            Ok(candle) => candle.map(Ok),
+            Err(err) => Some(Err(err))
+        }
+    }
+}
+
+/// The number of candlesticks needed to cover the range from `from` to `to`,
+/// limited to what Oanda will return for one request
+fn batch_size(from: DateTime<UTC>, to: DateTime<UTC>, step: Duration) -> i32 {
+    let range = to.signed_duration_since(from).num_seconds();
+    let step = step.num_seconds();
+    // one extra for the candlestick covering `from`
+    let needed = (range + step - 1) / step + 1;
+
+    needed.max(1).min(MAX_CANDLES_PER_REQUEST) as i32
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cell::RefCell;
+    use chrono::prelude::*;
+    use error::Error;
+    use serde_json;
+
+    fn candle(time: DateTime<UTC>) -> Candlestick {
+        serde_json::from_str(&format!(
+            r#"{{"time":"{}","mid":{{"o":1.1,"h":1.2,"l":1.0,"c":1.15}},"volume":10,"complete":true}}"#,
+            time.to_rfc3339()
+        )).unwrap()
+    }
+
+    #[test]
+    fn it_limits_the_batch_size() {
+        let from: DateTime<UTC> = UTC.ymd(2016, 1, 1).and_hms(0, 0, 0);
+        let to: DateTime<UTC> = UTC.ymd(2017, 1, 1).and_hms(0, 0, 0);
+
+        assert_eq!(batch_size(from, to, Duration::minutes(1)), 5000);
+        assert_eq!(batch_size(from, from + Duration::minutes(90), Duration::hours(1)), 3);
+        assert_eq!(batch_size(to, from, Duration::hours(1)), 1);
+    }
+
+    #[test]
+    fn it_loads_batches_until_the_end_of_the_range() {
+        let from: DateTime<UTC> = UTC.ymd(2017, 6, 21).and_hms(0, 0, 0);
+        let to = from + Duration::minutes(7000);
+        let requests = RefCell::new(Vec::new());
+        // Serves every minute from the start of the request, including the
+        // candlestick at the start
+        let fetch = |start: DateTime<UTC>, count: i32| {
+            requests.borrow_mut().push((start, count));
+
+            Ok((0..count as i64)
+                .map(|i| start + Duration::minutes(i))
+                .take_while(|&time| time < from + Duration::minutes(7200))
+                .map(candle)
+                .collect())
+        };
+
+        let candles = Candles::with_fetch(Box::new(fetch), from, to, Duration::minutes(1))
+            .collect::<Result<Vec<_>>>()
+            .unwrap();
+
+        assert_eq!(candles.len(), 7000);
+        assert!(candles.windows(2).all(|pair| pair[1].time.signed_duration_since(pair[0].time) == Duration::minutes(1)));
+        assert_eq!(candles[6999].time, to - Duration::minutes(1));
+        assert_eq!(*requests.borrow(), vec![(from, 5000), (from + Duration::minutes(4999), 2002)]);
+    }
+
+    #[test]
+    fn it_ends_after_a_failed_request() {
+        let from: DateTime<UTC> = UTC.ymd(2017, 6, 21).and_hms(0, 0, 0);
+        let fetch = |_, _| Err(Error::Format("rejected".to_string()));
+        let mut candles = Candles::with_fetch(Box::new(fetch), from, from + Duration::hours(1), Duration::minutes(1));
+
+        assert!(candles.next().unwrap().is_err());
+        assert!(candles.next().is_none());
+    }
+}
@@ -4,18 +4,21 @@ use chrono::UTC;
 use super::candlestick_granularity::CandlestickGranularity;
 use super::candlestick_data::CandlestickData;
 
-#[derive(Deserialize)]
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
 pub struct Candlestick {
     /// The start time of the candlestick
     pub time: DateTime<UTC>,
     /// The candlestick data based on bids. Only provided if bid-based candles
     /// were requested.
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub bid: Option<CandlestickData>,
     /// The candlestick data based on asks. Only provided if ask-based candles
     /// were requested.
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub ask: Option<CandlestickData>,
     /// The candlestick data based on midpoints. Only provided if midpoint-based
     /// candles were requested.
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub mid: Option<CandlestickData>,
     /// The number of prices created during the time-range represented by the
     /// candlestick.
@@ -1,13 +1,15 @@
-#[derive(Deserialize)]
+use primitives::PriceValue;
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
 pub struct CandlestickData {
     /// The first (open) price in the time-range represented by the candlestick.
-    pub o: f32,
+    pub o: PriceValue,
     /// The highest price in the time-range represented by the candlestick.
-    pub h: f32,
+    pub h: PriceValue,
     /// The lowest price in the time-range represented by the candlestick.
-    pub l: f32,
+    pub l: PriceValue,
     /// The last (closing) price in the time-range represented by the
     /// candlestick.
-    pub c: f32
+    pub c: PriceValue
 }
 
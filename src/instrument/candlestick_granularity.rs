@@ -1,4 +1,11 @@
-#[derive(Deserialize)]
+use std::fmt;
+use std::str::FromStr;
+
+use chrono::Duration;
+
+use primitives::ParseError;
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq, Hash)]
 pub enum CandlestickGranularity {
     /// 5 second candlesticks, minute alignment
     S5,
@@ -43,3 +50,74 @@ pub enum CandlestickGranularity {
     /// 1 month candlesticks, aligned to first day of the month
     M,
 }
+
+impl CandlestickGranularity {
+    pub const ALL: [CandlestickGranularity; 21] = [
+        CandlestickGranularity::S5, CandlestickGranularity::S10, CandlestickGranularity::S15,
+        CandlestickGranularity::S30, CandlestickGranularity::M1, CandlestickGranularity::M2,
+        CandlestickGranularity::M4, CandlestickGranularity::M5, CandlestickGranularity::M10,
+        CandlestickGranularity::M15, CandlestickGranularity::M30, CandlestickGranularity::H1,
+        CandlestickGranularity::H2, CandlestickGranularity::H3, CandlestickGranularity::H4,
+        CandlestickGranularity::H6, CandlestickGranularity::H8, CandlestickGranularity::H12,
+        CandlestickGranularity::D, CandlestickGranularity::W, CandlestickGranularity::M
+    ];
+
+    /// The time-range covered by a single candlestick. Weekly and monthly
+    /// candlesticks vary in length so the longest possible range is returned.
+    pub fn duration(&self) -> Duration {
+        match *self {
+            CandlestickGranularity::S5 => Duration::seconds(5),
+            CandlestickGranularity::S10 => Duration::seconds(10),
+            CandlestickGranularity::S15 => Duration::seconds(15),
+            CandlestickGranularity::S30 => Duration::seconds(30),
+            CandlestickGranularity::M1 => Duration::minutes(1),
+            CandlestickGranularity::M2 => Duration::minutes(2),
+            CandlestickGranularity::M4 => Duration::minutes(4),
+            CandlestickGranularity::M5 => Duration::minutes(5),
+            CandlestickGranularity::M10 => Duration::minutes(10),
+            CandlestickGranularity::M15 => Duration::minutes(15),
+            CandlestickGranularity::M30 => Duration::minutes(30),
+            CandlestickGranularity::H1 => Duration::hours(1),
+            CandlestickGranularity::H2 => Duration::hours(2),
+            CandlestickGranularity::H3 => Duration::hours(3),
+            CandlestickGranularity::H4 => Duration::hours(4),
+            CandlestickGranularity::H6 => Duration::hours(6),
+            CandlestickGranularity::H8 => Duration::hours(8),
+            CandlestickGranularity::H12 => Duration::hours(12),
+            CandlestickGranularity::D => Duration::days(1),
+            CandlestickGranularity::W => Duration::weeks(1),
+            CandlestickGranularity::M => Duration::days(31),
+        }
+    }
+}
+
+impl fmt::Display for CandlestickGranularity {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        // the variant names match the values Oanda expects
+        fmt::Debug::fmt(self, f)
+    }
+}
+
+impl FromStr for CandlestickGranularity {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<CandlestickGranularity, ParseError> {
+        CandlestickGranularity::ALL.iter()
+            .cloned()
+            .find(|g| g.to_string() == s)
+            .ok_or_else(|| ParseError { kind: "candlestick granularity", value: s.to_string() })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn it_parses_the_values_oanda_uses() {
+        for granularity in CandlestickGranularity::ALL.iter() {
+            assert_eq!(granularity.to_string().parse(), Ok(*granularity));
+        }
+        assert!("m1".parse::<CandlestickGranularity>().is_err());
+    }
+}
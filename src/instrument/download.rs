@@ -0,0 +1,202 @@
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Mutex;
+use std::thread;
+
+use chrono::datetime::DateTime;
+use chrono::UTC;
+
+use client::Client;
+use error::Result;
+use primitives::InstrumentName;
+
+use super::candles::MAX_CANDLES_PER_REQUEST;
+use super::candlestick::Candlestick;
+use super::candlestick_granularity::CandlestickGranularity;
+use super::price_components::PriceComponents;
+
+/// The progress of a Download, reported as candlesticks are loaded
+pub struct Progress<'p> {
+    /// The instrument the update is for
+    pub instrument: &'p InstrumentName,
+    /// The number of candlesticks loaded so far for the instrument
+    pub candles: usize,
+    /// A flag indicating if every candlestick for the instrument was loaded,
+    /// or loading failed
+    pub finished: bool,
+    /// The number of instruments finished so far
+    pub completed: usize,
+    /// The number of instruments being downloaded
+    pub total: usize
+}
+
+/// The outcome of downloading a single instrument
+pub struct InstrumentCandles {
+    /// The instrument the candlesticks are for
+    pub instrument: InstrumentName,
+    /// Every candlestick in the requested range, or the error that stopped
+    /// the download
+    pub candles: Result<Vec<Candlestick>>
+}
+
+/// Downloads the candlesticks for many instruments over the same range using
+/// a pool of worker threads. Every worker shares the Client and therefore its
+/// rate limit.
+pub struct Download<'a> {
+    /// Names of the Instruments [required]
+    instruments: Vec<InstrumentName>,
+    /// The start of the time range to fetch candlesticks for.
+    from: DateTime<UTC>,
+    /// The end of the time range to fetch candlesticks for.
+    to: DateTime<UTC>,
+    /// The Price component(s) to get candlestick data for. [default=M]
+    price: Option<PriceComponents>,
+    /// The granularity of the candlesticks to fetch [default=S5]
+    granularity: Option<CandlestickGranularity>,
+    /// The number of instruments downloaded at the same time [default=4]
+    workers: usize,
+    /// Called each time a batch of candlesticks is loaded and each time an
+    /// instrument is finished
+    progress: Option<Box<dyn Fn(&Progress) + Sync + 'a>>,
+    /// the client
+    client: &'a Client<'a>
+}
+
+impl <'a>Download<'a> {
+    pub fn new(client: &'a Client, instruments: Vec<InstrumentName>, from: DateTime<UTC>, to: DateTime<UTC>) -> Download<'a> {
+        Download {
+            instruments: instruments,
+            from: from,
+            to: to,
+            price: None,
+            granularity: None,
+            workers: 4,
+            progress: None,
+            client: client
+        }
+    }
+
+    pub fn with_price(&mut self, price: PriceComponents) -> &mut Download<'a> {
+        self.price = Some(price);
+        self
+    }
+
+    pub fn with_granularity(&mut self, granularity: CandlestickGranularity) -> &mut Download<'a> {
+        self.granularity = Some(granularity);
+        self
+    }
+
+    pub fn with_workers(&mut self, workers: usize) -> &mut Download<'a> {
+        self.workers = workers.max(1);
+        self
+    }
+
+    pub fn with_progress<F>(&mut self, progress: F) -> &mut Download<'a>
+        where F: Fn(&Progress) + Sync + 'a
+    {
+        self.progress = Some(Box::new(progress));
+        self
+    }
+
+    /// Download every instrument, returning the results in the same order as
+    /// the instruments were given
+    pub fn execute(&self) -> Vec<InstrumentCandles> {
+        let total = self.instruments.len();
+        let next = AtomicUsize::new(0);
+        let completed = AtomicUsize::new(0);
+        let results = Mutex::new((0..total).map(|_| None).collect::<Vec<_>>());
+
+        thread::scope(|scope| {
+            for _ in 0..self.workers.min(total) {
+                scope.spawn(|| {
+                    loop {
+                        let index = next.fetch_add(1, Ordering::SeqCst);
+                        if index >= total { break }
+
+                        let instrument = &self.instruments[index];
+                        let candles = self.download(instrument, &completed);
+
+                        results.lock().unwrap()[index] = Some(InstrumentCandles {
+                            instrument: instrument.clone(),
+                            candles: candles
+                        });
+                    }
+                });
+            }
+        });
+
+        results.into_inner()
+            .unwrap()
+            .into_iter()
+            .map(|result| result.expect("every instrument is downloaded"))
+            .collect()
+    }
+
+    fn download(&self, instrument: &InstrumentName, completed: &AtomicUsize) -> Result<Vec<Candlestick>> {
+        let mut query = self.client.pricing_for(instrument.clone(), self.from);
+        if let Some(price) = self.price { query.with_price(price); }
+        if let Some(granularity) = self.granularity { query.with_granularity(granularity); }
+
+        let mut candles = query.candles_between(self.from, self.to);
+        let mut result = Vec::new();
+
+        let outcome = loop {
+            match candles.next_candle() {
+                Ok(Some(candle)) => {
+                    result.push(candle);
+
+                    if result.len() % MAX_CANDLES_PER_REQUEST as usize == 0 {
+                        self.report(instrument, result.len(), false, completed.load(Ordering::SeqCst));
+                    }
+                },
+                Ok(None) => break Ok(()),
+                Err(err) => break Err(err)
+            }
+        };
+
+        let done = completed.fetch_add(1, Ordering::SeqCst) + 1;
+        self.report(instrument, result.len(), true, done);
+
+        outcome.map(|_| result)
+    }
+
+    fn report(&self, instrument: &InstrumentName, candles: usize, finished: bool, completed: usize) {
+        if let Some(ref progress) = self.progress {
+            progress(&Progress {
+                instrument: instrument,
+                candles: candles,
+                finished: finished,
+                completed: completed,
+                total: self.instruments.len()
+            });
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::prelude::*;
+
+    #[test]
+    fn it_reports_errors_per_instrument() {
+        let from: DateTime<UTC> = UTC.ymd(2017, 6, 21).and_hms(12, 0, 0);
+        let to: DateTime<UTC> = UTC.ymd(2017, 6, 22).and_hms(12, 0, 0);
+        // nothing listens on the discard port so every request fails
+        let client = Client::new("http://127.0.0.1:9", "");
+        let finished = AtomicUsize::new(0);
+        let instruments: Vec<InstrumentName> = vec![
+            "EUR_USD".parse().unwrap(),
+            "USD_JPY".parse().unwrap(),
+            "GBP_USD".parse().unwrap()
+        ];
+
+        let results = client.download(instruments.clone(), from, to)
+            .with_workers(2)
+            .with_progress(|p| if p.finished { finished.fetch_add(1, Ordering::SeqCst); })
+            .execute();
+
+        assert_eq!(finished.load(Ordering::SeqCst), 3);
+        assert_eq!(results.iter().map(|r| r.instrument.clone()).collect::<Vec<_>>(), instruments);
+        assert!(results.iter().all(|r| r.candles.is_err()));
+    }
+}
@@ -0,0 +1,183 @@
+use chrono::datetime::DateTime;
+use chrono::{Duration, UTC};
+
+use calendar::MarketCalendar;
+use super::candlestick::Candlestick;
+use super::candlestick_granularity::CandlestickGranularity;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum GapKind {
+    /// The market was closed for the whole gap, so no candlesticks exist
+    MarketClosed,
+    /// The market was open, so candlesticks are missing from the data or no
+    /// prices were created in a thin market
+    Unexpected
+}
+
+/// A time-range where a series has no candlesticks
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Gap {
+    /// The start time of the first missing candlestick
+    pub from: DateTime<UTC>,
+    /// The start time of the next candlestick after the gap
+    pub to: DateTime<UTC>,
+    pub kind: GapKind
+}
+
+impl Gap {
+    /// The number of candlesticks of `granularity` the gap covers
+    pub fn missing_candles(&self, granularity: CandlestickGranularity) -> i64 {
+        self.to.signed_duration_since(self.from).num_seconds() / granularity.duration().num_seconds()
+    }
+}
+
+/// The gaps between the candlesticks of a series, which must be in order.
+///
+/// A gap is split where the market opens or closes, so each one is entirely
+/// `MarketClosed` or `Unexpected`. A candlestick is only expected to be
+/// missing when its whole time-range is within a weekend or holiday of the
+/// default `MarketCalendar`. Monthly
+/// candlesticks are treated as 31 days long, so missing months are reported
+/// approximately.
+pub fn find_gaps(candles: &[Candlestick], granularity: CandlestickGranularity) -> Vec<Gap> {
+    match (candles.first(), candles.last()) {
+        (Some(first), Some(last)) => {
+            let to = last.time + granularity.duration();
+
+            find_gaps_with(&MarketCalendar::default(), candles, granularity, first.time, to)
+        },
+        _ => Vec::new()
+    }
+}
+
+/// The gaps in a series that should cover `from` until `to`, including any
+/// before the first or after the last candlestick
+pub fn find_gaps_between(
+    candles: &[Candlestick],
+    granularity: CandlestickGranularity,
+    from: DateTime<UTC>,
+    to: DateTime<UTC>
+) -> Vec<Gap> {
+    find_gaps_with(&MarketCalendar::default(), candles, granularity, from, to)
+}
+
+/// The gaps in a series that should cover `from` until `to`, using the
+/// weekends and holidays of `calendar`. Currency holidays are not considered.
+pub fn find_gaps_with(
+    calendar: &MarketCalendar,
+    candles: &[Candlestick],
+    granularity: CandlestickGranularity,
+    from: DateTime<UTC>,
+    to: DateTime<UTC>
+) -> Vec<Gap> {
+    let step = granularity.duration();
+    let mut gaps = Vec::new();
+    let mut expected = from;
+
+    for candle in candles.iter().filter(|c| c.time >= from && c.time < to) {
+        if candle.time > expected {
+            split(calendar, expected, candle.time, step, &mut gaps);
+        }
+        if candle.time + step > expected {
+            expected = candle.time + step;
+        }
+    }
+    if to > expected {
+        split(calendar, expected, to, step, &mut gaps);
+    }
+
+    gaps
+}
+
+/// Add the gap from `from` until `to`, split where the market opens or closes
+fn split(calendar: &MarketCalendar, from: DateTime<UTC>, to: DateTime<UTC>, step: Duration, gaps: &mut Vec<Gap>) {
+    let mut time = from;
+
+    while time < to {
+        let kind = kind_of(calendar, time, step);
+        let mut end = time + step;
+
+        while end < to && kind_of(calendar, end, step) == kind {
+            end = end + step;
+        }
+        if end > to {
+            end = to;
+        }
+
+        gaps.push(Gap { from: time, to: end, kind: kind });
+        time = end;
+    }
+}
+
+/// Whether a candlestick starting at `time` is expected to be missing
+fn kind_of(calendar: &MarketCalendar, time: DateTime<UTC>, step: Duration) -> GapKind {
+    match calendar.closure(None, time) {
+        Some((_, open)) if time + step <= open => GapKind::MarketClosed,
+        _ => GapKind::Unexpected
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::prelude::*;
+    use serde_json;
+
+    fn candle(time: DateTime<UTC>) -> Candlestick {
+        serde_json::from_str(&format!(
+            r#"{{"time":"{}","mid":{{"o":1.1,"h":1.2,"l":1.0,"c":1.15}},"volume":10,"complete":true}}"#,
+            time.to_rfc3339()
+        )).unwrap()
+    }
+
+    #[test]
+    fn it_separates_the_weekend_from_missing_candles() {
+        // 17:00 in New York is 21:00 UTC during daylight saving time
+        let close = UTC.ymd(2017, 6, 23).and_hms(21, 0, 0);
+        let open = UTC.ymd(2017, 6, 25).and_hms(21, 0, 0);
+        let minute = Duration::minutes(1);
+        let candles: Vec<Candlestick> = vec![close - minute * 2, close - minute, open, open + minute, open + minute * 5]
+            .into_iter()
+            .map(candle)
+            .collect();
+        let gaps = find_gaps(&candles, CandlestickGranularity::M1);
+
+        assert_eq!(gaps, vec![
+            Gap { from: close, to: open, kind: GapKind::MarketClosed },
+            Gap { from: open + minute * 2, to: open + minute * 5, kind: GapKind::Unexpected }
+        ]);
+        assert_eq!(gaps[1].missing_candles(CandlestickGranularity::M1), 3);
+    }
+
+    #[test]
+    fn it_splits_gaps_where_the_market_opens_and_closes() {
+        let hour = Duration::hours(1);
+        let close = UTC.ymd(2017, 6, 23).and_hms(21, 0, 0);
+        let open = UTC.ymd(2017, 6, 25).and_hms(21, 0, 0);
+        let candles = vec![candle(close - hour * 2)];
+
+        assert_eq!(find_gaps_between(&candles, CandlestickGranularity::H1, close - hour * 2, open + hour), vec![
+            Gap { from: close - hour, to: close, kind: GapKind::Unexpected },
+            Gap { from: close, to: open, kind: GapKind::MarketClosed },
+            Gap { from: open, to: open + hour, kind: GapKind::Unexpected }
+        ]);
+    }
+
+    #[test]
+    fn it_treats_holidays_as_closed() {
+        let day = Duration::days(1);
+        // 17:00 in New York is 22:00 UTC in December
+        let friday = UTC.ymd(2017, 12, 22).and_hms(22, 0, 0);
+        let candles = vec![candle(friday - day), candle(friday + day * 3)];
+        let mut calendar = MarketCalendar::default();
+        calendar.with_holiday(NaiveDate::from_ymd(2017, 12, 25));
+
+        assert_eq!(find_gaps(&candles, CandlestickGranularity::D), vec![
+            Gap { from: friday, to: friday + day * 2, kind: GapKind::MarketClosed },
+            Gap { from: friday + day * 2, to: friday + day * 3, kind: GapKind::Unexpected }
+        ]);
+        assert_eq!(find_gaps_with(&calendar, &candles, CandlestickGranularity::D, friday - day, friday + day * 4), vec![
+            Gap { from: friday, to: friday + day * 3, kind: GapKind::MarketClosed }
+        ]);
+    }
+}
@@ -1,5 +1,16 @@
 pub mod candlestick_data;
 pub mod candlestick_granularity;
 pub mod candlestick;
+pub mod alignment;
+pub mod book;
+pub mod candle_store;
+pub mod candles;
+pub mod download;
+pub mod gaps;
+pub mod order_book;
+pub mod position_book;
+pub mod price;
+pub mod price_components;
 pub mod pricing;
 pub mod pricing_query;
+pub mod resample;
@@ -0,0 +1,100 @@
+use chrono::datetime::DateTime;
+use chrono::UTC;
+
+use primitives::{Decimal, DecimalNumber, InstrumentName, PriceValue};
+
+use super::book::{self, Bucket};
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct OrderBookBucket {
+    /// The lowest price (inclusive) covered by the bucket. The bucket covers
+    /// the price range from the price to price + the OrderBook’s bucketWidth.
+    pub price: PriceValue,
+    /// The percentage of the total number of orders represented by the long
+    /// orders found in this bucket.
+    pub long_count_percent: DecimalNumber,
+    /// The percentage of the total number of orders represented by the short
+    /// orders found in this bucket.
+    pub short_count_percent: DecimalNumber
+}
+
+impl Bucket for OrderBookBucket {
+    fn price(&self) -> PriceValue { self.price }
+    fn long_count_percent(&self) -> DecimalNumber { self.long_count_percent }
+    fn short_count_percent(&self) -> DecimalNumber { self.short_count_percent }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct OrderBook {
+    /// The order book’s instrument
+    pub instrument: InstrumentName,
+    /// The time when the order book snapshot was created.
+    pub time: DateTime<UTC>,
+    /// The price (midpoint) for the order book’s instrument at the time of
+    /// the order book snapshot
+    pub price: PriceValue,
+    /// The price width for each bucket. Each bucket covers the price range from
+    /// the bucket’s price to the bucket’s price + bucketWidth.
+    pub bucket_width: PriceValue,
+    /// The partitioned order book, divided into buckets using a default
+    /// bucket width. These buckets are only provided for price ranges which
+    /// actually contain orders.
+    pub buckets: Vec<OrderBookBucket>
+}
+
+impl OrderBook {
+    /// The bucket covering `price`, or the bucket nearest to it if no bucket
+    /// does
+    pub fn nearest_bucket(&self, price: PriceValue) -> Option<&OrderBookBucket> {
+        book::nearest_bucket(&self.buckets, self.bucket_width, price)
+    }
+
+    /// The long percentage minus the short percentage over every bucket
+    pub fn net_sentiment(&self) -> Decimal {
+        book::net_sentiment(&self.buckets)
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct InstrumentOrderBook {
+    /// The instrument’s order book
+    pub order_book: OrderBook
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::str::FromStr;
+    use serde_json;
+
+    #[test]
+    fn it_finds_buckets_and_sentiment() {
+        let result: InstrumentOrderBook = serde_json::from_str(r#"{
+            "orderBook": {
+                "instrument": "EUR_USD",
+                "time": "2017-06-21T12:00:00Z",
+                "price": "1.11490",
+                "bucketWidth": "0.00050",
+                "buckets": [
+                    {"price": "1.11400", "longCountPercent": "0.2000", "shortCountPercent": "0.1000"},
+                    {"price": "1.11450", "longCountPercent": "0.3000", "shortCountPercent": "0.0500"},
+                    {"price": "1.11500", "longCountPercent": "0.1000", "shortCountPercent": "0.4000"}
+                ]
+            }
+        }"#).unwrap();
+        let book = result.order_book;
+
+        assert_eq!(
+            book.nearest_bucket(book.price).unwrap().price,
+            PriceValue::from_str("1.11450").unwrap()
+        );
+        assert_eq!(
+            book.nearest_bucket(PriceValue::from_str("1.11500").unwrap()).unwrap().price,
+            PriceValue::from_str("1.11500").unwrap()
+        );
+        assert_eq!(book.net_sentiment(), Decimal::from_str("0.05").unwrap());
+    }
+}
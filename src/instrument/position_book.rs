@@ -0,0 +1,101 @@
+use chrono::datetime::DateTime;
+use chrono::UTC;
+
+use primitives::{Decimal, DecimalNumber, InstrumentName, PriceValue};
+
+use super::book::{self, Bucket};
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct PositionBookBucket {
+    /// The lowest price (inclusive) covered by the bucket. The bucket covers
+    /// the price range from the price to price + the PositionBook’s bucketWidth.
+    pub price: PriceValue,
+    /// The percentage of the total number of positions represented by the long
+    /// positions found in this bucket.
+    pub long_count_percent: DecimalNumber,
+    /// The percentage of the total number of positions represented by the short
+    /// positions found in this bucket.
+    pub short_count_percent: DecimalNumber
+}
+
+impl Bucket for PositionBookBucket {
+    fn price(&self) -> PriceValue { self.price }
+    fn long_count_percent(&self) -> DecimalNumber { self.long_count_percent }
+    fn short_count_percent(&self) -> DecimalNumber { self.short_count_percent }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct PositionBook {
+    /// The position book’s instrument
+    pub instrument: InstrumentName,
+    /// The time when the position book snapshot was created.
+    pub time: DateTime<UTC>,
+    /// The price (midpoint) for the position book’s instrument at the time of
+    /// the position book snapshot
+    pub price: PriceValue,
+    /// The price width for each bucket. Each bucket covers the price range from
+    /// the bucket’s price to the bucket’s price + bucketWidth.
+    pub bucket_width: PriceValue,
+    /// The partitioned position book, divided into buckets using a default
+    /// bucket width. These buckets are only provided for price ranges which
+    /// actually contain positions.
+    pub buckets: Vec<PositionBookBucket>
+}
+
+impl PositionBook {
+    /// The bucket covering `price`, or the bucket nearest to it if no bucket
+    /// does
+    pub fn nearest_bucket(&self, price: PriceValue) -> Option<&PositionBookBucket> {
+        book::nearest_bucket(&self.buckets, self.bucket_width, price)
+    }
+
+    /// The long percentage minus the short percentage over every bucket
+    pub fn net_sentiment(&self) -> Decimal {
+        book::net_sentiment(&self.buckets)
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct InstrumentPositionBook {
+    /// The instrument’s position book
+    pub position_book: PositionBook
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::str::FromStr;
+    use serde_json;
+
+    #[test]
+    fn it_finds_buckets_and_sentiment() {
+        let result: InstrumentPositionBook = serde_json::from_str(r#"{
+            "positionBook": {
+                "instrument": "EUR_USD",
+                "time": "2017-06-21T12:00:00Z",
+                "price": "1.11490",
+                "bucketWidth": "0.00050",
+                "buckets": [
+                    {"price": "1.11400", "longCountPercent": "0.2000", "shortCountPercent": "0.3000"},
+                    {"price": "1.11450", "longCountPercent": "0.1000", "shortCountPercent": "0.2500"},
+                    {"price": "1.11600", "longCountPercent": "0.1000", "shortCountPercent": "0.0500"}
+                ]
+            }
+        }"#).unwrap();
+        let book = result.position_book;
+
+        assert_eq!(
+            book.nearest_bucket(book.price).unwrap().price,
+            PriceValue::from_str("1.11450").unwrap()
+        );
+        // No bucket covers 1.11560, and 1.11625 is the closest midpoint
+        assert_eq!(
+            book.nearest_bucket(PriceValue::from_str("1.11560").unwrap()).unwrap().price,
+            PriceValue::from_str("1.11600").unwrap()
+        );
+        assert_eq!(book.net_sentiment(), Decimal::from_str("-0.2").unwrap());
+    }
+}
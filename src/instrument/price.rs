@@ -0,0 +1,113 @@
+use chrono::datetime::DateTime;
+use chrono::UTC;
+
+use primitives::{DecimalNumber, InstrumentName, PriceValue};
+
+/// A Price Bucket represents a price available for an amount of liquidity
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct PriceBucket {
+    /// The Price offered by the PriceBucket
+    pub price: PriceValue,
+    /// The amount of liquidity offered by the PriceBucket
+    pub liquidity: DecimalNumber
+}
+
+/// QuoteHomeConversionFactors represents the factors that can be used used to
+/// convert quantities of a Price’s Instrument’s quote currency into the
+/// Account’s home currency.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct QuoteHomeConversionFactors {
+    /// The factor used to convert a positive amount of the Price’s
+    /// Instrument’s quote currency into a positive amount of the Account’s
+    /// home currency. Conversion is performed by multiplying the quote units
+    /// by the conversion factor.
+    pub positive_units: DecimalNumber,
+    /// The factor used to convert a negative amount of the Price’s
+    /// Instrument’s quote currency into a negative amount of the Account’s
+    /// home currency. Conversion is performed by multiplying the quote units
+    /// by the conversion factor.
+    pub negative_units: DecimalNumber
+}
+
+impl QuoteHomeConversionFactors {
+    /// Convert an amount of the quote currency into the home currency
+    pub fn to_home(&self, amount: DecimalNumber) -> DecimalNumber {
+        let factor = if amount.value().is_sign_negative() { self.negative_units } else { self.positive_units };
+
+        DecimalNumber(amount.value() * factor.value())
+    }
+}
+
+/// The specification of an Account-specific Price.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct ClientPrice {
+    /// The Price’s Instrument.
+    pub instrument: InstrumentName,
+    /// The date/time when the Price was created
+    pub time: DateTime<UTC>,
+    /// Flag indicating if the Price is tradeable or not
+    pub tradeable: bool,
+    /// The list of prices and liquidity available on the Instrument’s bid
+    /// side. It is possible for this list to be empty if there is no bid
+    /// liquidity currently available for the Instrument in the Account.
+    pub bids: Vec<PriceBucket>,
+    /// The list of prices and liquidity available on the Instrument’s ask
+    /// side. It is possible for this list to be empty if there is no ask
+    /// liquidity currently available for the Instrument in the Account.
+    pub asks: Vec<PriceBucket>,
+    /// The closeout bid Price. This Price is used when a bid is required to
+    /// closeout a Position (margin closeout or manual) yet there is no bid
+    /// liquidity. The closeout bid is never used to open a new position.
+    pub closeout_bid: PriceValue,
+    /// The closeout ask Price. This Price is used when a ask is required to
+    /// closeout a Position (margin closeout or manual) yet there is no ask
+    /// liquidity. The closeout ask is never used to open a new position.
+    pub closeout_ask: PriceValue,
+    /// The factors used to convert quantities of this price’s Instrument’s
+    /// quote currency into a quantity of the Account’s home currency.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub quote_home_conversion_factors: Option<QuoteHomeConversionFactors>
+}
+
+impl ClientPrice {
+    /// The price that `units` would be closed at, the closeout bid for a long
+    /// and the closeout ask for a short
+    pub fn closeout_price(&self, units: DecimalNumber) -> PriceValue {
+        if units.value().is_sign_negative() { self.closeout_ask } else { self.closeout_bid }
+    }
+
+    /// The profit or loss in the quote currency of closing `units` opened at
+    /// `price` at this Price
+    pub fn unrealized_pl(&self, units: DecimalNumber, price: PriceValue) -> DecimalNumber {
+        DecimalNumber((self.closeout_price(units).value() - price.value()) * units.value())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json;
+
+    #[test]
+    fn it_values_open_units_at_the_closeout_price() {
+        let price: ClientPrice = serde_json::from_str(r#"{
+            "type": "PRICE", "instrument": "EUR_USD", "time": "2017-06-21T12:00:00.000000000Z",
+            "tradeable": true, "status": "tradeable",
+            "bids": [{"price": "1.11430", "liquidity": 10000000}],
+            "asks": [{"price": "1.11450", "liquidity": 10000000}],
+            "closeoutBid": "1.11430", "closeoutAsk": "1.11450",
+            "quoteHomeConversionFactors": {"positiveUnits": "0.89730", "negativeUnits": "0.89750"}
+        }"#).unwrap();
+        let factors = price.quote_home_conversion_factors.clone().unwrap();
+
+        let long = price.unrealized_pl("10000".parse().unwrap(), "1.11330".parse().unwrap());
+        let short = price.unrealized_pl("-10000".parse().unwrap(), "1.11330".parse().unwrap());
+
+        assert_eq!(long, "10.00000".parse().unwrap());
+        assert_eq!(short, "-12.00000".parse().unwrap());
+        assert_eq!(factors.to_home(long), "8.9730000000".parse().unwrap());
+        assert_eq!(factors.to_home(short), "-10.7700000000".parse().unwrap());
+    }
+}
@@ -0,0 +1,87 @@
+use std::fmt;
+use std::ops::BitOr;
+use std::str::FromStr;
+
+use primitives::ParseError;
+
+/// The Price component(s) to get candlestick data for. Any combination of
+/// midpoint, bid and ask candles can be requested at once, e.g.
+/// `PriceComponents::BID | PriceComponents::ASK`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct PriceComponents {
+    bits: u8
+}
+
+impl PriceComponents {
+    /// Midpoint candles, “M”
+    pub const MID: PriceComponents = PriceComponents { bits: 0b001 };
+    /// Bid candles, “B”
+    pub const BID: PriceComponents = PriceComponents { bits: 0b010 };
+    /// Ask candles, “A”
+    pub const ASK: PriceComponents = PriceComponents { bits: 0b100 };
+
+    /// Returns true if every component in `other` is also in `self`
+    pub fn contains(&self, other: PriceComponents) -> bool {
+        self.bits & other.bits == other.bits
+    }
+}
+
+impl BitOr for PriceComponents {
+    type Output = PriceComponents;
+
+    fn bitor(self, other: PriceComponents) -> PriceComponents {
+        PriceComponents { bits: self.bits | other.bits }
+    }
+}
+
+impl fmt::Display for PriceComponents {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.contains(PriceComponents::MID) { write!(f, "M")?; }
+        if self.contains(PriceComponents::BID) { write!(f, "B")?; }
+        if self.contains(PriceComponents::ASK) { write!(f, "A")?; }
+
+        Ok(())
+    }
+}
+
+impl FromStr for PriceComponents {
+    type Err = ParseError;
+
+    /// Parse the value Oanda uses, e.g. “BA” for bid and ask candles
+    fn from_str(s: &str) -> Result<PriceComponents, ParseError> {
+        let error = || ParseError { kind: "price components", value: s.to_string() };
+        let mut bits = 0;
+
+        for c in s.chars() {
+            bits |= match c {
+                'M' => PriceComponents::MID.bits,
+                'B' => PriceComponents::BID.bits,
+                'A' => PriceComponents::ASK.bits,
+                _ => return Err(error())
+            };
+        }
+
+        if bits == 0 { Err(error()) } else { Ok(PriceComponents { bits: bits }) }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn it_displays_combined_components() {
+        let components = PriceComponents::ASK | PriceComponents::MID;
+
+        assert_eq!(components.to_string(), "MA");
+        assert!(components.contains(PriceComponents::ASK));
+        assert!(!components.contains(PriceComponents::BID));
+    }
+
+    #[test]
+    fn it_parses_combined_components() {
+        assert_eq!("AM".parse(), Ok(PriceComponents::ASK | PriceComponents::MID));
+        assert!("".parse::<PriceComponents>().is_err());
+        assert!("MX".parse::<PriceComponents>().is_err());
+    }
+}
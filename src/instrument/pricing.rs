@@ -1,12 +1,21 @@
+use primitives::InstrumentName;
+
 use super::candlestick_granularity::CandlestickGranularity;
 use super::candlestick::Candlestick;
 
-#[derive(Deserialize)]
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
 pub struct Pricing {
     /// The instrument whose Prices are represented by the candlesticks.
-    pub instrument: String,
+    pub instrument: InstrumentName,
     /// The granularity of the candlesticks provided.
     pub granularity: CandlestickGranularity,
     /// The list of candlesticks that satisfy the request.
     pub candles: Vec<Candlestick>
 }
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct LatestCandles {
+    /// The latest candle sticks for each requested specification.
+    pub latest_candles: Vec<Pricing>
+}
@@ -4,26 +4,37 @@ use serde_json;
 
 use chrono::datetime::DateTime;
 use chrono::UTC;
+use chrono::Weekday;
+use chrono_tz::Tz;
+use url::form_urlencoded::Serializer;
 
 use client::Client;
+use primitives::InstrumentName;
+use error::{Error, Result};
+use super::alignment::Alignment;
+use super::candles::Candles;
+use super::candlestick_granularity::CandlestickGranularity;
+use super::price_components::PriceComponents;
 use super::pricing::Pricing;
 
+#[derive(Clone)]
 pub struct PricingQuery<'a> {
     /// Name of the Instrument [required]
-    instrument: String,
+    instrument: InstrumentName,
     /// The Price component(s) to get candlestick data for. Can contain any
     /// combination of the characters “M” (midpoint candles) “B” (bid candles)
     /// and “A” (ask candles). [default=M]
-    price: Option<String>,
+    price: Option<PriceComponents>,
     /// The granularity of the candlesticks to fetch [default=S5]
-    granularity: Option<String>,
+    granularity: Option<CandlestickGranularity>,
     /// The number of candlesticks to return in the reponse. Count should not
     /// be specified if both the start and end parameters are provided, as the
     /// time range combined with the graularity will determine the number of
     /// candlesticks to return. [default=500, maximum=5000]
     count: Option<i32>,
-    /// The start of the time range to fetch candlesticks for.
-    from: DateTime<UTC>,
+    /// The start of the time range to fetch candlesticks for. Only the most
+    /// recent candlesticks are returned without it.
+    from: Option<DateTime<UTC>>,
     /// The end of the time range to fetch candlesticks for.
     to: Option<DateTime<UTC>>,
     /// A flag that controls whether the candlestick is “smoothed” or not.
@@ -43,46 +54,62 @@ pub struct PricingQuery<'a> {
     /// The timezone to use for the dailyAlignment parameter. Candlesticks with
     /// daily alignment will be aligned to the dailyAlignment hour within the
     /// alignmentTimezone. [default=America/New_York]
-    alignment_timezone: Option<String>,
+    alignment_timezone: Option<Tz>,
     /// The day of the week used for granularities that have weekly alignment.
     /// [default=Friday]
-    weekly_alignment: Option<String>,
+    weekly_alignment: Option<Weekday>,
     /// the client
     client: &'a Client<'a>
 }
 
 impl <'a>fmt::Display for PricingQuery<'a> {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        let mut result = format!("{}/candles", self.instrument);
-        let add_result = |s: &str, display: &str, mem: &mut String| {
-            mem.push_str(&format!("&{}={}", display, s))
-        };
-
-        // we should always have from
-        result.push_str(&format!("?from={}", self.from.to_rfc3339()));
-        // we may or may not have these 'optional' attributes
-        if let Some(ref price) = self.price { add_result(price, "price", &mut result) }
-        if let Some(ref granularity) = self.granularity { add_result(granularity, "granularity", &mut result) }
-        if let Some(ref count) = self.count { add_result(&count.to_string(), "count", &mut result) }
-        if let Some(ref to) = self.to { add_result(&to.to_string(), "to", &mut result) }
-        if let Some(ref smooth) = self.smooth { add_result(&smooth.to_string(), "smooth", &mut result) }
-        if let Some(ref include_first) = self.include_first { add_result(&include_first.to_string(), "includeFirst", &mut result) }
-        if let Some(ref daily_alignment) = self.daily_alignment { add_result(&daily_alignment.to_string(), "dailyAlignment", &mut result) }
-        if let Some(ref alignment_timezone) = self.alignment_timezone { add_result(&alignment_timezone.to_string(), "alignmentTimezone", &mut result) }
-        if let Some(ref weekly_alignment) = self.weekly_alignment { add_result(&weekly_alignment.to_string(), "weeklyAlignment", &mut result) }
-
-        write!(f, "{}", result)
+        let mut query = Serializer::new(String::new());
+
+        // every parameter is optional, Oanda returns the most recent
+        // candlesticks when there is no start of the time range
+        if let Some(ref from) = self.from { query.append_pair("from", &from.to_rfc3339()); }
+        if let Some(ref price) = self.price { query.append_pair("price", &price.to_string()); }
+        if let Some(ref granularity) = self.granularity { query.append_pair("granularity", &granularity.to_string()); }
+        if let Some(ref count) = self.count { query.append_pair("count", &count.to_string()); }
+        if let Some(ref to) = self.to { query.append_pair("to", &to.to_rfc3339()); }
+        if let Some(ref smooth) = self.smooth { query.append_pair("smooth", &smooth.to_string()); }
+        if let Some(ref include_first) = self.include_first { query.append_pair("includeFirst", &include_first.to_string()); }
+        if let Some(ref daily_alignment) = self.daily_alignment { query.append_pair("dailyAlignment", &daily_alignment.to_string()); }
+        if let Some(ref alignment_timezone) = self.alignment_timezone { query.append_pair("alignmentTimezone", alignment_timezone.name()); }
+        if let Some(ref weekly_alignment) = self.weekly_alignment { query.append_pair("weeklyAlignment", weekday_name(weekly_alignment)); }
+
+        let query = query.finish();
+
+        if query.is_empty() {
+            write!(f, "{}/candles", self.instrument)
+        } else {
+            write!(f, "{}/candles?{}", self.instrument, query)
+        }
+    }
+}
+
+/// The name Oanda expects for the weeklyAlignment parameter
+fn weekday_name(weekday: &Weekday) -> &'static str {
+    match *weekday {
+        Weekday::Mon => "Monday",
+        Weekday::Tue => "Tuesday",
+        Weekday::Wed => "Wednesday",
+        Weekday::Thu => "Thursday",
+        Weekday::Fri => "Friday",
+        Weekday::Sat => "Saturday",
+        Weekday::Sun => "Sunday"
     }
 }
 
 impl <'a>PricingQuery<'a> {
-    pub fn new(client: &'a Client, instrument: String, from: DateTime<UTC>) -> PricingQuery<'a> {
+    pub fn new(client: &'a Client, instrument: InstrumentName, from: DateTime<UTC>) -> PricingQuery<'a> {
         PricingQuery {
             instrument: instrument,
             price: None,
             granularity: None,
             count: None,
-            from: from,
+            from: Some(from),
             to: None,
             smooth: None,
             include_first: None,
@@ -92,12 +119,26 @@ impl <'a>PricingQuery<'a> {
             client: client
         }
     }
-    pub fn with_price(&mut self, price: String) -> &mut PricingQuery<'a> {
+    /// A query for the most recent `count` candlesticks of an instrument
+    pub fn recent(client: &'a Client, instrument: InstrumentName, count: i32) -> PricingQuery<'a> {
+        PricingQuery {
+            from: None,
+            count: Some(count),
+            ..PricingQuery::new(client, instrument, UTC::now())
+        }
+    }
+
+    pub fn with_from(&mut self, from: DateTime<UTC>) -> &mut PricingQuery<'a> {
+        self.from = Some(from);
+        self
+    }
+
+    pub fn with_price(&mut self, price: PriceComponents) -> &mut PricingQuery<'a> {
         self.price = Some(price);
         self
     }
 
-    pub fn with_granularity(&mut self, granularity: String) -> &mut PricingQuery<'a> {
+    pub fn with_granularity(&mut self, granularity: CandlestickGranularity) -> &mut PricingQuery<'a> {
         self.granularity = Some(granularity);
         self
     }
@@ -127,21 +168,60 @@ impl <'a>PricingQuery<'a> {
         self
     }
 
-    pub fn with_alignment_timezone(&mut self, alignment_timezone: String) -> &mut PricingQuery<'a> {
+    pub fn with_alignment_timezone(&mut self, alignment_timezone: Tz) -> &mut PricingQuery<'a> {
         self.alignment_timezone = Some(alignment_timezone);
         self
     }
 
-    pub fn with_weekly_alignment(&mut self, weekly_alignment: String) -> &mut PricingQuery<'a> {
+    pub fn with_weekly_alignment(&mut self, weekly_alignment: Weekday) -> &mut PricingQuery<'a> {
         self.weekly_alignment = Some(weekly_alignment);
         self
     }
 
+    /// Set the daily alignment, alignment timezone and weekly alignment at once
+    pub fn with_alignment(&mut self, alignment: &Alignment) -> &mut PricingQuery<'a> {
+        self.with_daily_alignment(alignment.daily_alignment as i32)
+            .with_alignment_timezone(alignment.alignment_timezone)
+            .with_weekly_alignment(alignment.weekly_alignment)
+    }
+
     pub fn execute(&self) -> Pricing {
-        let input = self.client.get(&format!("instruments/{}", self.to_string()));
-        let result: Pricing = serde_json::from_str(&input).unwrap();
+        self.try_execute().unwrap()
+    }
+
+    /// Perform the query, failing if the request was rejected or the response
+    /// could not be parsed. A count can not be combined with both a start and
+    /// an end.
+    pub fn try_execute(&self) -> Result<Pricing> {
+        if self.from.is_some() && self.to.is_some() && self.count.is_some() {
+            return Err(Error::Format("count can not be combined with both from and to".to_string()))
+        }
+
+        let input = self.client.try_get(&format!("instruments/{}", self.to_string()))?;
+        let result: Pricing = serde_json::from_str(&input)?;
 
-        result
+        Ok(result)
+    }
+
+    /// Iterate over every candlestick from the start of the query up to its
+    /// end, or up to now if the query has no end. A query with a count, or
+    /// without a start, is sent as a single request for `count` candlesticks
+    /// [default=500] from its start or up to its end, so market closures are
+    /// left to Oanda.
+    pub fn iter(&self) -> Candles<'a> {
+        match (self.from, self.count) {
+            (Some(from), None) => self.candles_between(from, self.to.unwrap_or_else(UTC::now)),
+            _ => Candles::single(self.clone())
+        }
+    }
+
+    /// Iterate over every candlestick from `from` up to `to`, issuing as many
+    /// requests as needed
+    pub fn candles_between(&self, from: DateTime<UTC>, to: DateTime<UTC>) -> Candles<'a> {
+        let step = self.granularity.unwrap_or(CandlestickGranularity::S5).duration();
+        let query = PricingQuery { to: None, count: None, ..self.clone() };
+
+        Candles::new(query, from, to, step)
     }
 }
 
@@ -151,6 +231,124 @@ mod tests {
     use std::env;
     use chrono::prelude::*;
     use chrono::offset::LocalResult;
+    use chrono::Duration;
+    use account::instruments::Instrument;
+    use mock::{MockServer, MockState};
+
+    const FROM: &'static str = "2017-06-21T12%3A00%3A00%2B00%3A00";
+    const TO: &'static str = "2017-06-22T12%3A00%3A00%2B00%3A00";
+
+    fn from() -> DateTime<UTC> {
+        UTC.ymd(2017, 6, 21).and_hms(12, 0, 0)
+    }
+
+    fn to() -> DateTime<UTC> {
+        UTC.ymd(2017, 6, 22).and_hms(12, 0, 0)
+    }
+
+    #[test]
+    fn it_formats_each_parameter() {
+        let client = Client::new("", "");
+        let base = PricingQuery::new(&client, "EUR_USD".parse().unwrap(), from());
+        let cases: Vec<(Box<dyn Fn(&mut PricingQuery)>, &str)> = vec![
+            (Box::new(|_| {}), ""),
+            (Box::new(|q| { q.with_price(PriceComponents::MID); }), "&price=M"),
+            (Box::new(|q| { q.with_price(PriceComponents::BID | PriceComponents::ASK); }), "&price=BA"),
+            (Box::new(|q| { q.with_granularity(CandlestickGranularity::H4); }), "&granularity=H4"),
+            (Box::new(|q| { q.with_count(5000); }), "&count=5000"),
+            (Box::new(|q| { q.with_to(to()); }), "&to=2017-06-22T12%3A00%3A00%2B00%3A00"),
+            (Box::new(|q| { q.with_smooth(true); }), "&smooth=true"),
+            (Box::new(|q| { q.with_include_first(false); }), "&includeFirst=false"),
+            (Box::new(|q| { q.with_daily_alignment(0); }), "&dailyAlignment=0"),
+            (Box::new(|q| { q.with_alignment_timezone("America/New_York".parse().unwrap()); }), "&alignmentTimezone=America%2FNew_York"),
+            (Box::new(|q| { q.with_weekly_alignment(Weekday::Sun); }), "&weeklyAlignment=Sunday")
+        ];
+
+        for (configure, expected) in cases {
+            let mut query = base.clone();
+            configure(&mut query);
+
+            assert_eq!(query.to_string(), format!("EUR_USD/candles?from={}{}", FROM, expected));
+        }
+    }
+
+    #[test]
+    fn it_formats_every_parameter_together() {
+        let client = Client::new("", "");
+        let mut iq = PricingQuery::new(&client, "EUR_USD".parse().unwrap(), from());
+        let query = iq.with_price(PriceComponents::MID | PriceComponents::BID)
+            .with_granularity(CandlestickGranularity::M1)
+            .with_to(to())
+            .with_smooth(false)
+            .with_include_first(true)
+            .with_daily_alignment(17)
+            .with_alignment_timezone("Europe/London".parse().unwrap())
+            .with_weekly_alignment(Weekday::Fri);
+
+        assert_eq!(
+            query.to_string(),
+            format!(
+                "EUR_USD/candles?from={}&price=MB&granularity=M1&to={}&smooth=false\
+                 &includeFirst=true&dailyAlignment=17&alignmentTimezone=Europe%2FLondon\
+                 &weeklyAlignment=Friday",
+                FROM,
+                TO
+            )
+        );
+    }
+
+    #[test]
+    fn it_formats_a_count_without_from() {
+        let client = Client::new("", "");
+        let mut iq = PricingQuery::recent(&client, "EUR_USD".parse().unwrap(), 10);
+
+        assert_eq!(iq.to_string(), "EUR_USD/candles?count=10");
+        assert_eq!(
+            iq.with_granularity(CandlestickGranularity::D).to_string(),
+            "EUR_USD/candles?granularity=D&count=10"
+        );
+    }
+
+    #[test]
+    fn it_iterates_over_the_most_recent_candles_across_a_weekend() {
+        // Hourly candlesticks from Thursday to Monday, with none over the
+        // weekend
+        let thursday = UTC.ymd(2017, 6, 22).and_hms(12, 0, 0);
+        let candles = (0..96).map(|hour| thursday + Duration::hours(hour))
+            .filter(|time| time.weekday() != Weekday::Sat && time.weekday() != Weekday::Sun)
+            .map(|time| serde_json::from_str(&format!(
+                r#"{{"time":"{}","mid":{{"o":1.1,"h":1.2,"l":1.0,"c":1.15}},"volume":10,"complete":true}}"#,
+                time.to_rfc3339()
+            )).unwrap())
+            .collect();
+        let mut state = MockState::new();
+        state
+            .with_instrument(Instrument::currency_pair("EUR_USD".parse().unwrap()))
+            .with_candles("EUR_USD".parse().unwrap(), CandlestickGranularity::H1, candles);
+        let server = MockServer::start(state).unwrap();
+        let client = Client::new(server.url(), "");
+        let monday = UTC.ymd(2017, 6, 26).and_hms(3, 0, 0);
+
+        let mut query = PricingQuery::recent(&client, "EUR_USD".parse().unwrap(), 20);
+        let recent = query.with_granularity(CandlestickGranularity::H1)
+            .with_to(monday)
+            .iter()
+            .collect::<Result<Vec<_>>>()
+            .unwrap();
+
+        assert_eq!(recent.len(), 20);
+        assert_eq!(recent[19].time, monday - Duration::hours(1));
+    }
+
+    #[test]
+    fn it_rejects_a_count_with_both_from_and_to() {
+        let client = Client::new("", "");
+        let mut query = PricingQuery::new(&client, "EUR_USD".parse().unwrap(), from());
+        query.with_to(to()).with_count(10);
+
+        assert!(query.try_execute().is_err());
+        assert!(query.iter().next().unwrap().is_err());
+    }
 
     #[test]
     fn it_can_perform_a_query() {
@@ -159,13 +357,10 @@ mod tests {
         let key = env::var("OANDA_API_KEY").unwrap();
         let account_id = env::var("OANDA_TEST_ACCOUNT_ID").unwrap();
         let client = Client::new(&url, &key);
-        let mut iq = PricingQuery::new(&client, "EUR_USD".to_string(), utc);
-        let query  = iq.with_price("M".to_string());
+        let mut iq = PricingQuery::new(&client, "EUR_USD".parse().unwrap(), utc);
+        let query  = iq.with_price(PriceComponents::MID);
 
-        assert_eq!(
-            query.to_string(),
-            format!("EUR_USD/candles?from={}&price=M", utc.to_rfc3339())
-        );
+        assert_eq!(query.to_string(), format!("EUR_USD/candles?from={}&price=M", FROM));
 
         assert_eq!(query.execute().instrument, "EUR_USD")
     }
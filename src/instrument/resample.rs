@@ -0,0 +1,238 @@
+use std::error;
+use std::fmt;
+
+use chrono::datetime::DateTime;
+use chrono::naive::date::NaiveDate;
+use chrono::{Datelike, Duration, UTC};
+
+use super::alignment::Alignment;
+use super::candlestick::Candlestick;
+use super::candlestick_data::CandlestickData;
+use super::candlestick_granularity::CandlestickGranularity;
+
+/// The reasons candlesticks cannot be resampled to a duration
+#[derive(Debug, PartialEq)]
+pub enum ResampleError {
+    /// The duration is shorter than the candlesticks being aggregated
+    ShorterThanSource,
+    /// The duration is not a whole number of the candlesticks being
+    /// aggregated, so some would span two aggregated candlesticks
+    NotMultipleOfSource,
+    /// The duration is a day or longer but not a whole number of days
+    NotWholeDays
+}
+
+impl fmt::Display for ResampleError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            ResampleError::ShorterThanSource => write!(f, "cannot resample to a shorter duration"),
+            ResampleError::NotMultipleOfSource => write!(f, "cannot resample to a duration that is not a whole number of the source candlesticks"),
+            ResampleError::NotWholeDays => write!(f, "cannot resample to a duration of a day or more that is not a whole number of days")
+        }
+    }
+}
+
+impl error::Error for ResampleError {}
+
+/// Aggregates candlesticks into candlesticks of any duration, e.g. 3 or 90
+/// minutes.
+///
+/// Durations shorter than a day are counted from the start of each day, so a
+/// day that is not a whole number of them ends with a shorter candlestick.
+/// Durations of whole days are counted in days, starting at the weekly
+/// alignment when the duration is a whole number of weeks.
+pub struct Resampler {
+    /// The granularity of the candlesticks being aggregated
+    source: CandlestickGranularity,
+    /// The time-range covered by each aggregated candlestick
+    duration: Duration,
+    /// How days and weeks are aligned [default=17:00 America/New_York, Friday]
+    alignment: Alignment
+}
+
+impl Resampler {
+    /// Fails if `duration` is shorter than the `source` granularity or not a
+    /// whole number of it, or is a day or longer but not a whole number of
+    /// days
+    pub fn new(source: CandlestickGranularity, duration: Duration) -> Result<Resampler, ResampleError> {
+        if duration < source.duration() {
+            return Err(ResampleError::ShorterThanSource)
+        }
+        if duration.num_seconds() % source.duration().num_seconds() != 0 {
+            return Err(ResampleError::NotMultipleOfSource)
+        }
+        if duration >= Duration::days(1) && duration != Duration::days(duration.num_days()) {
+            return Err(ResampleError::NotWholeDays)
+        }
+
+        Ok(Resampler {
+            source: source,
+            duration: duration,
+            alignment: Alignment::default()
+        })
+    }
+
+    pub fn with_alignment(&mut self, alignment: Alignment) -> &mut Resampler {
+        self.alignment = alignment;
+        self
+    }
+
+    /// The start and end of the aggregated candlestick containing `time`
+    pub fn bucket(&self, time: DateTime<UTC>) -> (DateTime<UTC>, DateTime<UTC>) {
+        let day = Duration::days(1);
+
+        if self.duration < day {
+            let start_of_day = self.alignment.start_of_day(time);
+            let next_day = self.alignment.next_day(start_of_day);
+            let step = self.duration.num_seconds();
+            let offset = time.signed_duration_since(start_of_day).num_seconds();
+            let start = start_of_day + Duration::seconds(offset / step * step);
+            let end = start + self.duration;
+
+            (start, if end < next_day { end } else { next_day })
+        } else {
+            let days = self.duration.num_days();
+            let date = self.alignment.trading_date(self.alignment.start_of_day(time));
+            // count days from a fixed date, the first on the weekly alignment
+            // for whole weeks
+            let mut reference = NaiveDate::from_ymd(1970, 1, 1);
+            while days % 7 == 0 && reference.weekday() != self.alignment.weekly_alignment {
+                reference = reference.succ();
+            }
+            let offset = date.signed_duration_since(reference).num_days();
+            let start = reference + Duration::days(offset.div_euclid(days) * days);
+
+            (self.alignment.day_start(start), self.alignment.day_start(start + Duration::days(days)))
+        }
+    }
+
+    /// Aggregate `candles`, which must be in order. Each price component is
+    /// only provided if every candlestick aggregated has it, so bid, ask and
+    /// mid always cover the same candlesticks.
+    pub fn resample(&self, candles: &[Candlestick]) -> Vec<Candlestick> {
+        let mut result = Vec::new();
+        let mut start = 0;
+
+        while start < candles.len() {
+            let (from, to) = self.bucket(candles[start].time);
+            let len = candles[start..].iter().take_while(|c| c.time < to).count();
+
+            result.push(self.aggregate(from, to, &candles[start..start + len]));
+            start += len;
+        }
+
+        result
+    }
+
+    fn aggregate(&self, from: DateTime<UTC>, to: DateTime<UTC>, candles: &[Candlestick]) -> Candlestick {
+        let last = candles.last().expect("a bucket has at least one candlestick");
+
+        Candlestick {
+            time: from,
+            bid: aggregate_data(candles.iter().map(|c| c.bid.as_ref())),
+            ask: aggregate_data(candles.iter().map(|c| c.ask.as_ref())),
+            mid: aggregate_data(candles.iter().map(|c| c.mid.as_ref())),
+            volume: candles.iter().map(|c| c.volume).sum(),
+            // complete once every candlestick is and the range has passed
+            complete: candles.iter().all(|c| c.complete) && last.time + self.source.duration() >= to
+        }
+    }
+}
+
+/// Open of the first, highest high, lowest low and close of the last
+fn aggregate_data<'c, I>(data: I) -> Option<CandlestickData>
+    where I: Iterator<Item = Option<&'c CandlestickData>>
+{
+    let mut result: Option<CandlestickData> = None;
+
+    for d in data {
+        let d = match d { Some(d) => d, None => return None };
+
+        result = Some(match result {
+            None => d.clone(),
+            Some(r) => CandlestickData {
+                o: r.o,
+                h: if d.h > r.h { d.h } else { r.h },
+                l: if d.l < r.l { d.l } else { r.l },
+                c: d.c
+            }
+        });
+    }
+
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::prelude::*;
+    use serde_json;
+
+    fn candle(time: DateTime<UTC>, o: &str, h: &str, l: &str, c: &str, bid: bool) -> Candlestick {
+        let data = format!(r#"{{"o":"{}","h":"{}","l":"{}","c":"{}"}}"#, o, h, l, c);
+        let bid = if bid { format!(r#","bid":{}"#, data) } else { String::new() };
+
+        serde_json::from_str(&format!(
+            r#"{{"time":"{}","mid":{}{},"volume":5,"complete":true}}"#,
+            time.to_rfc3339(), data, bid
+        )).unwrap()
+    }
+
+    fn prices(data: &Option<CandlestickData>) -> String {
+        let d = data.as_ref().unwrap();
+
+        format!("{} {} {} {}", d.o, d.h, d.l, d.c)
+    }
+
+    #[test]
+    fn it_aggregates_minutes_into_three_minute_candles() {
+        let start = UTC.ymd(2017, 6, 21).and_hms(12, 0, 0);
+        let candles: Vec<Candlestick> = vec![
+            candle(start, "1.1", "1.3", "1.0", "1.2", true),
+            candle(start + Duration::minutes(1), "1.2", "1.5", "1.1", "1.4", false),
+            candle(start + Duration::minutes(2), "1.4", "1.4", "0.9", "1.3", true),
+            candle(start + Duration::minutes(3), "1.3", "1.3", "1.2", "1.25", true)
+        ];
+        let result = Resampler::new(CandlestickGranularity::M1, Duration::minutes(3)).unwrap().resample(&candles);
+
+        assert_eq!(result.len(), 2);
+        assert_eq!(result[0].time, start);
+        assert_eq!(prices(&result[0].mid), "1.1 1.5 0.9 1.3");
+        assert_eq!(result[0].volume, 15);
+        assert!(result[0].complete);
+        assert!(result[0].bid.is_none());
+        assert_eq!(prices(&result[1].bid), "1.3 1.3 1.2 1.25");
+        assert!(!result[1].complete);
+    }
+
+    #[test]
+    fn it_aligns_to_the_start_of_the_day() {
+        let resampler = Resampler::new(CandlestickGranularity::M1, Duration::minutes(90)).unwrap();
+        // 17:00 in New York is 21:00 UTC during daylight saving time
+        let time = UTC.ymd(2017, 6, 21).and_hms(22, 45, 0);
+
+        assert_eq!(
+            resampler.bucket(time),
+            (UTC.ymd(2017, 6, 21).and_hms(22, 30, 0), UTC.ymd(2017, 6, 22).and_hms(0, 0, 0))
+        );
+    }
+
+    #[test]
+    fn it_rejects_durations_the_source_does_not_fit() {
+        let new = |source, duration| Resampler::new(source, duration).err();
+
+        assert_eq!(new(CandlestickGranularity::H1, Duration::minutes(30)), Some(ResampleError::ShorterThanSource));
+        assert_eq!(new(CandlestickGranularity::H1, Duration::minutes(90)), Some(ResampleError::NotMultipleOfSource));
+        assert_eq!(new(CandlestickGranularity::H1, Duration::hours(36)), Some(ResampleError::NotWholeDays));
+        assert_eq!(new(CandlestickGranularity::H1, Duration::hours(48)), None);
+    }
+
+    #[test]
+    fn it_aligns_whole_weeks_to_the_weekly_alignment() {
+        let resampler = Resampler::new(CandlestickGranularity::D, Duration::weeks(2)).unwrap();
+        let (from, to) = resampler.bucket(UTC.ymd(2017, 6, 21).and_hms(12, 0, 0));
+
+        assert_eq!(from.with_timezone(&::chrono_tz::America::New_York).weekday(), Weekday::Fri);
+        assert_eq!(to.signed_duration_since(from), Duration::weeks(2));
+    }
+}
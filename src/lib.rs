@@ -1,14 +1,33 @@
 extern crate chrono;
+extern crate chrono_tz;
 
 #[macro_use]
 extern crate hyper;
 extern crate hyper_native_tls;
+#[cfg(feature = "parquet")]
+extern crate parquet;
+extern crate rust_decimal;
 
 extern crate serde;
 extern crate serde_json;
 #[macro_use]
 extern crate serde_derive;
+extern crate url;
+
+#[macro_use]
+mod macros;
 
 pub mod account;
+pub mod backtest;
+pub mod calendar;
 pub mod client;
+pub mod error;
+pub mod export;
+pub mod indicators;
 pub mod instrument;
+#[cfg(any(test, feature = "mock"))]
+pub mod mock;
+pub mod paper;
+pub mod primitives;
+pub mod strategy;
+pub mod stream;
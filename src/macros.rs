@@ -0,0 +1,57 @@
+/// Defines an enum of the string values Oanda documents for a field, plus an
+/// `Unknown` variant holding any value added to the API since, so new values
+/// never break deserialization
+macro_rules! open_enum {
+    (
+        $(#[$attr:meta])*
+        pub enum $name:ident {
+            $($(#[$variant_attr:meta])* $variant:ident),* $(,)*
+        }
+    ) => {
+        $(#[$attr])*
+        #[allow(non_camel_case_types)]
+        #[derive(Clone, Debug, PartialEq, Eq, Hash)]
+        pub enum $name {
+            $($(#[$variant_attr])* $variant,)*
+            /// A value not known to this version of the library
+            Unknown(String)
+        }
+
+        impl $name {
+            /// The value Oanda uses for this variant
+            pub fn as_str(&self) -> &str {
+                match *self {
+                    $($name::$variant => stringify!($variant),)*
+                    $name::Unknown(ref value) => value
+                }
+            }
+        }
+
+        impl ::std::fmt::Display for $name {
+            fn fmt(&self, f: &mut ::std::fmt::Formatter) -> ::std::fmt::Result {
+                f.write_str(self.as_str())
+            }
+        }
+
+        impl ::serde::Serialize for $name {
+            fn serialize<S>(&self, serializer: S) -> ::std::result::Result<S::Ok, S::Error>
+                where S: ::serde::Serializer
+            {
+                serializer.serialize_str(self.as_str())
+            }
+        }
+
+        impl ::serde::Deserialize for $name {
+            fn deserialize<D>(deserializer: D) -> ::std::result::Result<$name, D::Error>
+                where D: ::serde::Deserializer
+            {
+                let value = <String as ::serde::Deserialize>::deserialize(deserializer)?;
+
+                Ok(match value.as_str() {
+                    $(stringify!($variant) => $name::$variant,)*
+                    _ => $name::Unknown(value)
+                })
+            }
+        }
+    }
+}
@@ -0,0 +1,350 @@
+//! A local stand-in for the v20 REST API, to run a `Client` against in tests
+//! and demos without an Oanda account.
+//!
+//! The server keeps its accounts in memory and serves:
+//!
+//! * `GET /v3/accounts`
+//! * `GET /v3/accounts/{id}`, `/summary` and `/instruments`
+//! * `GET /v3/accounts/{id}/positions/{instrument}`
+//! * `POST /v3/accounts/{id}/orders` for Market Orders
+//! * `PUT /v3/accounts/{id}/orders/{id}/cancel`
+//! * `PUT /v3/accounts/{id}/trades/{id}/close`
+//! * `PUT /v3/accounts/{id}/positions/{instrument}/close`
+//! * `GET /v3/instruments/{instrument}/candles`
+//!
+//! ```no_run
+//! use oandars::account::instruments::Instrument;
+//! use oandars::client::Client;
+//! use oandars::mock::{MockServer, MockState};
+//!
+//! let mut state = MockState::new();
+//! state
+//!     .with_account("101-001-1-001", "USD".parse().unwrap(), "100000".parse().unwrap())
+//!     .with_instrument(Instrument::currency_pair("EUR_USD".parse().unwrap()))
+//!     .with_price("EUR_USD".parse().unwrap(), "1.10000".parse().unwrap(), "1.10020".parse().unwrap());
+//!
+//! let server = MockServer::start(state).unwrap();
+//! let client = Client::new(server.url(), "any key");
+//! let accounts = client.accounts();
+//! ```
+
+mod state;
+
+pub use self::state::{MockState, Rejection};
+
+use std::collections::HashMap;
+use std::io::Read;
+use std::sync::{Arc, Mutex, MutexGuard};
+
+use hyper::header::ContentType;
+use hyper::method::Method;
+use hyper::server::{Handler, Listening, Request, Response, Server};
+use hyper::status::StatusCode;
+use hyper::uri::RequestUri;
+use serde::Serialize;
+use serde_json;
+use url::Url;
+use url::percent_encoding::percent_decode;
+
+use account::Accounts;
+use account::details::AccountDetails;
+use account::instruments::AccountInstruments;
+use account::orders::{CreateOrderRequest, MarketOrderRequest};
+use account::positions::{AccountPosition, ClosePositionRequest};
+use account::summary::AccountSummary;
+use account::trades::{CloseTradeRequest, CloseUnits};
+use error::Result;
+use instrument::candlestick::Candlestick;
+use instrument::candlestick_granularity::CandlestickGranularity;
+use instrument::price_components::PriceComponents;
+use instrument::pricing::Pricing;
+use primitives::InstrumentName;
+
+/// The number of candlesticks returned when a request does not give a count
+const DEFAULT_COUNT: usize = 500;
+
+/// A mock v20 REST API listening on localhost until it is dropped
+pub struct MockServer {
+    url: String,
+    state: Arc<Mutex<MockState>>,
+    listening: Listening
+}
+
+impl MockServer {
+    /// Serve `state` on a free port of localhost
+    pub fn start(state: MockState) -> Result<MockServer> {
+        MockServer::bind("127.0.0.1:0", state)
+    }
+
+    /// Serve `state` on `address`, e.g. 127.0.0.1:8080
+    pub fn bind(address: &str, state: MockState) -> Result<MockServer> {
+        let state = Arc::new(Mutex::new(state));
+        let listening = Server::http(address)?.handle(Router { state: state.clone() })?;
+
+        Ok(MockServer {
+            url: format!("http://{}/v3", listening.socket),
+            state: state,
+            listening: listening
+        })
+    }
+
+    /// The URL to create a `Client` with
+    pub fn url(&self) -> &str {
+        &self.url
+    }
+
+    /// The state being served, e.g. to change prices between requests
+    pub fn state<'a>(&'a self) -> MutexGuard<'a, MockState> {
+        self.state.lock().unwrap()
+    }
+}
+
+impl Drop for MockServer {
+    fn drop(&mut self) {
+        self.listening.close().ok();
+    }
+}
+
+/// The body Oanda responds with when a request is rejected
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct ErrorResponse<'a> {
+    error_message: &'a str
+}
+
+struct Router {
+    state: Arc<Mutex<MockState>>
+}
+
+impl Handler for Router {
+    fn handle(&self, mut request: Request, mut response: Response) {
+        let path = match request.uri {
+            RequestUri::AbsolutePath(ref path) => path.clone(),
+            ref uri => uri.to_string()
+        };
+        let mut body = String::new();
+
+        let reply = match request.read_to_string(&mut body) {
+            Ok(_) => respond(&mut self.state.lock().unwrap(), &request.method, &path, &body),
+            Err(err) => Err(Rejection::new(400, &err.to_string()))
+        };
+        let (status, body) = match reply {
+            Ok(body) => (200, body),
+            Err(rejection) => (
+                rejection.status,
+                serde_json::to_string(&ErrorResponse { error_message: &rejection.message }).unwrap()
+            )
+        };
+
+        *response.status_mut() = StatusCode::from_u16(status);
+        response.headers_mut().set(ContentType::json());
+        response.send(body.as_bytes()).ok();
+    }
+}
+
+/// The body of the response to a request for `path`, e.g.
+/// /v3/accounts?x=y
+fn respond(state: &mut MockState, method: &Method, path: &str, body: &str) -> ::std::result::Result<String, Rejection> {
+    let url = Url::parse(&format!("http://localhost{}", path)).map_err(|err| Rejection::new(400, &err.to_string()))?;
+    let segments: Vec<String> = url.path_segments()
+        .map(|segments| segments.map(|s| percent_decode(s.as_bytes()).decode_utf8_lossy().into_owned()).collect())
+        .unwrap_or_default();
+    let query: HashMap<String, String> = url.query_pairs().into_owned().collect();
+    let segments: Vec<&str> = segments.iter().map(String::as_str).collect();
+
+    let path = match segments.split_first() {
+        Some((&"v3", path)) => path,
+        _ => return Err(not_found(&url))
+    };
+
+    match (method, path) {
+        (&Method::Get, &["accounts"]) => to_json(&Accounts { accounts: state.accounts() }),
+        (&Method::Get, &["accounts", id]) => to_json(&AccountDetails {
+            account: state.details(id)?,
+            last_transaction_id: state.last_transaction_id()
+        }),
+        (&Method::Get, &["accounts", id, "summary"]) => to_json(&AccountSummary {
+            account: state.summary(id)?,
+            last_transaction_id: state.last_transaction_id()
+        }),
+        (&Method::Get, &["accounts", id, "instruments"]) => {
+            let names = match query.get("instruments") {
+                Some(names) => Some(names.split(',').map(instrument).collect::<::std::result::Result<Vec<_>, _>>()?),
+                None => None
+            };
+
+            to_json(&AccountInstruments {
+                instruments: state.instruments(id, names.as_ref().map(Vec::as_slice))?,
+                last_transaction_id: state.last_transaction_id()
+            })
+        },
+        (&Method::Get, &["accounts", id, "positions", name]) => to_json(&AccountPosition {
+            position: state.position(id, &instrument(name)?)?,
+            last_transaction_id: state.last_transaction_id()
+        }),
+        (&Method::Post, &["accounts", id, "orders"]) => {
+            let request: CreateOrderRequest<MarketOrderRequest> = from_json(body)?;
+
+            to_json(&state.create_order(id, &request.order)?)
+        },
+        (&Method::Put, &["accounts", id, "orders", order_id, "cancel"]) => to_json(&state.cancel_order(id, order_id)?),
+        (&Method::Put, &["accounts", id, "trades", trade_id, "close"]) => {
+            let units = if body.trim().is_empty() { CloseUnits::All } else { from_json::<CloseTradeRequest>(body)?.units };
+
+            to_json(&state.close_trade(id, trade_id, &units)?)
+        },
+        (&Method::Put, &["accounts", id, "positions", name, "close"]) => {
+            let request: ClosePositionRequest = from_json(body)?;
+
+            to_json(&state.close_position(id, &instrument(name)?, &request)?)
+        },
+        (&Method::Get, &["instruments", name, "candles"]) => {
+            let name = instrument(name)?;
+            let granularity = parameter(&query, "granularity")?.unwrap_or(CandlestickGranularity::S5);
+            let price = parameter(&query, "price")?.unwrap_or(PriceComponents::MID);
+            let count = parameter(&query, "count")?.unwrap_or(DEFAULT_COUNT);
+            let include_first = parameter(&query, "includeFirst")?.unwrap_or(true);
+            let candles = state.candles(
+                &name,
+                granularity,
+                parameter(&query, "from")?,
+                parameter(&query, "to")?,
+                count,
+                include_first
+            )?;
+
+            to_json(&Pricing {
+                instrument: name,
+                granularity: granularity,
+                candles: candles.into_iter().map(|c| components(c, price)).collect()
+            })
+        },
+        _ => Err(not_found(&url))
+    }
+}
+
+/// Only the bid, ask and mid prices of `candle` in `price`
+fn components(candle: Candlestick, price: PriceComponents) -> Candlestick {
+    Candlestick {
+        bid: if price.contains(PriceComponents::BID) { candle.bid } else { None },
+        ask: if price.contains(PriceComponents::ASK) { candle.ask } else { None },
+        mid: if price.contains(PriceComponents::MID) { candle.mid } else { None },
+        ..candle
+    }
+}
+
+fn instrument(name: &str) -> ::std::result::Result<InstrumentName, Rejection> {
+    name.parse().map_err(|_| Rejection::new(400, &format!("Invalid value specified for 'instrument': {}", name)))
+}
+
+fn parameter<T: ::std::str::FromStr>(
+    query: &HashMap<String, String>,
+    name: &str
+) -> ::std::result::Result<Option<T>, Rejection> {
+    match query.get(name) {
+        Some(value) => value.parse()
+            .map(Some)
+            .map_err(|_| Rejection::new(400, &format!("Invalid value specified for '{}': {}", name, value))),
+        None => Ok(None)
+    }
+}
+
+fn from_json<T: ::serde::Deserialize>(body: &str) -> ::std::result::Result<T, Rejection> {
+    serde_json::from_str(body).map_err(|err| Rejection::new(400, &format!("Invalid request body: {}", err)))
+}
+
+fn to_json<T: Serialize>(value: &T) -> ::std::result::Result<String, Rejection> {
+    serde_json::to_string(value).map_err(|err| Rejection::new(500, &err.to_string()))
+}
+
+fn not_found(url: &Url) -> Rejection {
+    Rejection::new(404, &format!("No endpoint for {}", url.path()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use account::instruments::Instrument;
+    use account::transactions::TransactionType;
+    use export::test_support::candles;
+
+    fn state() -> MockState {
+        let mut state = MockState::new();
+        state
+            .with_account("101-001-1-001", "USD".parse().unwrap(), "100000".parse().unwrap())
+            .with_instrument(Instrument::currency_pair("EUR_USD".parse().unwrap()))
+            .with_price("EUR_USD".parse().unwrap(), "1.10000".parse().unwrap(), "1.10020".parse().unwrap());
+
+        state
+    }
+
+    fn post(state: &mut MockState, path: &str, body: &str) -> ::std::result::Result<String, Rejection> {
+        respond(state, &Method::Post, path, body)
+    }
+
+    fn put(state: &mut MockState, path: &str, body: &str) -> ::std::result::Result<String, Rejection> {
+        respond(state, &Method::Put, path, body)
+    }
+
+    fn get<T: ::serde::Deserialize>(state: &mut MockState, path: &str) -> T {
+        serde_json::from_str(&respond(state, &Method::Get, path, "").unwrap()).unwrap()
+    }
+
+    #[test]
+    fn it_fills_market_orders_and_closes_trades() {
+        let mut state = state();
+        let order = r#"{"order":{"type":"MARKET","instrument":"EUR_USD","units":"10000","timeInForce":"FOK","positionFill":"DEFAULT","stopLossOnFill":{"price":"1.09000","timeInForce":"GTC"}}}"#;
+
+        post(&mut state, "/v3/accounts/101-001-1-001/orders", order).unwrap();
+        state.with_price("EUR_USD".parse().unwrap(), "1.10120".parse().unwrap(), "1.10140".parse().unwrap());
+
+        let details: AccountDetails = get(&mut state, "/v3/accounts/101-001-1-001");
+        assert_eq!(details.account.trades[0].unrealized_pl, "10.0000".parse().unwrap());
+        assert_eq!(details.account.trades[0].stop_loss_order_id, Some("3".to_string()));
+        assert_eq!(details.account.orders.len(), 1);
+
+        put(&mut state, "/v3/accounts/101-001-1-001/trades/2/close", r#"{"units":"4000"}"#).unwrap();
+        put(&mut state, "/v3/accounts/101-001-1-001/positions/EUR_USD/close", r#"{"longUnits":"ALL"}"#).unwrap();
+
+        let summary: AccountSummary = get(&mut state, "/v3/accounts/101-001-1-001/summary");
+        assert_eq!(summary.account.balance, "100010.0000".parse().unwrap());
+        assert_eq!(summary.account.open_trade_count, 0);
+        assert_eq!(summary.account.pending_order_count, 0);
+        assert_eq!(
+            state.transactions().iter().map(|t| t.transaction_type.clone()).collect::<Vec<_>>(),
+            vec![
+                TransactionType::MARKET_ORDER, TransactionType::ORDER_FILL, TransactionType::STOP_LOSS_ORDER,
+                TransactionType::MARKET_ORDER, TransactionType::ORDER_FILL,
+                TransactionType::MARKET_ORDER, TransactionType::ORDER_FILL, TransactionType::ORDER_CANCEL
+            ]
+        );
+    }
+
+    #[test]
+    fn it_rejects_what_oanda_would() {
+        let mut state = state();
+
+        assert_eq!(put(&mut state, "/v3/accounts/101-001-1-001/orders/9/cancel", "").unwrap_err().status, 404);
+        assert_eq!(put(&mut state, "/v3/accounts/101-001-1-001/trades/9/close", "").unwrap_err().status, 404);
+        assert_eq!(respond(&mut state, &Method::Get, "/v3/accounts/unknown", "").unwrap_err().status, 404);
+        assert_eq!(respond(&mut state, &Method::Get, "/v3/nothing", "").unwrap_err().status, 404);
+        assert_eq!(post(&mut state, "/v3/accounts/101-001-1-001/orders", "{}").unwrap_err().status, 400);
+    }
+
+    #[test]
+    fn it_serves_seeded_candles() {
+        let mut state = state();
+        let seeded = candles();
+        state.with_candles("EUR_USD".parse().unwrap(), CandlestickGranularity::M1, seeded.clone());
+
+        let from = seeded[0].time.to_rfc3339().replace("+", "%2B");
+        let pricing: Pricing = get(
+            &mut state,
+            &format!("/v3/instruments/EUR_USD/candles?granularity=M1&price=BA&from={}&includeFirst=false", from)
+        );
+
+        assert_eq!(pricing.candles.len(), seeded.len() - 1);
+        assert_eq!(pricing.candles[0].time, seeded[1].time);
+        assert!(pricing.candles[0].mid.is_none());
+    }
+}
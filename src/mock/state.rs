@@ -0,0 +1,751 @@
+use std::collections::{BTreeMap, HashMap};
+
+use chrono::datetime::DateTime;
+use chrono::UTC;
+
+use account::details::{ClientExtensions, Details, Order, OrderState, Position, PositionSide, TradeState, TradeSummary};
+use account::instruments::{Instrument, InstrumentType};
+use account::orders::{CancelOrderResponse, CreateOrderResponse, MarketOrderRequest, OrderType};
+use account::positions::{ClosePositionRequest, ClosePositionResponse};
+use account::summary::Summary;
+use account::trades::{CloseTradeResponse, CloseUnits};
+use account::transactions::{TradeOpen, TradeReduce, Transaction, TransactionType};
+use account::Account;
+use instrument::candlestick::Candlestick;
+use instrument::candlestick_granularity::CandlestickGranularity;
+use primitives::{AccountUnits, Currency, Decimal, DecimalNumber, InstrumentName, PriceValue};
+
+/// The margin rate of instruments that were not added with
+/// `MockState::with_instrument`
+const DEFAULT_MARGIN_RATE: &'static str = "0.02";
+
+/// The user every Transaction is created by
+const USER_ID: i32 = 1;
+
+/// A request the mock server refuses, with the HTTP status and the
+/// errorMessage Oanda would respond with
+#[derive(Clone, Debug, PartialEq)]
+pub struct Rejection {
+    pub status: u16,
+    pub message: String
+}
+
+impl Rejection {
+    pub fn new(status: u16, message: &str) -> Rejection {
+        Rejection { status: status, message: message.to_string() }
+    }
+}
+
+/// The in-memory accounts, instruments and prices served by a `MockServer`.
+///
+/// Market Orders fill immediately at the latest ask (buying) or bid (selling)
+/// and reduce open Trades in the opposite direction first, oldest first, as
+/// for an Account without hedging. Profit and loss is in the instrument's
+/// quote currency whatever the Account's currency is, and there is no
+/// financing. Stop Loss and Take Profit Orders created on fill stay pending
+/// until they are cancelled or their Trade is closed.
+#[derive(Clone, Debug, Default)]
+pub struct MockState {
+    accounts: Vec<MockAccount>,
+    instruments: Vec<Instrument>,
+    candles: HashMap<(InstrumentName, CandlestickGranularity), Vec<Candlestick>>,
+    prices: HashMap<InstrumentName, (PriceValue, PriceValue)>,
+    transactions: Vec<Transaction>
+}
+
+#[derive(Clone, Debug)]
+struct MockAccount {
+    id: String,
+    currency: Currency,
+    balance: AccountUnits,
+    pl: AccountUnits,
+    created_time: DateTime<UTC>,
+    trades: Vec<TradeSummary>,
+    orders: Vec<(Order, String)>,
+    position_pl: BTreeMap<InstrumentName, (AccountUnits, AccountUnits)>
+}
+
+/// The Transactions created by filling a Market Order
+struct Fill {
+    create: Transaction,
+    fill: Transaction,
+    related: Vec<String>
+}
+
+impl MockState {
+    pub fn new() -> MockState {
+        MockState::default()
+    }
+
+    /// Add an Account without hedging
+    pub fn with_account(&mut self, id: &str, currency: Currency, balance: AccountUnits) -> &mut MockState {
+        self.accounts.push(MockAccount {
+            id: id.to_string(),
+            currency: currency,
+            balance: balance,
+            pl: AccountUnits::default(),
+            created_time: UTC::now(),
+            trades: Vec::new(),
+            orders: Vec::new(),
+            position_pl: BTreeMap::new()
+        });
+        self
+    }
+
+    /// Add an instrument every Account can trade, e.g. an `Instrument::currency_pair`
+    pub fn with_instrument(&mut self, instrument: Instrument) -> &mut MockState {
+        self.instruments.retain(|i| i.name != instrument.name);
+        self.instruments.push(instrument);
+        self
+    }
+
+    /// Serve `candles` for an instrument and granularity. Unless a price is
+    /// set, orders fill at the close of the latest candlestick of the
+    /// instrument.
+    pub fn with_candles(
+        &mut self,
+        instrument: InstrumentName,
+        granularity: CandlestickGranularity,
+        candles: Vec<Candlestick>
+    ) -> &mut MockState {
+        let mut candles = candles;
+        candles.sort_by_key(|c| c.time);
+
+        self.candles.insert((instrument, granularity), candles);
+        self.revalue();
+        self
+    }
+
+    /// Set the bid and ask that orders for `instrument` fill at, and that open
+    /// Trades are valued at
+    pub fn with_price(&mut self, instrument: InstrumentName, bid: PriceValue, ask: PriceValue) -> &mut MockState {
+        self.prices.insert(instrument, (bid, ask));
+        self.revalue();
+        self
+    }
+
+    /// Every Transaction created so far, oldest first
+    pub fn transactions(&self) -> &[Transaction] {
+        &self.transactions
+    }
+
+    /// The Accounts as listed by the `/accounts` endpoint
+    pub fn accounts(&self) -> Vec<Account<'static>> {
+        self.accounts.iter().map(|a| Account::new(&a.id, None)).collect()
+    }
+
+    pub fn last_transaction_id(&self) -> String {
+        self.transactions.len().to_string()
+    }
+
+    pub fn summary(&self, account_id: &str) -> Result<Summary, Rejection> {
+        let account = self.account(account_id)?;
+        let unrealized_pl = account.trades.iter().fold(AccountUnits::default(), |pl, t| pl + t.unrealized_pl);
+        let nav = account.balance + unrealized_pl;
+        let margin_used = account.trades.iter().fold(Decimal::from(0), |margin, trade| {
+            margin + self.position_value(trade) * self.margin_rate(&trade.instrument).value()
+        });
+        let position_value = account.trades.iter().fold(Decimal::from(0), |value, t| value + self.position_value(t));
+        let margin_available = AccountUnits::from((nav.value() - margin_used).max(Decimal::from(0)));
+        let percent = |divisor: Decimal| if divisor.is_zero() {
+            DecimalNumber::default()
+        } else {
+            DecimalNumber::from((margin_used / divisor).round_dp(5))
+        };
+
+        Ok(Summary {
+            id: account.id.clone(),
+            alias: Some(account.id.clone()),
+            currency: account.currency,
+            balance: account.balance,
+            created_by_user_id: USER_ID,
+            created_time: account.created_time,
+            pl: account.pl,
+            resettable_pl: account.pl,
+            resettabled_pl_time: None,
+            margin_rate: Some(DEFAULT_MARGIN_RATE.parse().unwrap()),
+            margin_call_enter_time: None,
+            margin_call_extension_count: None,
+            last_margin_call_extension_time: None,
+            open_trade_count: account.trades.len() as i32,
+            open_position_count: self.positions(account).iter()
+                .filter(|p| !p.long.units.value().is_zero() || !p.short.units.value().is_zero())
+                .count() as i32,
+            pending_order_count: account.orders.len() as i32,
+            hedging_enabled: false,
+            unrealized_pl: unrealized_pl,
+            nav: nav,
+            margin_used: AccountUnits::from(margin_used),
+            margin_available: margin_available,
+            position_value: AccountUnits::from(position_value),
+            margin_closeout_unrealized_pl: unrealized_pl,
+            margin_closeout_nav: nav,
+            margin_closeout_margin_used: AccountUnits::from(margin_used),
+            margin_closeout_percent: percent(nav.value() * Decimal::from(2)),
+            margin_closeout_position_value: AccountUnits::from(position_value),
+            withdrawal_limit: margin_available,
+            margin_call_margin_used: AccountUnits::from(margin_used),
+            margin_call_percent: percent(nav.value()),
+            last_transaction_id: self.last_transaction_id()
+        })
+    }
+
+    pub fn details(&self, account_id: &str) -> Result<Details, Rejection> {
+        let summary = self.summary(account_id)?;
+        let account = self.account(account_id)?;
+
+        Ok(Details {
+            id: summary.id,
+            alias: summary.alias,
+            currency: summary.currency,
+            balance: summary.balance,
+            created_by_user_id: summary.created_by_user_id,
+            created_time: summary.created_time,
+            pl: summary.pl,
+            resettable_pl: summary.resettable_pl,
+            resettabled_pl_time: summary.resettabled_pl_time,
+            margin_rate: summary.margin_rate,
+            margin_call_enter_time: summary.margin_call_enter_time,
+            margin_call_extension_count: summary.margin_call_extension_count,
+            last_margin_call_extension_time: summary.last_margin_call_extension_time,
+            open_trade_count: summary.open_trade_count,
+            open_position_count: summary.open_position_count,
+            pending_order_count: summary.pending_order_count,
+            hedging_enabled: summary.hedging_enabled,
+            unrealized_pl: summary.unrealized_pl,
+            nav: summary.nav,
+            margin_used: summary.margin_used,
+            margin_available: summary.margin_available,
+            position_value: summary.position_value,
+            margin_closeout_unrealized_pl: summary.margin_closeout_unrealized_pl,
+            margin_closeout_nav: summary.margin_closeout_nav,
+            margin_closeout_margin_used: summary.margin_closeout_margin_used,
+            margin_closeout_percent: summary.margin_closeout_percent,
+            margin_closeout_position_value: summary.margin_closeout_position_value,
+            withdrawal_limit: summary.withdrawal_limit,
+            margin_call_margin_used: summary.margin_call_margin_used,
+            margin_call_percent: summary.margin_call_percent,
+            last_transaction_id: summary.last_transaction_id,
+            trades: account.trades.clone(),
+            positions: self.positions(account),
+            orders: account.orders.iter().map(|&(ref order, _)| order.clone()).collect()
+        })
+    }
+
+    /// The instruments an Account can trade, only those in `names` if given
+    pub fn instruments(&self, account_id: &str, names: Option<&[InstrumentName]>) -> Result<Vec<Instrument>, Rejection> {
+        self.account(account_id)?;
+
+        Ok(self.instruments.iter()
+            .filter(|i| names.map_or(true, |names| names.contains(&i.name)))
+            .cloned()
+            .collect())
+    }
+
+    pub fn position(&self, account_id: &str, instrument: &InstrumentName) -> Result<Position, Rejection> {
+        let account = self.account(account_id)?;
+
+        Ok(self.position_of(account, instrument))
+    }
+
+    /// The candlesticks of an instrument that are at or after `from` and
+    /// before `to`. With only one of them, `count` candlesticks from `from` or
+    /// up to `to`.
+    pub fn candles(
+        &self,
+        instrument: &InstrumentName,
+        granularity: CandlestickGranularity,
+        from: Option<DateTime<UTC>>,
+        to: Option<DateTime<UTC>>,
+        count: usize,
+        include_first: bool
+    ) -> Result<Vec<Candlestick>, Rejection> {
+        let candles = match self.candles.get(&(instrument.clone(), granularity)) {
+            Some(candles) => candles,
+            None if self.instruments.iter().any(|i| i.name == *instrument) => return Ok(Vec::new()),
+            None => return Err(Rejection::new(400, &format!("Invalid value specified for 'instrument': {}", instrument)))
+        };
+
+        let in_range = candles.iter().filter(|c| {
+            from.map_or(true, |from| if include_first { c.time >= from } else { c.time > from }) &&
+                to.map_or(true, |to| c.time < to)
+        });
+
+        Ok(match (from, to) {
+            (None, _) => {
+                let in_range: Vec<_> = in_range.collect();
+                in_range[in_range.len().saturating_sub(count)..].iter().map(|c| (*c).clone()).collect()
+            },
+            (Some(_), None) => in_range.take(count).cloned().collect(),
+            (Some(_), Some(_)) => in_range.cloned().collect()
+        })
+    }
+
+    /// Fill a Market Order
+    pub fn create_order(&mut self, account_id: &str, order: &MarketOrderRequest) -> Result<CreateOrderResponse, Rejection> {
+        if order.order_type != OrderType::MARKET {
+            return Err(Rejection::new(400, "Only Market Orders can be created on the mock server"))
+        }
+
+        let reason = "CLIENT_ORDER";
+        let mut fill = self.fill(account_id, &order.instrument, order.units, reason, None, |_| true)?;
+
+        if let Some(ref trade) = fill.fill.trade_opened.clone() {
+            let price_details = vec![
+                (TransactionType::STOP_LOSS_ORDER, order.stop_loss_on_fill.as_ref().map(|s| s.price)),
+                (TransactionType::TAKE_PROFIT_ORDER, order.take_profit_on_fill.as_ref().map(|t| t.price))
+            ];
+
+            for (transaction_type, price) in price_details {
+                if let Some(price) = price {
+                    let id = self.dependent_order(account_id, &fill.create.batch_id, &trade.trade_id, transaction_type, price);
+                    fill.related.push(id);
+                }
+            }
+        }
+
+        Ok(CreateOrderResponse {
+            order_create_transaction: fill.create,
+            order_fill_transaction: Some(fill.fill),
+            order_cancel_transaction: None,
+            related_transaction_ids: fill.related,
+            last_transaction_id: self.last_transaction_id()
+        })
+    }
+
+    /// Cancel a pending Order
+    pub fn cancel_order(&mut self, account_id: &str, order_id: &str) -> Result<CancelOrderResponse, Rejection> {
+        if !self.account(account_id)?.orders.iter().any(|&(ref o, _)| o.id == order_id) {
+            return Err(Rejection::new(404, "The Order specified does not exist"))
+        }
+
+        let cancel = self.cancel(account_id, order_id, "CLIENT_REQUEST", None);
+
+        Ok(CancelOrderResponse {
+            related_transaction_ids: vec![cancel.id.clone()],
+            order_cancel_transaction: cancel,
+            last_transaction_id: self.last_transaction_id()
+        })
+    }
+
+    /// Close all or some of the units of an open Trade
+    pub fn close_trade(&mut self, account_id: &str, trade_id: &str, units: &CloseUnits) -> Result<CloseTradeResponse, Rejection> {
+        let trade = match self.account(account_id)?.trades.iter().find(|t| t.id == trade_id) {
+            Some(trade) => trade.clone(),
+            None => return Err(Rejection::new(404, "The Trade specified does not exist"))
+        };
+
+        let units = match *units {
+            CloseUnits::All => trade.current_units,
+            CloseUnits::Units(units) if units.value() > Decimal::from(0) && units.value() <= trade.current_units.value().abs() => {
+                if trade.current_units.value().is_sign_negative() { -units } else { units }
+            },
+            _ => return Err(Rejection::new(400, "The units specified to close the Trade are invalid"))
+        };
+
+        let fill = self.fill(account_id, &trade.instrument, -units, "TRADE_CLOSE", Some(trade_id), |t| t.id == trade_id)?;
+
+        Ok(CloseTradeResponse {
+            order_create_transaction: fill.create,
+            order_fill_transaction: Some(fill.fill),
+            order_cancel_transaction: None,
+            related_transaction_ids: fill.related,
+            last_transaction_id: self.last_transaction_id()
+        })
+    }
+
+    /// Close the long and/or short units of a Position
+    pub fn close_position(
+        &mut self,
+        account_id: &str,
+        instrument: &InstrumentName,
+        request: &ClosePositionRequest
+    ) -> Result<ClosePositionResponse, Rejection> {
+        let position = self.position(account_id, instrument)?;
+        let mut response = ClosePositionResponse {
+            long_order_create_transaction: None,
+            long_order_fill_transaction: None,
+            long_order_cancel_transaction: None,
+            short_order_create_transaction: None,
+            short_order_fill_transaction: None,
+            short_order_cancel_transaction: None,
+            related_transaction_ids: Vec::new(),
+            last_transaction_id: String::new()
+        };
+
+        let sides = vec![(&request.long_units, position.long.units, true), (&request.short_units, position.short.units, false)];
+        for (close, open, long) in sides {
+            let units = match *close {
+                None | Some(CloseUnits::None) => continue,
+                Some(CloseUnits::All) => open,
+                Some(CloseUnits::Units(units)) if units.value().abs() <= open.value().abs() => {
+                    if long { units } else { -units }
+                }
+                Some(CloseUnits::Units(_)) => return Err(Rejection::new(400, "The units specified to close the Position are invalid"))
+            };
+
+            if units.value().is_zero() {
+                return Err(Rejection::new(400, "The Position requested to be closed out does not exist"))
+            }
+
+            let mut fill = self.fill(account_id, instrument, -units, "POSITION_CLOSEOUT", None, |_| true)?;
+            response.related_transaction_ids.append(&mut fill.related);
+
+            if long {
+                response.long_order_create_transaction = Some(fill.create);
+                response.long_order_fill_transaction = Some(fill.fill);
+            } else {
+                response.short_order_create_transaction = Some(fill.create);
+                response.short_order_fill_transaction = Some(fill.fill);
+            }
+        }
+
+        response.last_transaction_id = self.last_transaction_id();
+
+        Ok(response)
+    }
+
+    /// The bid and ask of an instrument, the close of its latest candlestick
+    /// unless one was set with `with_price`
+    pub fn price(&self, instrument: &InstrumentName) -> Option<(PriceValue, PriceValue)> {
+        if let Some(price) = self.prices.get(instrument) {
+            return Some(*price)
+        }
+
+        self.candles.iter()
+            .filter(|&(&(ref name, _), _)| name == instrument)
+            .filter_map(|(_, candles)| candles.last())
+            .max_by_key(|c| c.time)
+            .and_then(|c| match (c.bid.as_ref(), c.ask.as_ref(), c.mid.as_ref()) {
+                (Some(bid), Some(ask), _) => Some((bid.c, ask.c)),
+                (_, _, Some(mid)) => Some((mid.c, mid.c)),
+                _ => None
+            })
+    }
+
+    /// Fill `units` of a Market Order, reducing the open Trades of the
+    /// instrument that are in the other direction and `reducible` before
+    /// opening a new one
+    fn fill<F>(
+        &mut self,
+        account_id: &str,
+        instrument: &InstrumentName,
+        units: DecimalNumber,
+        reason: &str,
+        trade_id: Option<&str>,
+        reducible: F
+    ) -> Result<Fill, Rejection>
+        where F: Fn(&TradeSummary) -> bool
+    {
+        self.account(account_id)?;
+
+        if units.value().is_zero() {
+            return Err(Rejection::new(400, "The units specified for the Order are invalid"))
+        }
+        let (bid, ask) = match self.price(instrument) {
+            Some(price) => price,
+            None => return Err(Rejection::new(400, &format!("There is no price for {}", instrument)))
+        };
+        let price = if units.value().is_sign_negative() { bid } else { ask };
+
+        let mut create = self.transaction(account_id, TransactionType::MARKET_ORDER, None);
+        create.instrument = Some(instrument.clone());
+        create.units = Some(units);
+        create.reason = Some(reason.to_string());
+        create.trade_id = trade_id.map(str::to_string);
+        self.transactions.push(create.clone());
+
+        let mut fill = self.transaction(account_id, TransactionType::ORDER_FILL, Some(&create.batch_id));
+        fill.order_id = Some(create.id.clone());
+        fill.instrument = Some(instrument.clone());
+        fill.units = Some(units);
+        fill.price = Some(price);
+        fill.reason = Some(match reason {
+            "CLIENT_ORDER" => "MARKET_ORDER".to_string(),
+            reason => format!("MARKET_ORDER_{}", reason)
+        });
+
+        let now = fill.time;
+        let fill_id = fill.id.clone();
+        let mut remaining = units.value();
+        let mut pl = Decimal::from(0);
+        let mut closed = Vec::new();
+        let mut reduced = None;
+
+        {
+            let account = self.account_mut(account_id)?;
+
+            for trade in account.trades.iter_mut().filter(|t| t.instrument == *instrument && reducible(t)) {
+                let open = trade.current_units.value();
+
+                if remaining.is_zero() || open.is_sign_negative() == remaining.is_sign_negative() {
+                    continue
+                }
+
+                let closing = if open.abs() <= remaining.abs() { open } else { -remaining };
+                let realized = ((price.value() - trade.price.value()) * closing).round_dp(4);
+
+                remaining += closing;
+                pl += realized;
+                trade.current_units = DecimalNumber::from(open - closing);
+                trade.realized_pl = trade.realized_pl + AccountUnits::from(realized);
+                trade.average_close_price = Some(price);
+                trade.closing_transaction_ids.push(fill_id.clone());
+
+                let reduce = TradeReduce {
+                    trade_id: trade.id.clone(),
+                    units: DecimalNumber::from(-closing),
+                    price: Some(price),
+                    realized_pl: AccountUnits::from(realized),
+                    financing: AccountUnits::default()
+                };
+
+                let position = account.position_pl.entry(instrument.clone()).or_insert_with(Default::default);
+                if open.is_sign_negative() {
+                    position.1 = position.1 + AccountUnits::from(realized);
+                } else {
+                    position.0 = position.0 + AccountUnits::from(realized);
+                }
+
+                if trade.current_units.value().is_zero() {
+                    trade.state = TradeState::CLOSED;
+                    trade.close_time = Some(now);
+                    closed.push(reduce);
+                } else {
+                    reduced = Some(reduce);
+                }
+            }
+
+            account.trades.retain(|t| t.state == TradeState::OPEN);
+            account.balance = account.balance + AccountUnits::from(pl);
+            account.pl = account.pl + AccountUnits::from(pl);
+
+            if !remaining.is_zero() {
+                account.trades.push(TradeSummary {
+                    id: fill_id.clone(),
+                    instrument: instrument.clone(),
+                    price: price,
+                    open_time: now,
+                    state: TradeState::OPEN,
+                    initial_units: DecimalNumber::from(remaining),
+                    current_units: DecimalNumber::from(remaining),
+                    realized_pl: AccountUnits::default(),
+                    unrealized_pl: AccountUnits::default(),
+                    average_close_price: None,
+                    closing_transaction_ids: Vec::new(),
+                    financing: AccountUnits::default(),
+                    close_time: None,
+                    client_extensions: ClientExtensions { id: String::new(), tag: String::new(), comment: String::new() },
+                    take_profit_order_id: None,
+                    stop_loss_order_id: None,
+                    trailing_stop_loss_order_id: None
+                });
+
+                fill.trade_opened = Some(TradeOpen { trade_id: fill_id.clone(), units: DecimalNumber::from(remaining), price: Some(price) });
+            }
+
+            fill.pl = Some(AccountUnits::from(pl));
+            fill.financing = Some(AccountUnits::default());
+            fill.account_balance = Some(account.balance);
+        }
+
+        if !closed.is_empty() {
+            fill.trades_closed = Some(closed.clone());
+        }
+        fill.trade_reduced = reduced;
+        self.transactions.push(fill.clone());
+
+        let mut related = vec![create.id.clone(), fill.id.clone()];
+        for trade in closed {
+            let linked = self.account(account_id)?.orders.iter()
+                .filter(|&&(_, ref linked)| *linked == trade.trade_id)
+                .map(|&(ref order, _)| order.id.clone())
+                .collect::<Vec<_>>();
+
+            for order_id in linked {
+                related.push(self.cancel(account_id, &order_id, "LINKED_TRADE_CLOSED", Some(&create.batch_id)).id);
+            }
+        }
+
+        self.revalue();
+
+        Ok(Fill { create: create, fill: fill, related: related })
+    }
+
+    /// Create a Stop Loss or Take Profit Order for an open Trade
+    fn dependent_order(
+        &mut self,
+        account_id: &str,
+        batch_id: &str,
+        trade_id: &str,
+        transaction_type: TransactionType,
+        price: PriceValue
+    ) -> String {
+        let mut transaction = self.transaction(account_id, transaction_type.clone(), Some(batch_id));
+        transaction.trade_id = Some(trade_id.to_string());
+        transaction.price = Some(price);
+        transaction.reason = Some("ON_FILL".to_string());
+        self.transactions.push(transaction.clone());
+
+        let order = Order {
+            id: transaction.id.clone(),
+            create_time: transaction.time,
+            state: OrderState::PENDING,
+            client_extensions: ClientExtensions { id: String::new(), tag: String::new(), comment: String::new() }
+        };
+
+        if let Ok(account) = self.account_mut(account_id) {
+            if let Some(trade) = account.trades.iter_mut().find(|t| t.id == trade_id) {
+                if transaction_type == TransactionType::STOP_LOSS_ORDER {
+                    trade.stop_loss_order_id = Some(order.id.clone());
+                } else {
+                    trade.take_profit_order_id = Some(order.id.clone());
+                }
+            }
+            account.orders.push((order, trade_id.to_string()));
+        }
+
+        transaction.id
+    }
+
+    fn cancel(&mut self, account_id: &str, order_id: &str, reason: &str, batch_id: Option<&str>) -> Transaction {
+        let mut cancel = self.transaction(account_id, TransactionType::ORDER_CANCEL, batch_id);
+        cancel.order_id = Some(order_id.to_string());
+        cancel.reason = Some(reason.to_string());
+        self.transactions.push(cancel.clone());
+
+        if let Ok(account) = self.account_mut(account_id) {
+            account.orders.retain(|&(ref o, _)| o.id != order_id);
+
+            for trade in account.trades.iter_mut() {
+                if trade.stop_loss_order_id.as_ref().map(String::as_str) == Some(order_id) {
+                    trade.stop_loss_order_id = None;
+                }
+                if trade.take_profit_order_id.as_ref().map(String::as_str) == Some(order_id) {
+                    trade.take_profit_order_id = None;
+                }
+            }
+        }
+
+        cancel
+    }
+
+    /// A Transaction with the next ID, in the batch of `batch_id` or a batch
+    /// of its own
+    fn transaction(&self, account_id: &str, transaction_type: TransactionType, batch_id: Option<&str>) -> Transaction {
+        let id = (self.transactions.len() + 1).to_string();
+
+        Transaction {
+            batch_id: batch_id.map_or_else(|| id.clone(), str::to_string),
+            id: id,
+            time: UTC::now(),
+            user_id: USER_ID,
+            account_id: account_id.to_string(),
+            request_id: None,
+            transaction_type: transaction_type,
+            instrument: None,
+            units: None,
+            price: None,
+            reason: None,
+            order_id: None,
+            trade_id: None,
+            pl: None,
+            financing: None,
+            account_balance: None,
+            trade_opened: None,
+            trades_closed: None,
+            trade_reduced: None
+        }
+    }
+
+    /// Value every open Trade at the latest prices
+    fn revalue(&mut self) {
+        let latest = self.instruments_with_trades().into_iter()
+            .filter_map(|i| self.price(&i).map(|p| (i, p)))
+            .collect::<HashMap<_, _>>();
+
+        for account in self.accounts.iter_mut() {
+            for trade in account.trades.iter_mut() {
+                if let Some(&(bid, ask)) = latest.get(&trade.instrument) {
+                    let units = trade.current_units.value();
+                    let close = if units.is_sign_negative() { ask } else { bid };
+
+                    trade.unrealized_pl = AccountUnits::from(((close.value() - trade.price.value()) * units).round_dp(4));
+                }
+            }
+        }
+    }
+
+    fn instruments_with_trades(&self) -> Vec<InstrumentName> {
+        let mut instruments: Vec<_> = self.accounts.iter()
+            .flat_map(|a| a.trades.iter().map(|t| t.instrument.clone()))
+            .collect();
+        instruments.sort();
+        instruments.dedup();
+
+        instruments
+    }
+
+    fn position_value(&self, trade: &TradeSummary) -> Decimal {
+        trade.current_units.value().abs() * trade.price.value()
+    }
+
+    fn margin_rate(&self, instrument: &InstrumentName) -> DecimalNumber {
+        self.instruments.iter()
+            .find(|i| i.name == *instrument)
+            .map(|i| i.margin_rate)
+            .unwrap_or_else(|| DEFAULT_MARGIN_RATE.parse().unwrap())
+    }
+
+    fn positions(&self, account: &MockAccount) -> Vec<Position> {
+        let mut instruments: Vec<_> = account.position_pl.keys().cloned().collect();
+        instruments.extend(account.trades.iter().map(|t| t.instrument.clone()));
+        instruments.sort();
+        instruments.dedup();
+
+        instruments.iter().map(|i| self.position_of(account, i)).collect()
+    }
+
+    fn position_of(&self, account: &MockAccount, instrument: &InstrumentName) -> Position {
+        let (long_pl, short_pl) = account.position_pl.get(instrument).cloned().unwrap_or_default();
+        let side = |long: bool, pl: AccountUnits| {
+            let trades: Vec<_> = account.trades.iter()
+                .filter(|t| t.instrument == *instrument && t.current_units.value().is_sign_negative() != long)
+                .collect();
+            let units = trades.iter().fold(Decimal::from(0), |units, t| units + t.current_units.value());
+            let cost = trades.iter().fold(Decimal::from(0), |cost, t| cost + t.current_units.value() * t.price.value());
+
+            PositionSide {
+                units: DecimalNumber::from(units),
+                average_price: PriceValue::from(if units.is_zero() { units } else { (cost / units).round_dp(5) }),
+                trade_ids: trades.iter().map(|t| t.id.clone()).collect(),
+                pl: pl,
+                unrealized_pl: trades.iter().fold(AccountUnits::default(), |upl, t| upl + t.unrealized_pl),
+                resettable_pl: pl
+            }
+        };
+        let long = side(true, long_pl);
+        let short = side(false, short_pl);
+
+        Position {
+            instrument: instrument.clone(),
+            pl: long.pl + short.pl,
+            unrealized_pl: long.unrealized_pl + short.unrealized_pl,
+            resettable_pl: long.pl + short.pl,
+            long: long,
+            short: short
+        }
+    }
+
+    fn account(&self, account_id: &str) -> Result<&MockAccount, Rejection> {
+        self.accounts.iter()
+            .find(|a| a.id == account_id)
+            .ok_or_else(|| Rejection::new(404, "The Account specified does not exist"))
+    }
+
+    fn account_mut(&mut self, account_id: &str) -> Result<&mut MockAccount, Rejection> {
+        self.accounts.iter_mut()
+            .find(|a| a.id == account_id)
+            .ok_or_else(|| Rejection::new(404, "The Account specified does not exist"))
+    }
+}
@@ -0,0 +1,1166 @@
+use std::collections::{BTreeMap, HashMap};
+
+use chrono::datetime::DateTime;
+use chrono::{Datelike, UTC, Weekday};
+
+use account::details::{ClientExtensions, Details, Order, OrderState, Position, PositionSide, TradeState, TradeSummary};
+use account::instruments::{DayOfWeek, Instrument};
+use account::orders::{OrderType, StopLossDetails, TakeProfitDetails, TimeInForce};
+use account::summary::Summary;
+use account::transactions::{TradeOpen, TradeReduce, Transaction, TransactionType};
+use error::{Error, Result};
+use instrument::alignment::Alignment;
+use instrument::candlestick::Candlestick;
+use instrument::candlestick_data::CandlestickData;
+use instrument::price::{ClientPrice, QuoteHomeConversionFactors};
+use primitives::{AccountUnits, Currency, Decimal, DecimalNumber, InstrumentName, PriceValue, RoundingStrategy};
+
+/// The user every Transaction is created by
+const USER_ID: i32 = 1;
+
+/// How many times the price the Account reached its margin closeout at during
+/// a candlestick is halved in on
+const CLOSEOUT_SEARCH_STEPS: usize = 32;
+
+/// The open, high, low and close of one side of a quote
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Bar {
+    pub o: Decimal,
+    pub h: Decimal,
+    pub l: Decimal,
+    pub c: Decimal
+}
+
+impl Bar {
+    fn at(price: Decimal) -> Bar {
+        Bar { o: price, h: price, l: price, c: price }
+    }
+}
+
+/// The bid and ask of an instrument over a candlestick, or at an instant
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Quote {
+    pub time: DateTime<UTC>,
+    pub bid: Bar,
+    pub ask: Bar
+}
+
+impl Quote {
+    /// The bid and ask of a Price, its best bid and ask or the closeout prices
+    /// when there is no liquidity
+    pub fn from_price(price: &ClientPrice) -> Quote {
+        let bid = price.bids.first().map_or(price.closeout_bid, |b| b.price);
+        let ask = price.asks.first().map_or(price.closeout_ask, |a| a.price);
+
+        Quote { time: price.time, bid: Bar::at(bid.value()), ask: Bar::at(ask.value()) }
+    }
+
+    /// The bid and ask of a candlestick, the mid prices for both when it has
+    /// no bid or ask
+    pub fn from_candle(candle: &Candlestick) -> Option<Quote> {
+        let bar = |data: &CandlestickData| Bar {
+            o: data.o.value(),
+            h: data.h.value(),
+            l: data.l.value(),
+            c: data.c.value()
+        };
+
+        match (candle.bid.as_ref(), candle.ask.as_ref(), candle.mid.as_ref()) {
+            (Some(bid), Some(ask), _) => Some(Quote { time: candle.time, bid: bar(bid), ask: bar(ask) }),
+            (_, _, Some(mid)) => Some(Quote { time: candle.time, bid: bar(mid), ask: bar(mid) }),
+            _ => None
+        }
+    }
+
+    /// The latest price, where a Market Order fills
+    fn latest(&self) -> Quote {
+        Quote { time: self.time, bid: Bar::at(self.bid.c), ask: Bar::at(self.ask.c) }
+    }
+
+    fn mid(&self) -> Decimal {
+        (self.bid.c + self.ask.c) / Decimal::from(2)
+    }
+
+    /// The quote at an instant during this one when the bid was `bid`, with
+    /// the spread of the open
+    fn at_bid(&self, bid: Decimal) -> Quote {
+        Quote { time: self.time, bid: Bar::at(bid), ask: Bar::at(bid + self.ask.o - self.bid.o) }
+    }
+}
+
+/// The fields of a Market, Limit or Stop Order request the book understands
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OrderBody {
+    #[serde(rename = "type")]
+    order_type: OrderType,
+    instrument: InstrumentName,
+    units: DecimalNumber,
+    #[serde(default)]
+    price: Option<PriceValue>,
+    #[serde(default)]
+    price_bound: Option<PriceValue>,
+    #[serde(default)]
+    time_in_force: Option<TimeInForce>,
+    #[serde(default)]
+    take_profit_on_fill: Option<TakeProfitDetails>,
+    #[serde(default)]
+    stop_loss_on_fill: Option<StopLossDetails>
+}
+
+/// A pending Limit, Stop, Take Profit or Stop Loss Order
+#[derive(Clone, Debug)]
+struct Pending {
+    order: Order,
+    order_type: OrderType,
+    instrument: InstrumentName,
+    /// The units of a Limit or Stop Order. Take Profit and Stop Loss Orders
+    /// close all the units of their Trade.
+    units: Decimal,
+    price: Decimal,
+    price_bound: Option<Decimal>,
+    trade_id: Option<String>,
+    take_profit: Option<Decimal>,
+    stop_loss: Option<Decimal>
+}
+
+impl Pending {
+    /// Whether the Order fills at its price or better, rather than at its
+    /// price or worse
+    fn is_limit(&self) -> bool {
+        self.order_type == OrderType::LIMIT || self.order_type == OrderType::TAKE_PROFIT
+    }
+
+    /// The reason of the ORDER_FILL Transaction that fills the Order
+    fn fill_reason(&self) -> String {
+        format!("{}_ORDER", self.order_type)
+    }
+}
+
+/// The price `units` of an Order at `price` fill at over `quote`, or None if
+/// the market did not reach the Order. When the market gapped through the
+/// price the Order fills at the open, which is better than a Limit Order's
+/// price and worse than a Stop Order's.
+fn trigger(limit: bool, units: Decimal, price: Decimal, quote: &Quote) -> Option<Decimal> {
+    let buy = !units.is_sign_negative();
+    let bar = if buy { quote.ask } else { quote.bid };
+
+    match (limit, buy) {
+        (true, true) if bar.l <= price => Some(bar.o.min(price)),
+        (true, false) if bar.h >= price => Some(bar.o.max(price)),
+        (false, true) if bar.h >= price => Some(bar.o.max(price)),
+        (false, false) if bar.l <= price => Some(bar.o.min(price)),
+        _ => None
+    }
+}
+
+fn day_of_week(day: Weekday) -> DayOfWeek {
+    match day {
+        Weekday::Sun => DayOfWeek::SUNDAY,
+        Weekday::Mon => DayOfWeek::MONDAY,
+        Weekday::Tue => DayOfWeek::TUESDAY,
+        Weekday::Wed => DayOfWeek::WEDNESDAY,
+        Weekday::Thu => DayOfWeek::THURSDAY,
+        Weekday::Fri => DayOfWeek::FRIDAY,
+        Weekday::Sat => DayOfWeek::SATURDAY
+    }
+}
+
+/// The days of financing Oanda charges currency pairs on each rollover, for
+/// instruments whose financing days are not given: one each weekday and three
+/// on Wednesday, for the weekend
+fn usual_days_charged(day: &DayOfWeek) -> i32 {
+    match *day {
+        DayOfWeek::WEDNESDAY => 3,
+        DayOfWeek::SATURDAY | DayOfWeek::SUNDAY => 0,
+        _ => 1
+    }
+}
+
+fn no_extensions() -> ClientExtensions {
+    ClientExtensions { id: String::new(), tag: String::new(), comment: String::new() }
+}
+
+fn rejection(status: u16, message: &str) -> Error {
+    Error::Api { status: status, message: message.to_string() }
+}
+
+/// The state of a paper Account. Every method that changes it starts a new
+/// batch of Transactions with `begin`.
+#[derive(Clone, Debug)]
+pub struct Book {
+    id: String,
+    currency: Currency,
+    balance: Decimal,
+    pl: Decimal,
+    created_time: DateTime<UTC>,
+    instruments: HashMap<InstrumentName, Instrument>,
+    quotes: HashMap<InstrumentName, Quote>,
+    factors: HashMap<InstrumentName, QuoteHomeConversionFactors>,
+    trades: Vec<TradeSummary>,
+    closed: Vec<TradeSummary>,
+    orders: Vec<Pending>,
+    transactions: Vec<Transaction>,
+    position_pl: BTreeMap<InstrumentName, (Decimal, Decimal)>,
+    time: Option<DateTime<UTC>>,
+    rollover: Option<DateTime<UTC>>,
+    margin_call: Option<DateTime<UTC>>,
+    batch: Option<String>,
+    related: Vec<String>
+}
+
+impl Book {
+    pub fn new(id: &str, currency: Currency, balance: AccountUnits) -> Book {
+        Book {
+            id: id.to_string(),
+            currency: currency,
+            balance: balance.value(),
+            pl: Decimal::from(0),
+            created_time: UTC::now(),
+            instruments: HashMap::new(),
+            quotes: HashMap::new(),
+            factors: HashMap::new(),
+            trades: Vec::new(),
+            closed: Vec::new(),
+            orders: Vec::new(),
+            transactions: Vec::new(),
+            position_pl: BTreeMap::new(),
+            time: None,
+            rollover: None,
+            margin_call: None,
+            batch: None,
+            related: Vec::new()
+        }
+    }
+
+    pub fn add_instrument(&mut self, instrument: Instrument) {
+        self.instruments.insert(instrument.name.clone(), instrument);
+    }
+
+    pub fn transactions(&self) -> &[Transaction] {
+        &self.transactions
+    }
+
+    pub fn closed_trades(&self) -> &[TradeSummary] {
+        &self.closed
+    }
+
+    pub fn time(&self) -> Option<DateTime<UTC>> {
+        self.time
+    }
+
+    pub fn set_factors(&mut self, instrument: &InstrumentName, factors: QuoteHomeConversionFactors) {
+        self.factors.insert(instrument.clone(), factors);
+    }
+
+    /// Move the market of `instrument` to `quote`: charge financing for every
+    /// rollover since the last quote, fill the pending Orders the quote
+    /// reaches, closeout the Account if it no longer has the margin for its
+    /// Trades at any point of the quote and revalue open Trades. Returns the
+    /// Transactions created.
+    pub fn update(&mut self, instrument: &InstrumentName, quote: Quote) -> Vec<Transaction> {
+        let first = self.transactions.len();
+
+        self.finance(quote.time);
+        self.time = Some(self.time.map_or(quote.time, |time| time.max(quote.time)));
+        self.quotes.insert(instrument.clone(), quote);
+        self.trigger_orders(instrument, &quote);
+        self.closeout_during(instrument, quote);
+        self.revalue();
+        self.check_margin();
+
+        self.transactions[first..].to_vec()
+    }
+
+    pub fn summary(&self) -> Summary {
+        let unrealized_pl = self.trades.iter().fold(Decimal::from(0), |pl, t| pl + t.unrealized_pl.value());
+        let nav = self.balance + unrealized_pl;
+        let margin_used = self.margin_used();
+        let position_value = self.trades.iter().fold(Decimal::from(0), |value, t| {
+            value + self.to_home(&t.instrument, t.current_units.value().abs() * self.mid(t))
+        });
+        let margin_available = (nav - margin_used).max(Decimal::from(0));
+        let percent = |divisor: Decimal| if divisor.is_zero() {
+            DecimalNumber::default()
+        } else {
+            DecimalNumber::from((margin_used / divisor).round_dp(5))
+        };
+        let money = |amount: Decimal| AccountUnits::from(amount.round_dp(4));
+
+        Summary {
+            id: self.id.clone(),
+            alias: Some(self.id.clone()),
+            currency: self.currency,
+            balance: money(self.balance),
+            created_by_user_id: USER_ID,
+            created_time: self.created_time,
+            pl: money(self.pl),
+            resettable_pl: money(self.pl),
+            resettabled_pl_time: None,
+            margin_rate: None,
+            margin_call_enter_time: self.margin_call,
+            margin_call_extension_count: None,
+            last_margin_call_extension_time: None,
+            open_trade_count: self.trades.len() as i32,
+            open_position_count: self.positions().iter()
+                .filter(|p| !p.long.units.value().is_zero() || !p.short.units.value().is_zero())
+                .count() as i32,
+            pending_order_count: self.orders.len() as i32,
+            hedging_enabled: false,
+            unrealized_pl: money(unrealized_pl),
+            nav: money(nav),
+            margin_used: money(margin_used),
+            margin_available: money(margin_available),
+            position_value: money(position_value),
+            margin_closeout_unrealized_pl: money(unrealized_pl),
+            margin_closeout_nav: money(nav),
+            margin_closeout_margin_used: money(margin_used),
+            margin_closeout_percent: percent(nav * Decimal::from(2)),
+            margin_closeout_position_value: money(position_value),
+            withdrawal_limit: money(margin_available),
+            margin_call_margin_used: money(margin_used),
+            margin_call_percent: percent(nav),
+            last_transaction_id: self.last_transaction_id()
+        }
+    }
+
+    pub fn details(&self) -> Details {
+        let summary = self.summary();
+
+        Details {
+            id: summary.id,
+            alias: summary.alias,
+            currency: summary.currency,
+            balance: summary.balance,
+            created_by_user_id: summary.created_by_user_id,
+            created_time: summary.created_time,
+            pl: summary.pl,
+            resettable_pl: summary.resettable_pl,
+            resettabled_pl_time: summary.resettabled_pl_time,
+            margin_rate: summary.margin_rate,
+            margin_call_enter_time: summary.margin_call_enter_time,
+            margin_call_extension_count: summary.margin_call_extension_count,
+            last_margin_call_extension_time: summary.last_margin_call_extension_time,
+            open_trade_count: summary.open_trade_count,
+            open_position_count: summary.open_position_count,
+            pending_order_count: summary.pending_order_count,
+            hedging_enabled: summary.hedging_enabled,
+            unrealized_pl: summary.unrealized_pl,
+            nav: summary.nav,
+            margin_used: summary.margin_used,
+            margin_available: summary.margin_available,
+            position_value: summary.position_value,
+            margin_closeout_unrealized_pl: summary.margin_closeout_unrealized_pl,
+            margin_closeout_nav: summary.margin_closeout_nav,
+            margin_closeout_margin_used: summary.margin_closeout_margin_used,
+            margin_closeout_percent: summary.margin_closeout_percent,
+            margin_closeout_position_value: summary.margin_closeout_position_value,
+            withdrawal_limit: summary.withdrawal_limit,
+            margin_call_margin_used: summary.margin_call_margin_used,
+            margin_call_percent: summary.margin_call_percent,
+            last_transaction_id: summary.last_transaction_id,
+            trades: self.trades.clone(),
+            positions: self.positions(),
+            orders: self.orders.iter().map(|p| p.order.clone()).collect()
+        }
+    }
+
+    pub fn last_transaction_id(&self) -> String {
+        self.transactions.len().to_string()
+    }
+
+    pub fn trade(&self, trade_id: &str) -> Result<TradeSummary> {
+        self.trades.iter()
+            .find(|t| t.id == trade_id)
+            .cloned()
+            .ok_or_else(|| rejection(404, "The Trade specified does not exist"))
+    }
+
+    /// Create a Market, Limit or Stop Order. Returns the ID of the Order's
+    /// create Transaction.
+    pub fn create_order(&mut self, body: OrderBody) -> Result<String> {
+        if !self.instruments.contains_key(&body.instrument) {
+            return Err(rejection(400, &format!("Invalid value specified for 'instrument': {}", body.instrument)))
+        }
+        if body.units.value().is_zero() {
+            return Err(rejection(400, "The units specified for the Order are invalid"))
+        }
+        if !self.converts(&body.instrument) {
+            let message = format!("There is no price to convert {} into {}", body.instrument.quote_currency(), self.currency);
+            return Err(rejection(400, &message))
+        }
+
+        let on_fill = (
+            body.take_profit_on_fill.as_ref().map(|t| t.price.value()),
+            body.stop_loss_on_fill.as_ref().map(|s| s.price.value())
+        );
+        let price_bound = body.price_bound.map(|p| p.value());
+
+        let transaction_type = match body.order_type {
+            OrderType::MARKET => {
+                self.begin();
+                return self.market(&body.instrument, body.units.value(), "CLIENT_ORDER", None, price_bound, on_fill)
+            },
+            OrderType::LIMIT => TransactionType::LIMIT_ORDER,
+            OrderType::STOP => TransactionType::STOP_ORDER,
+            ref order_type => {
+                return Err(rejection(400, &format!("{} Orders can not be created on a paper Account", order_type)))
+            }
+        };
+        let price = match body.price {
+            Some(price) => price,
+            None => return Err(rejection(400, "The price specified for the Order is invalid"))
+        };
+
+        self.begin();
+        let mut create = self.transaction(transaction_type);
+        create.instrument = Some(body.instrument.clone());
+        create.units = Some(body.units);
+        create.price = Some(price);
+        create.reason = Some("CLIENT_ORDER".to_string());
+        let id = self.push(create.clone());
+
+        let pending = Pending {
+            order: Order { id: id.clone(), create_time: create.time, state: OrderState::PENDING, client_extensions: no_extensions() },
+            order_type: body.order_type,
+            instrument: body.instrument.clone(),
+            units: body.units.value(),
+            price: price.value(),
+            price_bound: price_bound,
+            trade_id: None,
+            take_profit: on_fill.0,
+            stop_loss: on_fill.1
+        };
+        self.orders.push(pending.clone());
+
+        let immediate = match body.time_in_force {
+            Some(TimeInForce::FOK) | Some(TimeInForce::IOC) => true,
+            _ => false
+        };
+        let filled = match self.quotes.get(&body.instrument).map(Quote::latest) {
+            Some(quote) => match trigger(pending.is_limit(), pending.units, pending.price, &quote) {
+                Some(fill_price) => {
+                    self.execute(&pending, pending.units, fill_price);
+                    true
+                },
+                None => false
+            },
+            None => false
+        };
+
+        if immediate && !filled {
+            self.cancel(&id, "TIME_IN_FORCE_EXPIRED");
+        }
+        self.revalue();
+
+        Ok(id)
+    }
+
+    pub fn cancel_order(&mut self, order_id: &str) -> Result<Transaction> {
+        if !self.orders.iter().any(|o| o.order.id == order_id) {
+            return Err(rejection(404, "The Order specified does not exist"))
+        }
+
+        self.begin();
+        Ok(self.cancel(order_id, "CLIENT_REQUEST"))
+    }
+
+    /// Close `units` of a Trade, all of them when None. Returns the ID of the
+    /// Market Order's create Transaction.
+    pub fn close_trade(&mut self, trade_id: &str, units: Option<DecimalNumber>) -> Result<String> {
+        let trade = self.trade(trade_id)?;
+        let open = trade.current_units.value();
+        let units = match units {
+            None => open,
+            Some(units) if units.value() > Decimal::from(0) && units.value() <= open.abs() => {
+                if open.is_sign_negative() { -units.value() } else { units.value() }
+            },
+            Some(_) => return Err(rejection(400, "The units specified to close the Trade are invalid"))
+        };
+
+        self.begin();
+        self.market(&trade.instrument, -units, "TRADE_CLOSE", Some(trade_id), None, (None, None))
+    }
+
+    /// Close `units` of the long or short side of a Position with a Market
+    /// Order, all of them when None. Returns the ID of the Market Order's
+    /// create Transaction.
+    pub fn close_position(&mut self, instrument: &InstrumentName, long: bool, units: Option<DecimalNumber>) -> Result<String> {
+        let position = self.position(instrument);
+        let open = if long { position.long.units.value() } else { position.short.units.value() };
+        let units = match units {
+            None => open,
+            Some(units) if units.value().abs() <= open.abs() => if long { units.value() } else { -units.value() },
+            Some(_) => return Err(rejection(400, "The units specified to close the Position are invalid"))
+        };
+
+        if units.is_zero() {
+            return Err(rejection(400, "The Position requested to be closed out does not exist"))
+        }
+
+        self.begin();
+        self.market(instrument, -units, "POSITION_CLOSEOUT", None, None, (None, None))
+    }
+
+    /// The Transactions of the batch of the latest request
+    pub fn related(&self) -> &[String] {
+        &self.related
+    }
+
+    /// The ORDER_FILL or ORDER_CANCEL Transaction of an Order, if it was
+    /// filled or cancelled by the latest request
+    pub fn outcome(&self, order_id: &str, transaction_type: TransactionType) -> Option<Transaction> {
+        self.related.iter()
+            .filter_map(|id| self.transactions.iter().rev().find(|t| t.id == *id))
+            .find(|t| t.transaction_type == transaction_type && t.order_id.as_ref().map(String::as_str) == Some(order_id))
+            .cloned()
+    }
+
+    pub fn transaction_by_id(&self, id: &str) -> Option<Transaction> {
+        self.transactions.iter().find(|t| t.id == id).cloned()
+    }
+
+    pub fn position(&self, instrument: &InstrumentName) -> Position {
+        let (long_pl, short_pl) = self.position_pl.get(instrument).cloned()
+            .unwrap_or((Decimal::from(0), Decimal::from(0)));
+        let side = |long: bool, pl: Decimal| {
+            let trades: Vec<_> = self.trades.iter()
+                .filter(|t| t.instrument == *instrument && t.current_units.value().is_sign_negative() != long)
+                .collect();
+            let units = trades.iter().fold(Decimal::from(0), |units, t| units + t.current_units.value());
+            let cost = trades.iter().fold(Decimal::from(0), |cost, t| cost + t.current_units.value() * t.price.value());
+            let pl = AccountUnits::from(pl.round_dp(4));
+
+            PositionSide {
+                units: DecimalNumber::from(units),
+                average_price: PriceValue::from(if units.is_zero() { units } else { (cost / units).round_dp(5) }),
+                trade_ids: trades.iter().map(|t| t.id.clone()).collect(),
+                pl: pl,
+                unrealized_pl: trades.iter().fold(AccountUnits::default(), |upl, t| upl + t.unrealized_pl),
+                resettable_pl: pl
+            }
+        };
+        let long = side(true, long_pl);
+        let short = side(false, short_pl);
+
+        Position {
+            instrument: instrument.clone(),
+            pl: long.pl + short.pl,
+            unrealized_pl: long.unrealized_pl + short.unrealized_pl,
+            resettable_pl: long.pl + short.pl,
+            long: long,
+            short: short
+        }
+    }
+
+    fn positions(&self) -> Vec<Position> {
+        let mut instruments: Vec<_> = self.position_pl.keys().cloned().collect();
+        instruments.extend(self.trades.iter().map(|t| t.instrument.clone()));
+        instruments.sort();
+        instruments.dedup();
+
+        instruments.iter().map(|i| self.position(i)).collect()
+    }
+
+    /// Fill `units` with a Market Order at the latest bid (selling) or ask
+    /// (buying), or cancel it if that is past `price_bound` or would use more
+    /// margin than is available. Returns the ID of the create Transaction.
+    fn market(
+        &mut self,
+        instrument: &InstrumentName,
+        units: Decimal,
+        reason: &str,
+        trade_id: Option<&str>,
+        price_bound: Option<Decimal>,
+        on_fill: (Option<Decimal>, Option<Decimal>)
+    ) -> Result<String> {
+        let quote = match self.quotes.get(instrument) {
+            Some(quote) => quote.latest(),
+            None => return Err(rejection(400, &format!("There is no price for {}", instrument)))
+        };
+        let buy = !units.is_sign_negative();
+        let price = if buy { quote.ask.c } else { quote.bid.c };
+
+        let mut create = self.transaction(TransactionType::MARKET_ORDER);
+        create.instrument = Some(instrument.clone());
+        create.units = Some(DecimalNumber::from(units));
+        create.reason = Some(reason.to_string());
+        create.trade_id = trade_id.map(str::to_string);
+        let id = self.push(create);
+
+        let fill_reason = match reason {
+            "CLIENT_ORDER" => "MARKET_ORDER".to_string(),
+            reason => format!("MARKET_ORDER_{}", reason)
+        };
+        let out_of_bounds = price_bound.map_or(false, |bound| if buy { price > bound } else { price < bound });
+
+        if out_of_bounds {
+            self.cancel_unlisted(&id, "BOUNDS_VIOLATION");
+        } else if let Err(reason) = self.fill(&id, instrument, units, price, &fill_reason, trade_id, on_fill) {
+            self.cancel_unlisted(&id, reason);
+        }
+        self.revalue();
+
+        Ok(id)
+    }
+
+    /// Fill or cancel a pending Order the market reached at `price`
+    fn execute(&mut self, pending: &Pending, units: Decimal, price: Decimal) {
+        let id = pending.order.id.clone();
+        self.orders.retain(|o| o.order.id != id);
+        self.unlink(&id);
+
+        let out_of_bounds = pending.price_bound.map_or(false, |bound| {
+            if units.is_sign_negative() { price < bound } else { price > bound }
+        });
+
+        if out_of_bounds {
+            self.cancel_unlisted(&id, "BOUNDS_VIOLATION");
+        } else {
+            let trade_id = pending.trade_id.clone();
+            let on_fill = (pending.take_profit, pending.stop_loss);
+            let reason = pending.fill_reason();
+
+            if let Err(reason) = self.fill(&id, &pending.instrument, units, price, &reason, trade_id.as_ref().map(String::as_str), on_fill) {
+                self.cancel_unlisted(&id, reason);
+            }
+        }
+    }
+
+    /// Fill `units` at `price`, reducing open Trades of the instrument in the
+    /// other direction (only `trade_id` if given) first, oldest first, before
+    /// opening a Trade with the remaining units. Fails with the reason to
+    /// cancel the Order when the Account does not have the margin to open the
+    /// Trade.
+    fn fill(
+        &mut self,
+        order_id: &str,
+        instrument: &InstrumentName,
+        units: Decimal,
+        price: Decimal,
+        reason: &str,
+        trade_id: Option<&str>,
+        on_fill: (Option<Decimal>, Option<Decimal>)
+    ) -> ::std::result::Result<(), &'static str> {
+        let reducible = |t: &TradeSummary| {
+            t.instrument == *instrument &&
+                trade_id.map_or(true, |id| t.id == id) &&
+                t.current_units.value().is_sign_negative() != units.is_sign_negative()
+        };
+        let open_against = self.trades.iter()
+            .filter(|t| reducible(t))
+            .fold(Decimal::from(0), |total, t| total + t.current_units.value());
+        let reducing = if open_against.abs() >= units.abs() { units } else { -open_against };
+        let opening = units - reducing;
+
+        if !opening.is_zero() {
+            let required = self.margin(instrument, opening, price);
+            let released = self.margin(instrument, reducing, price);
+
+            if required > self.nav() - self.margin_used() + released {
+                return Err("INSUFFICIENT_MARGIN")
+            }
+        }
+
+        let mut fill = self.transaction(TransactionType::ORDER_FILL);
+        fill.order_id = Some(order_id.to_string());
+        fill.instrument = Some(instrument.clone());
+        fill.units = Some(DecimalNumber::from(units));
+        fill.price = Some(PriceValue::from(price));
+        fill.reason = Some(reason.to_string());
+
+        let now = fill.time;
+        let fill_id = fill.id.clone();
+        let mut remaining = units;
+        let mut pl = Decimal::from(0);
+        let mut closed = Vec::new();
+        let mut reduced = None;
+
+        for index in 0..self.trades.len() {
+            if remaining.is_zero() || !reducible(&self.trades[index]) {
+                continue
+            }
+
+            let open = self.trades[index].current_units.value();
+            let closing = if open.abs() <= remaining.abs() { open } else { -remaining };
+            let realized = self.to_home(instrument, (price - self.trades[index].price.value()) * closing).round_dp(4);
+
+            remaining += closing;
+            pl += realized;
+
+            let trade = &mut self.trades[index];
+            trade.current_units = DecimalNumber::from(open - closing);
+            trade.realized_pl = trade.realized_pl + AccountUnits::from(realized);
+            trade.average_close_price = Some(PriceValue::from(price));
+            trade.closing_transaction_ids.push(fill_id.clone());
+
+            let reduce = TradeReduce {
+                trade_id: trade.id.clone(),
+                units: DecimalNumber::from(-closing),
+                price: Some(PriceValue::from(price)),
+                realized_pl: AccountUnits::from(realized),
+                financing: AccountUnits::default()
+            };
+
+            let position = self.position_pl.entry(instrument.clone()).or_insert((Decimal::from(0), Decimal::from(0)));
+            if open.is_sign_negative() {
+                position.1 += realized;
+            } else {
+                position.0 += realized;
+            }
+
+            if trade.current_units.value().is_zero() {
+                trade.state = TradeState::CLOSED;
+                trade.close_time = Some(now);
+                closed.push(reduce);
+            } else {
+                reduced = Some(reduce);
+            }
+        }
+
+        self.closed.extend(self.trades.iter().filter(|t| t.state == TradeState::CLOSED).cloned());
+        self.trades.retain(|t| t.state == TradeState::OPEN);
+        self.balance += pl;
+        self.pl += pl;
+
+        if !remaining.is_zero() {
+            self.trades.push(TradeSummary {
+                id: fill_id.clone(),
+                instrument: instrument.clone(),
+                price: PriceValue::from(price),
+                open_time: now,
+                state: TradeState::OPEN,
+                initial_units: DecimalNumber::from(remaining),
+                current_units: DecimalNumber::from(remaining),
+                realized_pl: AccountUnits::default(),
+                unrealized_pl: AccountUnits::default(),
+                average_close_price: None,
+                closing_transaction_ids: Vec::new(),
+                financing: AccountUnits::default(),
+                close_time: None,
+                client_extensions: no_extensions(),
+                take_profit_order_id: None,
+                stop_loss_order_id: None,
+                trailing_stop_loss_order_id: None
+            });
+
+            fill.trade_opened = Some(TradeOpen {
+                trade_id: fill_id.clone(),
+                units: DecimalNumber::from(remaining),
+                price: Some(PriceValue::from(price))
+            });
+        }
+
+        fill.pl = Some(AccountUnits::from(pl));
+        fill.financing = Some(AccountUnits::default());
+        fill.account_balance = Some(AccountUnits::from(self.balance));
+        if !closed.is_empty() {
+            fill.trades_closed = Some(closed.clone());
+        }
+        fill.trade_reduced = reduced;
+        self.push(fill);
+
+        for trade in closed {
+            let linked: Vec<_> = self.orders.iter()
+                .filter(|o| o.trade_id.as_ref() == Some(&trade.trade_id))
+                .map(|o| o.order.id.clone())
+                .collect();
+
+            for order_id in linked {
+                self.cancel(&order_id, "LINKED_TRADE_CLOSED");
+            }
+        }
+
+        if !remaining.is_zero() {
+            if let Some(price) = on_fill.0 {
+                self.dependent_order(&fill_id, OrderType::TAKE_PROFIT, price);
+            }
+            if let Some(price) = on_fill.1 {
+                self.dependent_order(&fill_id, OrderType::STOP_LOSS, price);
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Create a Take Profit or Stop Loss Order for an open Trade
+    fn dependent_order(&mut self, trade_id: &str, order_type: OrderType, price: Decimal) {
+        let instrument = match self.trades.iter().find(|t| t.id == trade_id) {
+            Some(trade) => trade.instrument.clone(),
+            None => return
+        };
+        let transaction_type = if order_type == OrderType::STOP_LOSS {
+            TransactionType::STOP_LOSS_ORDER
+        } else {
+            TransactionType::TAKE_PROFIT_ORDER
+        };
+
+        let mut transaction = self.transaction(transaction_type);
+        transaction.trade_id = Some(trade_id.to_string());
+        transaction.price = Some(PriceValue::from(price));
+        transaction.reason = Some("ON_FILL".to_string());
+        let time = transaction.time;
+        let id = self.push(transaction);
+
+        if let Some(trade) = self.trades.iter_mut().find(|t| t.id == trade_id) {
+            if order_type == OrderType::STOP_LOSS {
+                trade.stop_loss_order_id = Some(id.clone());
+            } else {
+                trade.take_profit_order_id = Some(id.clone());
+            }
+        }
+
+        self.orders.push(Pending {
+            order: Order { id: id, create_time: time, state: OrderState::PENDING, client_extensions: no_extensions() },
+            order_type: order_type,
+            instrument: instrument,
+            units: Decimal::from(0),
+            price: price,
+            price_bound: None,
+            trade_id: Some(trade_id.to_string()),
+            take_profit: None,
+            stop_loss: None
+        });
+    }
+
+    /// Cancel a pending Order
+    fn cancel(&mut self, order_id: &str, reason: &str) -> Transaction {
+        self.orders.retain(|o| o.order.id != order_id);
+        self.unlink(order_id);
+        self.cancel_unlisted(order_id, reason)
+    }
+
+    /// Create the ORDER_CANCEL Transaction of an Order that is not pending
+    fn cancel_unlisted(&mut self, order_id: &str, reason: &str) -> Transaction {
+        let mut cancel = self.transaction(TransactionType::ORDER_CANCEL);
+        cancel.order_id = Some(order_id.to_string());
+        cancel.reason = Some(reason.to_string());
+        self.push(cancel.clone());
+
+        cancel
+    }
+
+    /// Forget a Take Profit or Stop Loss Order on its Trade
+    fn unlink(&mut self, order_id: &str) {
+        for trade in self.trades.iter_mut() {
+            if trade.stop_loss_order_id.as_ref().map(String::as_str) == Some(order_id) {
+                trade.stop_loss_order_id = None;
+            }
+            if trade.take_profit_order_id.as_ref().map(String::as_str) == Some(order_id) {
+                trade.take_profit_order_id = None;
+            }
+        }
+    }
+
+    /// Fill the pending Orders of `instrument` that `quote` reaches, Stop
+    /// Loss Orders first and then oldest first. A candlestick does not tell
+    /// whether it reached a Trade's Take Profit or Stop Loss first, so when it
+    /// reaches both the Stop Loss fills. Orders created by those fills wait
+    /// for the next quote.
+    fn trigger_orders(&mut self, instrument: &InstrumentName, quote: &Quote) {
+        let mut pending: Vec<_> = self.orders.iter().filter(|o| o.instrument == *instrument).collect();
+        pending.sort_by_key(|o| o.order_type != OrderType::STOP_LOSS);
+        let ids: Vec<_> = pending.into_iter().map(|o| o.order.id.clone()).collect();
+
+        for id in ids {
+            let pending = match self.orders.iter().find(|o| o.order.id == id) {
+                Some(pending) => pending.clone(),
+                None => continue
+            };
+            let units = match pending.trade_id {
+                Some(ref trade_id) => match self.trades.iter().find(|t| t.id == *trade_id) {
+                    Some(trade) => -trade.current_units.value(),
+                    None => continue
+                },
+                None => pending.units
+            };
+
+            if let Some(price) = trigger(pending.is_limit(), units, pending.price, quote) {
+                self.begin();
+                self.execute(&pending, units, price);
+            }
+        }
+    }
+
+    /// Charge or pay the financing of open Trades at every daily rollover
+    /// (5pm New York) up to `time`
+    fn finance(&mut self, time: DateTime<UTC>) {
+        let alignment = Alignment::default();
+        let mut rollover = match self.rollover {
+            Some(rollover) => rollover,
+            None => alignment.next_day(alignment.start_of_day(time))
+        };
+
+        while rollover <= time {
+            self.charge_financing(rollover, alignment.trading_date(rollover).weekday());
+            rollover = alignment.next_day(rollover);
+        }
+
+        self.rollover = Some(rollover);
+    }
+
+    fn charge_financing(&mut self, rollover: DateTime<UTC>, day: Weekday) {
+        let day = day_of_week(day);
+        let mut total = Decimal::from(0);
+
+        for index in 0..self.trades.len() {
+            let charge = {
+                let trade = &self.trades[index];
+                let conversion = self.to_home(&trade.instrument, Decimal::from(1));
+
+                self.instruments.get(&trade.instrument)
+                    .and_then(|i| {
+                        let price = PriceValue::from(self.mid(trade));
+                        i.estimate_financing(trade.current_units, price, conversion, i.days_charged_on(&day).unwrap_or_else(|| usual_days_charged(&day)))
+                    })
+                    .map_or(Decimal::from(0), |f| f.value().round_dp(4))
+            };
+
+            self.trades[index].financing = self.trades[index].financing + AccountUnits::from(charge);
+            total += charge;
+        }
+
+        if total.is_zero() {
+            return
+        }
+
+        self.balance += total;
+        self.begin();
+
+        let mut transaction = self.transaction(TransactionType::DAILY_FINANCING);
+        transaction.time = rollover;
+        transaction.financing = Some(AccountUnits::from(total));
+        transaction.account_balance = Some(AccountUnits::from(self.balance));
+        self.push(transaction);
+    }
+
+    /// Value every open Trade at the latest bid (long) or ask (short)
+    fn revalue(&mut self) {
+        for index in 0..self.trades.len() {
+            let upl = {
+                let trade = &self.trades[index];
+
+                self.quotes.get(&trade.instrument).map(|quote| {
+                    let units = trade.current_units.value();
+                    let close = if units.is_sign_negative() { quote.ask.c } else { quote.bid.c };
+
+                    self.to_home(&trade.instrument, (close - trade.price.value()) * units).round_dp(4)
+                })
+            };
+
+            if let Some(upl) = upl {
+                self.trades[index].unrealized_pl = AccountUnits::from(upl);
+            }
+        }
+    }
+
+    /// Closeout the Account at the price it reached its margin closeout at
+    /// during `quote`, if it did. Open Trades of `instrument` are valued at
+    /// the adverse extreme of the quote, the low bid of long Trades or the
+    /// high ask of short Trades, rather than at its close, and the closeout
+    /// fills where the price first crossed the closeout level on the way
+    /// there from the open.
+    fn closeout_during(&mut self, instrument: &InstrumentName, quote: Quote) {
+        let units = self.trades.iter()
+            .filter(|t| t.instrument == *instrument)
+            .fold(Decimal::from(0), |units, t| units + t.current_units.value());
+        let short = units.is_sign_negative();
+        let extreme = if short { quote.ask.h - quote.ask.o + quote.bid.o } else { quote.bid.l };
+
+        if units.is_zero() || !self.closed_out_at(instrument, quote.at_bid(extreme)) {
+            self.quotes.insert(instrument.clone(), quote);
+            return
+        }
+
+        let (mut reached, mut not_reached) = (extreme, quote.bid.o);
+        if self.closed_out_at(instrument, quote.at_bid(not_reached)) {
+            reached = not_reached;
+        } else {
+            for _ in 0..CLOSEOUT_SEARCH_STEPS {
+                let middle = (reached + not_reached) / Decimal::from(2);
+
+                if self.closed_out_at(instrument, quote.at_bid(middle)) {
+                    reached = middle;
+                } else {
+                    not_reached = middle;
+                }
+            }
+
+            // Round to a price the instrument quotes, further into the closeout
+            if let Some(precision) = self.instruments.get(instrument).map(|i| i.display_precision.max(0) as u32) {
+                let strategy = if short { RoundingStrategy::ToPositiveInfinity } else { RoundingStrategy::ToNegativeInfinity };
+                reached = reached.round_dp_with_strategy(precision, strategy);
+            }
+        }
+
+        self.quotes.insert(instrument.clone(), quote.at_bid(reached));
+        self.revalue();
+        self.check_margin();
+        self.quotes.insert(instrument.clone(), quote);
+    }
+
+    /// Whether the Account is at its margin closeout with `instrument` at
+    /// `quote`. Leaves the open Trades valued at it.
+    fn closed_out_at(&mut self, instrument: &InstrumentName, quote: Quote) -> bool {
+        self.quotes.insert(instrument.clone(), quote);
+        self.revalue();
+
+        let used = self.margin_used();
+        !used.is_zero() && self.nav() * Decimal::from(2) <= used
+    }
+
+    /// Enter or exit a margin call as the NAV falls below or recovers to the
+    /// margin used, and close every Trade once the NAV is half the margin
+    /// used or less
+    fn check_margin(&mut self) {
+        let nav = self.nav();
+        let used = self.margin_used();
+        let in_call = !used.is_zero() && nav < used;
+
+        if in_call != self.margin_call.is_some() {
+            self.begin();
+            let transaction = self.transaction(if in_call {
+                TransactionType::MARGIN_CALL_ENTER
+            } else {
+                TransactionType::MARGIN_CALL_EXIT
+            });
+            self.margin_call = if in_call { Some(transaction.time) } else { None };
+            self.push(transaction);
+        }
+
+        if used.is_zero() || nav * Decimal::from(2) > used {
+            return
+        }
+
+        let mut instruments: Vec<_> = self.trades.iter().map(|t| t.instrument.clone()).collect();
+        instruments.sort();
+        instruments.dedup();
+
+        for instrument in instruments {
+            let units = self.trades.iter()
+                .filter(|t| t.instrument == instrument)
+                .fold(Decimal::from(0), |units, t| units + t.current_units.value());
+
+            if !units.is_zero() {
+                self.begin();
+                let _ = self.market(&instrument, -units, "MARGIN_CLOSEOUT", None, None, (None, None));
+            }
+        }
+
+        self.check_margin();
+    }
+
+    fn nav(&self) -> Decimal {
+        self.trades.iter().fold(self.balance, |nav, t| nav + t.unrealized_pl.value())
+    }
+
+    fn margin_used(&self) -> Decimal {
+        self.trades.iter().fold(Decimal::from(0), |margin, t| {
+            margin + self.margin(&t.instrument, t.current_units.value(), self.mid(t))
+        })
+    }
+
+    /// The margin in the home currency that `units` at `price` use
+    fn margin(&self, instrument: &InstrumentName, units: Decimal, price: Decimal) -> Decimal {
+        let rate = self.instruments.get(instrument).map_or(Decimal::from(1), |i| i.margin_rate.value());
+
+        self.to_home(instrument, units.abs() * price) * rate
+    }
+
+    /// The latest mid price of a Trade's instrument, its open price when
+    /// there is no quote
+    fn mid(&self, trade: &TradeSummary) -> Decimal {
+        self.quotes.get(&trade.instrument).map_or(trade.price.value(), Quote::mid)
+    }
+
+    /// Convert an amount of an instrument's quote currency into the home
+    /// currency. Orders are only created on instruments that can be
+    /// converted, and prices are never forgotten, so every Trade and Order
+    /// can be.
+    fn to_home(&self, instrument: &InstrumentName, amount: Decimal) -> Decimal {
+        self.conversion(instrument, amount)
+            .expect("orders are only created on instruments that convert into the home currency")
+    }
+
+    /// Whether amounts of an instrument can be converted into the home
+    /// currency once it has a price
+    fn converts(&self, instrument: &InstrumentName) -> bool {
+        instrument.base_currency() == Some(self.currency) || self.conversion(instrument, Decimal::from(1)).is_some()
+    }
+
+    /// Convert an amount of an instrument's quote currency into the home
+    /// currency, with the conversion factors of its latest Price or the mid
+    /// price of a pair of the two currencies, or None if there is no such
+    /// price.
+    fn conversion(&self, instrument: &InstrumentName, amount: Decimal) -> Option<Decimal> {
+        let quote = instrument.quote_currency();
+
+        if quote == self.currency {
+            return Some(amount)
+        }
+        if let Some(factors) = self.factors.get(instrument) {
+            return Some(factors.to_home(DecimalNumber::from(amount)).value())
+        }
+
+        let mut names: Vec<_> = self.quotes.keys().collect();
+        names.sort();
+
+        names.into_iter()
+            .filter_map(|name| {
+                let mid = self.quotes[name].mid();
+
+                if name.base_currency() == Some(quote) && name.quote_currency() == self.currency {
+                    Some(amount * mid)
+                } else if name.base_currency() == Some(self.currency) && name.quote_currency() == quote && !mid.is_zero() {
+                    Some(amount / mid)
+                } else {
+                    None
+                }
+            })
+            .next()
+    }
+
+    /// Start a new batch of Transactions
+    fn begin(&mut self) {
+        self.batch = None;
+        self.related.clear();
+    }
+
+    /// A Transaction with the next ID in the current batch, at the time of
+    /// the latest quote
+    fn transaction(&mut self, transaction_type: TransactionType) -> Transaction {
+        let id = (self.transactions.len() + 1).to_string();
+        let batch_id = self.batch.get_or_insert(id.clone()).clone();
+
+        Transaction {
+            id: id,
+            time: self.time.unwrap_or_else(UTC::now),
+            user_id: USER_ID,
+            account_id: self.id.clone(),
+            batch_id: batch_id,
+            request_id: None,
+            transaction_type: transaction_type,
+            instrument: None,
+            units: None,
+            price: None,
+            reason: None,
+            order_id: None,
+            trade_id: None,
+            pl: None,
+            financing: None,
+            account_balance: None,
+            trade_opened: None,
+            trades_closed: None,
+            trade_reduced: None
+        }
+    }
+
+    fn push(&mut self, transaction: Transaction) -> String {
+        let id = transaction.id.clone();
+
+        self.related.push(id.clone());
+        self.transactions.push(transaction);
+
+        id
+    }
+}
@@ -0,0 +1,496 @@
+//! A simulated Account that trades against prices you feed it.
+//!
+//! `PaperAccount` implements `TradingAccount`, so code written against the
+//! trait can trade a real `Account` or a paper one. Feed it live Prices with
+//! `update_price`, e.g. from a `PriceStream`, or replay candlesticks with
+//! `update_candle`:
+//!
+//! ```no_run
+//! # use oandars::account::orders::{CreateOrderRequest, LimitOrderRequest};
+//! # use oandars::instrument::candlestick::Candlestick;
+//! # use oandars::account::instruments::Instrument;
+//! # use oandars::paper::PaperAccount;
+//! # let candles: Vec<Candlestick> = Vec::new();
+//! let eur_usd = "EUR_USD".parse().unwrap();
+//! let mut account = PaperAccount::new("paper", "USD".parse().unwrap(), "10000".parse().unwrap());
+//! account.with_instrument(Instrument::currency_pair("EUR_USD".parse().unwrap()));
+//!
+//! let order = LimitOrderRequest::new("EUR_USD".parse().unwrap(), "1000".parse().unwrap(), "1.10000".parse().unwrap());
+//! account.create_order(&CreateOrderRequest { order: order });
+//!
+//! for candle in &candles {
+//!     for transaction in account.update_candle(&eur_usd, candle) {
+//!         println!("{} {}", transaction.id, transaction.transaction_type);
+//!     }
+//! }
+//! ```
+//!
+//! Orders fill as they would on an Account without hedging:
+//!
+//! - Market Orders fill at the latest ask (buying) or bid (selling).
+//! - Limit, Stop, Take Profit and Stop Loss Orders fill when the bid or ask
+//!   reaches their price, at their price or at the open when the market
+//!   gapped through it. A candlestick without bid and ask prices is treated
+//!   as having no spread. GTD and GFD Orders do not expire.
+//! - Fills reduce open Trades in the other direction first, oldest first.
+//! - An Order that would use more margin than is available, at the
+//!   instrument's margin rate, is cancelled.
+//! - Financing is charged at every 5pm New York rollover at the instrument's
+//!   financing rates.
+//! - All Trades are closed once the NAV falls to half the margin used.
+//!
+//! Profit and loss is converted into the Account's currency with the
+//! conversion factors of the latest Price, or the latest price of a pair of
+//! the two currencies the Account was fed.
+
+mod book;
+
+use std::cell::RefCell;
+
+use chrono::datetime::DateTime;
+use chrono::UTC;
+use serde_json;
+
+use account::details::{Details, Position, TradeSummary};
+use account::instruments::Instrument;
+use account::orders::{CancelOrderResponse, CreateOrderRequest, CreateOrderResponse, OrderRequest};
+use account::positions::{ClosePositionRequest, ClosePositionResponse};
+use account::summary::Summary;
+use account::trades::{CloseTradeRequest, CloseTradeResponse, CloseUnits};
+use account::trading::TradingAccount;
+use account::transactions::{Transaction, TransactionType};
+use error::{Error, Result};
+use instrument::candlestick::Candlestick;
+use instrument::price::ClientPrice;
+use primitives::{AccountUnits, Currency, InstrumentName};
+use self::book::{Book, OrderBody, Quote};
+
+/// A simulated Account
+#[derive(Clone, Debug)]
+pub struct PaperAccount {
+    id: String,
+    book: RefCell<Book>
+}
+
+impl PaperAccount {
+    /// An Account with `balance` in `currency` and no instruments
+    pub fn new(id: &str, currency: Currency, balance: AccountUnits) -> PaperAccount {
+        PaperAccount { id: id.to_string(), book: RefCell::new(Book::new(id, currency, balance)) }
+    }
+
+    /// Allow the Account to trade an instrument, e.g. one of
+    /// `Account::instruments` or `Instrument::currency_pair`
+    pub fn with_instrument(&mut self, instrument: Instrument) -> &mut PaperAccount {
+        self.book.borrow_mut().add_instrument(instrument);
+        self
+    }
+
+    /// Move the market to a Price. Returns the Transactions it caused, e.g.
+    /// Order fills and financing.
+    pub fn update_price(&self, price: &ClientPrice) -> Vec<Transaction> {
+        let mut book = self.book.borrow_mut();
+
+        if let Some(ref factors) = price.quote_home_conversion_factors {
+            book.set_factors(&price.instrument, factors.clone());
+        }
+
+        book.update(&price.instrument, Quote::from_price(price))
+    }
+
+    /// Move the market of `instrument` through a candlestick, filling the
+    /// Orders its high and low reach and valuing Trades at its close. Returns
+    /// the Transactions it caused.
+    pub fn update_candle(&self, instrument: &InstrumentName, candle: &Candlestick) -> Vec<Transaction> {
+        match Quote::from_candle(candle) {
+            Some(quote) => self.book.borrow_mut().update(instrument, quote),
+            None => Vec::new()
+        }
+    }
+
+    /// Every Transaction created so far, oldest first
+    pub fn transactions(&self) -> Vec<Transaction> {
+        self.book.borrow().transactions().to_vec()
+    }
+
+    /// The Trades that have been closed, in the order they were closed
+    pub fn closed_trades(&self) -> Vec<TradeSummary> {
+        self.book.borrow().closed_trades().to_vec()
+    }
+
+    /// The time of the latest Price or candlestick, which Transactions are
+    /// created at
+    pub fn time(&self) -> Option<DateTime<UTC>> {
+        self.book.borrow().time()
+    }
+
+    pub fn summary(&self) -> Summary {
+        self.try_summary().unwrap()
+    }
+
+    pub fn details(&self) -> Details {
+        self.try_details().unwrap()
+    }
+
+    pub fn position(&self, instrument: &InstrumentName) -> Position {
+        self.try_position(instrument).unwrap()
+    }
+
+    pub fn create_order<O: OrderRequest>(&self, request: &CreateOrderRequest<O>) -> CreateOrderResponse {
+        self.try_create_order(request).unwrap()
+    }
+
+    /// The create Transaction of an Order created by the latest request, and
+    /// its fill or cancel Transaction if it was filled or cancelled at once
+    fn market_order(book: &Book, order_id: &str) -> (Transaction, Option<Transaction>, Option<Transaction>) {
+        let create = book.transaction_by_id(order_id).expect("the Order was created");
+
+        (
+            create,
+            book.outcome(order_id, TransactionType::ORDER_FILL),
+            book.outcome(order_id, TransactionType::ORDER_CANCEL)
+        )
+    }
+}
+
+impl TradingAccount for PaperAccount {
+    fn id(&self) -> &str {
+        &self.id
+    }
+
+    fn try_summary(&self) -> Result<Summary> {
+        Ok(self.book.borrow().summary())
+    }
+
+    fn try_details(&self) -> Result<Details> {
+        Ok(self.book.borrow().details())
+    }
+
+    fn try_position(&self, instrument: &InstrumentName) -> Result<Position> {
+        Ok(self.book.borrow().position(instrument))
+    }
+
+    fn try_create_order<O: OrderRequest>(&self, request: &CreateOrderRequest<O>) -> Result<CreateOrderResponse> {
+        let body: OrderBody = serde_json::from_str(&serde_json::to_string(&request.order)?)
+            .map_err(|e| Error::Api { status: 400, message: format!("Invalid Order specification: {}", e) })?;
+        let mut book = self.book.borrow_mut();
+        let order_id = book.create_order(body)?;
+        let (create, fill, cancel) = PaperAccount::market_order(&book, &order_id);
+
+        Ok(CreateOrderResponse {
+            order_create_transaction: create,
+            order_fill_transaction: fill,
+            order_cancel_transaction: cancel,
+            related_transaction_ids: book.related().to_vec(),
+            last_transaction_id: book.last_transaction_id()
+        })
+    }
+
+    fn try_cancel_order(&self, order_id: &str) -> Result<CancelOrderResponse> {
+        let mut book = self.book.borrow_mut();
+        let cancel = book.cancel_order(order_id)?;
+
+        Ok(CancelOrderResponse {
+            order_cancel_transaction: cancel,
+            related_transaction_ids: book.related().to_vec(),
+            last_transaction_id: book.last_transaction_id()
+        })
+    }
+
+    fn try_close_trade(&self, trade_id: &str, request: &CloseTradeRequest) -> Result<CloseTradeResponse> {
+        let units = match request.units {
+            CloseUnits::All => None,
+            CloseUnits::Units(units) => Some(units),
+            CloseUnits::None => return Err(Error::Api {
+                status: 400,
+                message: "The units specified to close the Trade are invalid".to_string()
+            })
+        };
+        let mut book = self.book.borrow_mut();
+        let order_id = book.close_trade(trade_id, units)?;
+        let (create, fill, cancel) = PaperAccount::market_order(&book, &order_id);
+
+        Ok(CloseTradeResponse {
+            order_create_transaction: create,
+            order_fill_transaction: fill,
+            order_cancel_transaction: cancel,
+            related_transaction_ids: book.related().to_vec(),
+            last_transaction_id: book.last_transaction_id()
+        })
+    }
+
+    fn try_close_position(
+        &self,
+        instrument: &InstrumentName,
+        request: &ClosePositionRequest
+    ) -> Result<ClosePositionResponse> {
+        let mut book = self.book.borrow_mut();
+        let mut response = ClosePositionResponse {
+            long_order_create_transaction: None,
+            long_order_fill_transaction: None,
+            long_order_cancel_transaction: None,
+            short_order_create_transaction: None,
+            short_order_fill_transaction: None,
+            short_order_cancel_transaction: None,
+            related_transaction_ids: Vec::new(),
+            last_transaction_id: String::new()
+        };
+
+        for &(close, long) in &[(&request.long_units, true), (&request.short_units, false)] {
+            let units = match *close {
+                None | Some(CloseUnits::None) => continue,
+                Some(CloseUnits::All) => None,
+                Some(CloseUnits::Units(units)) => Some(units)
+            };
+            let order_id = book.close_position(instrument, long, units)?;
+            let (create, fill, cancel) = PaperAccount::market_order(&book, &order_id);
+            response.related_transaction_ids.extend(book.related().iter().cloned());
+
+            if long {
+                response.long_order_create_transaction = Some(create);
+                response.long_order_fill_transaction = fill;
+                response.long_order_cancel_transaction = cancel;
+            } else {
+                response.short_order_create_transaction = Some(create);
+                response.short_order_fill_transaction = fill;
+                response.short_order_cancel_transaction = cancel;
+            }
+        }
+
+        response.last_transaction_id = book.last_transaction_id();
+
+        Ok(response)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+    use account::instruments::{FinancingDayOfWeek, InstrumentFinancing};
+    use account::instruments::DayOfWeek;
+    use account::orders::{LimitOrderRequest, MarketOrderRequest, StopOrderRequest};
+    use instrument::candlestick_data::CandlestickData;
+    use primitives::{Decimal, PriceValue};
+
+    fn eur_usd() -> InstrumentName {
+        "EUR_USD".parse().unwrap()
+    }
+
+    fn account() -> PaperAccount {
+        let mut account = PaperAccount::new("paper", "USD".parse().unwrap(), "10000".parse().unwrap());
+        account.with_instrument(Instrument::currency_pair(eur_usd()));
+        account
+    }
+
+    /// A candlestick at `hour` on 2017-06-21 with a spread of 2 pips
+    fn candle(hour: u32, o: &str, h: &str, l: &str, c: &str) -> Candlestick {
+        let data = |spread: &str| {
+            let spread: Decimal = spread.parse().unwrap();
+            let price = |p: &str| PriceValue::from(p.parse::<Decimal>().unwrap() + spread);
+
+            CandlestickData { o: price(o), h: price(h), l: price(l), c: price(c) }
+        };
+
+        Candlestick {
+            time: UTC.ymd(2017, 6, 21).and_hms(hour, 0, 0),
+            bid: Some(data("0")),
+            ask: Some(data("0.0002")),
+            mid: None,
+            volume: 100,
+            complete: true
+        }
+    }
+
+    #[test]
+    fn it_fills_limit_orders_when_a_candle_reaches_them() {
+        let account = account();
+        account.update_candle(&eur_usd(), &candle(0, "1.11000", "1.11100", "1.10900", "1.11000"));
+
+        let mut order = LimitOrderRequest::new(eur_usd(), "10000".parse().unwrap(), "1.10500".parse().unwrap());
+        order.with_take_profit("1.11500".parse().unwrap());
+        let created = account.create_order(&CreateOrderRequest { order: order });
+        assert!(created.order_fill_transaction.is_none());
+        assert_eq!(account.details().orders.len(), 1);
+
+        let transactions = account.update_candle(&eur_usd(), &candle(1, "1.10800", "1.10900", "1.10400", "1.10600"));
+        assert_eq!(transactions[0].transaction_type, TransactionType::ORDER_FILL);
+        assert_eq!(transactions[0].price, Some("1.10500".parse().unwrap()));
+        assert_eq!(transactions[0].time, UTC.ymd(2017, 6, 21).and_hms(1, 0, 0));
+        assert_eq!(transactions[1].transaction_type, TransactionType::TAKE_PROFIT_ORDER);
+        assert_eq!(account.summary().unrealized_pl, "10.0000".parse().unwrap());
+
+        // The take profit is reached by a gap, so fills at the better open
+        let transactions = account.update_candle(&eur_usd(), &candle(2, "1.11600", "1.11700", "1.11500", "1.11600"));
+        assert_eq!(transactions[0].price, Some("1.11600".parse().unwrap()));
+        assert_eq!(transactions[0].pl, Some("110.0000".parse().unwrap()));
+
+        let summary = account.summary();
+        assert_eq!(summary.balance, "10110.0000".parse().unwrap());
+        assert_eq!(summary.open_trade_count, 0);
+        assert_eq!(summary.pending_order_count, 0);
+    }
+
+    #[test]
+    fn it_fills_market_orders_and_cancels_orders_out_of_bounds_or_margin() {
+        let account = account();
+        account.update_candle(&eur_usd(), &candle(0, "1.11000", "1.11000", "1.11000", "1.11000"));
+
+        let order = MarketOrderRequest::new(eur_usd(), "-1000".parse().unwrap());
+        let filled = account.create_order(&CreateOrderRequest { order: order });
+        assert_eq!(filled.order_fill_transaction.unwrap().price, Some("1.11000".parse().unwrap()));
+        assert_eq!(account.position(&eur_usd()).short.units, "-1000".parse().unwrap());
+        assert_eq!(account.summary().margin_used, "22.2020".parse().unwrap());
+
+        let order = MarketOrderRequest::new(eur_usd(), "1000000".parse().unwrap());
+        let cancelled = account.create_order(&CreateOrderRequest { order: order });
+        assert_eq!(cancelled.order_cancel_transaction.unwrap().reason, Some("INSUFFICIENT_MARGIN".to_string()));
+
+        let mut order = StopOrderRequest::new(eur_usd(), "1000".parse().unwrap(), "1.11100".parse().unwrap());
+        order.with_price_bound("1.11200".parse().unwrap());
+        account.create_order(&CreateOrderRequest { order: order });
+
+        let transactions = account.update_candle(&eur_usd(), &candle(1, "1.11300", "1.11400", "1.11300", "1.11300"));
+        assert_eq!(transactions[0].reason, Some("BOUNDS_VIOLATION".to_string()));
+        assert!(account.details().orders.is_empty());
+
+        match account.try_cancel_order("1") {
+            Err(Error::Api { status: 404, .. }) => (),
+            result => panic!("expected the order not to exist, got {:?}", result.map(|_| ()))
+        }
+    }
+
+    #[test]
+    fn it_closes_every_trade_at_the_margin_closeout() {
+        let account = account();
+        account.update_candle(&eur_usd(), &candle(0, "1.10000", "1.10000", "1.10000", "1.10000"));
+
+        let order = MarketOrderRequest::new(eur_usd(), "400000".parse().unwrap());
+        account.create_order(&CreateOrderRequest { order: order });
+
+        // The low reaches the closeout at a bid of 1.08606, where the NAV is
+        // half the margin used, but the close recovers from it
+        let transactions = account.update_candle(&eur_usd(), &candle(1, "1.09000", "1.09000", "1.08000", "1.09500"));
+        let types: Vec<_> = transactions.iter().map(|t| t.transaction_type.clone()).collect();
+        assert_eq!(types, vec![
+            TransactionType::MARGIN_CALL_ENTER,
+            TransactionType::MARKET_ORDER,
+            TransactionType::ORDER_FILL,
+            TransactionType::MARGIN_CALL_EXIT
+        ]);
+        assert_eq!(transactions[1].reason, Some("MARGIN_CLOSEOUT".to_string()));
+        assert_eq!(transactions[2].price, Some("1.08606".parse().unwrap()));
+
+        let summary = account.summary();
+        assert_eq!(summary.open_trade_count, 0);
+        assert_eq!(summary.balance, "4344.0000".parse().unwrap());
+    }
+
+    #[test]
+    fn it_closes_out_shorts_at_the_high_ask() {
+        let account = account();
+        account.update_candle(&eur_usd(), &candle(0, "1.10000", "1.10000", "1.10000", "1.10000"));
+
+        let order = MarketOrderRequest::new(eur_usd(), "-400000".parse().unwrap());
+        account.create_order(&CreateOrderRequest { order: order });
+
+        // The high ask of 1.12020 passes the closeout at 1.11387
+        let transactions = account.update_candle(&eur_usd(), &candle(1, "1.10500", "1.12000", "1.10000", "1.10500"));
+        assert_eq!(transactions[1].reason, Some("MARGIN_CLOSEOUT".to_string()));
+        assert_eq!(transactions[2].price, Some("1.11387".parse().unwrap()));
+        assert_eq!(account.summary().balance, "4452.0000".parse().unwrap());
+    }
+
+    #[test]
+    fn it_fills_the_stop_loss_when_a_candle_reaches_the_take_profit_too() {
+        let account = account();
+        account.update_candle(&eur_usd(), &candle(0, "1.10000", "1.10000", "1.10000", "1.10000"));
+
+        let mut order = MarketOrderRequest::new(eur_usd(), "10000".parse().unwrap());
+        order.with_take_profit("1.10500".parse().unwrap()).with_stop_loss("1.09500".parse().unwrap());
+        account.create_order(&CreateOrderRequest { order: order });
+
+        let transactions = account.update_candle(&eur_usd(), &candle(1, "1.10000", "1.10600", "1.09400", "1.10000"));
+        assert_eq!(transactions[0].reason, Some("STOP_LOSS_ORDER".to_string()));
+        assert_eq!(transactions[0].price, Some("1.09500".parse().unwrap()));
+        assert_eq!(transactions[1].reason, Some("LINKED_TRADE_CLOSED".to_string()));
+        assert_eq!(account.summary().pending_order_count, 0);
+    }
+
+    #[test]
+    fn it_charges_the_usual_financing_days_when_oanda_lists_none() {
+        let mut instrument = Instrument::currency_pair(eur_usd());
+        instrument.financing = Some(InstrumentFinancing {
+            long_rate: "-0.0365".parse().unwrap(),
+            short_rate: "0.01".parse().unwrap(),
+            financing_days_of_week: Vec::new()
+        });
+        let mut account = account();
+        account.with_instrument(instrument);
+        account.update_candle(&eur_usd(), &candle(12, "1.00000", "1.00000", "1.00000", "1.00000"));
+
+        let order = MarketOrderRequest::new(eur_usd(), "10000".parse().unwrap());
+        account.create_order(&CreateOrderRequest { order: order });
+
+        let wednesday = account.update_candle(&eur_usd(), &candle(22, "1.00000", "1.00000", "1.00000", "1.00000"));
+        assert_eq!(wednesday[0].financing, Some("-3.0003".parse().unwrap()));
+
+        let thursday = UTC.ymd(2017, 6, 22).and_hms(22, 0, 0);
+        let rollover = Candlestick { time: thursday, ..candle(22, "1.00000", "1.00000", "1.00000", "1.00000") };
+        assert_eq!(account.update_candle(&eur_usd(), &rollover)[0].financing, Some("-1.0001".parse().unwrap()));
+    }
+
+    #[test]
+    fn it_rejects_cross_pair_orders_until_it_can_convert_them() {
+        let eur_jpy: InstrumentName = "EUR_JPY".parse().unwrap();
+        let usd_jpy: InstrumentName = "USD_JPY".parse().unwrap();
+        let mut account = account();
+        account.with_instrument(Instrument::currency_pair(eur_jpy.clone()));
+        account.update_candle(&eur_jpy, &candle(0, "125.0000", "125.0000", "125.0000", "125.0000"));
+
+        let order = MarketOrderRequest::new(eur_jpy.clone(), "10000".parse().unwrap());
+        match account.try_create_order(&CreateOrderRequest { order: order.clone() }) {
+            Err(Error::Api { status: 400, ref message }) => assert_eq!(message, "There is no price to convert JPY into USD"),
+            result => panic!("expected the order to be rejected, got {:?}", result.map(|_| ()))
+        }
+        assert!(account.transactions().is_empty());
+
+        account.update_candle(&usd_jpy, &candle(0, "110.0000", "110.0000", "110.0000", "110.0000"));
+        let created = account.create_order(&CreateOrderRequest { order: order });
+        assert_eq!(created.order_fill_transaction.unwrap().price, Some("125.0002".parse().unwrap()));
+
+        // Yen P/L is converted at the USD_JPY mid of 110.0001
+        account.update_candle(&eur_jpy, &candle(1, "126.1000", "126.1000", "126.1000", "126.1000"));
+        assert_eq!(account.summary().unrealized_pl, "99.9817".parse().unwrap());
+    }
+
+    #[test]
+    fn it_charges_financing_at_the_rollover() {
+        let mut instrument = Instrument::currency_pair(eur_usd());
+        instrument.financing = Some(InstrumentFinancing {
+            long_rate: "-0.0365".parse().unwrap(),
+            short_rate: "0.01".parse().unwrap(),
+            financing_days_of_week: vec![FinancingDayOfWeek { day_of_week: DayOfWeek::WEDNESDAY, days_charged: 3 }]
+        });
+        let mut account = account();
+        account.with_instrument(instrument);
+        account.update_candle(&eur_usd(), &candle(12, "1.00000", "1.00000", "1.00000", "1.00000"));
+
+        let order = MarketOrderRequest::new(eur_usd(), "10000".parse().unwrap());
+        account.create_order(&CreateOrderRequest { order: order });
+
+        // 2017-06-21 is a Wednesday, and 5pm New York is 21:00 UTC
+        assert!(account.update_candle(&eur_usd(), &candle(20, "1.00000", "1.00000", "1.00000", "1.00000")).is_empty());
+        let transactions = account.update_candle(&eur_usd(), &candle(22, "1.00000", "1.00000", "1.00000", "1.00000"));
+
+        assert_eq!(transactions.len(), 1);
+        assert_eq!(transactions[0].transaction_type, TransactionType::DAILY_FINANCING);
+        assert_eq!(transactions[0].time, UTC.ymd(2017, 6, 21).and_hms(21, 0, 0));
+        assert_eq!(transactions[0].financing, Some("-3.0003".parse().unwrap()));
+        assert_eq!(account.details().trades[0].financing, "-3.0003".parse().unwrap());
+
+        // Thursday is not a financing day of the instrument
+        let thursday = UTC.ymd(2017, 6, 22).and_hms(22, 0, 0);
+        let rollover = Candlestick { time: thursday, ..candle(22, "1.00000", "1.00000", "1.00000", "1.00000") };
+        assert!(account.update_candle(&eur_usd(), &rollover).is_empty());
+    }
+}
@@ -0,0 +1,421 @@
+use std::borrow::Borrow;
+use std::error;
+use std::fmt;
+use std::hash::{Hash, Hasher};
+use std::ops::{Add, Neg, Sub};
+use std::str::{self, FromStr};
+
+use serde::{de, Deserialize, Deserializer, Serialize, Serializer};
+
+use rust_decimal::prelude::ToPrimitive;
+
+pub use rust_decimal::{Decimal, RoundingStrategy};
+
+/// Defines a newtype around `Decimal` that Oanda sends and expects as a
+/// decimal string, so no precision is lost in either direction
+macro_rules! decimal_primitive {
+    ($(#[$attr:meta])* $name:ident) => {
+        $(#[$attr])*
+        #[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+        pub struct $name(pub Decimal);
+
+        impl $name {
+            /// The underlying decimal value
+            pub fn value(&self) -> Decimal {
+                self.0
+            }
+
+            /// The closest floating point value, for calculations that do
+            /// not need to be exact
+            pub fn to_f64(&self) -> f64 {
+                self.0.to_f64().expect("every decimal has a floating point approximation")
+            }
+        }
+
+        impl From<Decimal> for $name {
+            fn from(value: Decimal) -> $name {
+                $name(value)
+            }
+        }
+
+        impl From<$name> for Decimal {
+            fn from(value: $name) -> Decimal {
+                value.0
+            }
+        }
+
+        impl FromStr for $name {
+            type Err = rust_decimal::Error;
+
+            fn from_str(s: &str) -> Result<$name, rust_decimal::Error> {
+                Decimal::from_str(s).map($name)
+            }
+        }
+
+        impl fmt::Display for $name {
+            fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                fmt::Display::fmt(&self.0, f)
+            }
+        }
+
+        impl Add for $name {
+            type Output = $name;
+
+            fn add(self, other: $name) -> $name {
+                $name(self.0 + other.0)
+            }
+        }
+
+        impl Sub for $name {
+            type Output = $name;
+
+            fn sub(self, other: $name) -> $name {
+                $name(self.0 - other.0)
+            }
+        }
+
+        impl Neg for $name {
+            type Output = $name;
+
+            fn neg(self) -> $name {
+                $name(-self.0)
+            }
+        }
+
+        impl Serialize for $name {
+            fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+                where S: Serializer
+            {
+                serializer.serialize_str(&self.0.to_string())
+            }
+        }
+
+        impl Deserialize for $name {
+            fn deserialize<D>(deserializer: D) -> Result<$name, D::Error>
+                where D: Deserializer
+            {
+                deserializer.deserialize(DecimalVisitor).map($name)
+            }
+        }
+    }
+}
+
+decimal_primitive! {
+    /// The string representation of a Price for an Instrument, e.g. “1.10345”
+    /// or “112.052”.
+    PriceValue
+}
+
+decimal_primitive! {
+    /// The string representation of a decimal number, used for units, sizes,
+    /// distances and rates.
+    DecimalNumber
+}
+
+decimal_primitive! {
+    /// The string representation of a quantity of an Account’s home currency.
+    AccountUnits
+}
+
+/// Reads a decimal from a string, or from a JSON number for data that was not
+/// written by Oanda
+struct DecimalVisitor;
+
+impl de::Visitor for DecimalVisitor {
+    type Value = Decimal;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        write!(formatter, "a decimal number or a string containing one")
+    }
+
+    fn visit_str<E>(self, value: &str) -> Result<Decimal, E>
+        where E: de::Error
+    {
+        Decimal::from_str(value).map_err(|err| E::custom(err.to_string()))
+    }
+
+    fn visit_i64<E>(self, value: i64) -> Result<Decimal, E>
+        where E: de::Error
+    {
+        Ok(Decimal::from(value))
+    }
+
+    fn visit_u64<E>(self, value: u64) -> Result<Decimal, E>
+        where E: de::Error
+    {
+        Ok(Decimal::from(value))
+    }
+
+    fn visit_f64<E>(self, value: f64) -> Result<Decimal, E>
+        where E: de::Error
+    {
+        // the shortest representation that round trips back to `value`
+        self.visit_str(&value.to_string())
+    }
+}
+
+/// Every currency code in ISO 4217, including the precious metals
+const ISO_4217: &'static [&'static str] = &[
+    "AED", "AFN", "ALL", "AMD", "ANG", "AOA", "ARS", "AUD", "AWG", "AZN",
+    "BAM", "BBD", "BDT", "BGN", "BHD", "BIF", "BMD", "BND", "BOB", "BOV",
+    "BRL", "BSD", "BTN", "BWP", "BYN", "BZD", "CAD", "CDF", "CHE", "CHF",
+    "CHW", "CLF", "CLP", "CNH", "CNY", "COP", "COU", "CRC", "CUC", "CUP",
+    "CVE", "CZK", "DJF", "DKK", "DOP", "DZD", "EGP", "ERN", "ETB", "EUR",
+    "FJD", "FKP", "GBP", "GEL", "GHS", "GIP", "GMD", "GNF", "GTQ", "GYD",
+    "HKD", "HNL", "HRK", "HTG", "HUF", "IDR", "ILS", "INR", "IQD", "IRR",
+    "ISK", "JMD", "JOD", "JPY", "KES", "KGS", "KHR", "KMF", "KPW", "KRW",
+    "KWD", "KYD", "KZT", "LAK", "LBP", "LKR", "LRD", "LSL", "LYD", "MAD",
+    "MDL", "MGA", "MKD", "MMK", "MNT", "MOP", "MRU", "MUR", "MVR", "MWK",
+    "MXN", "MXV", "MYR", "MZN", "NAD", "NGN", "NIO", "NOK", "NPR", "NZD",
+    "OMR", "PAB", "PEN", "PGK", "PHP", "PKR", "PLN", "PYG", "QAR", "RON",
+    "RSD", "RUB", "RWF", "SAR", "SBD", "SCR", "SDG", "SEK", "SGD", "SHP",
+    "SLE", "SLL", "SOS", "SRD", "SSP", "STN", "SVC", "SYP", "SZL", "THB",
+    "TJS", "TMT", "TND", "TOP", "TRY", "TTD", "TWD", "TZS", "UAH", "UGX",
+    "USD", "USN", "UYI", "UYU", "UYW", "UZS", "VED", "VES", "VND", "VUV",
+    "WST", "XAF", "XAG", "XAU", "XBA", "XBB", "XBC", "XBD", "XCD", "XDR",
+    "XOF", "XPD", "XPF", "XPT", "XSU", "XUA", "YER", "ZAR", "ZMW", "ZWL"
+];
+
+/// A value could not be parsed into one of the primitive types
+#[derive(Debug, PartialEq)]
+pub struct ParseError {
+    /// The type that was being parsed
+    pub kind: &'static str,
+    /// The value that was rejected
+    pub value: String
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "invalid {}: {:?}", self.kind, self.value)
+    }
+}
+
+impl error::Error for ParseError {}
+
+/// Currency name identifier. Used by clients to refer to currencies.
+/// A string containing an ISO 4217 currency, e.g. “USD”.
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Currency([u8; 3]);
+
+impl Currency {
+    pub fn as_str(&self) -> &str {
+        // only ever built from one of the ASCII codes in ISO_4217
+        str::from_utf8(&self.0).unwrap()
+    }
+}
+
+impl FromStr for Currency {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Currency, ParseError> {
+        if ISO_4217.binary_search(&s).is_err() {
+            return Err(ParseError { kind: "currency", value: s.to_string() })
+        }
+
+        let bytes = s.as_bytes();
+
+        Ok(Currency([bytes[0], bytes[1], bytes[2]]))
+    }
+}
+
+impl fmt::Display for Currency {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl fmt::Debug for Currency {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Currency({})", self.as_str())
+    }
+}
+
+/// Instrument name identifier. Used by clients to refer to an Instrument.
+/// A string containing the base currency and quote currency delimited by a
+/// “_”, e.g. “EUR_USD”. The base of a CFD is not a currency, e.g.
+/// “SPX500_USD”.
+#[derive(Clone)]
+pub struct InstrumentName {
+    /// The full name
+    name: String,
+    /// The position of the “_” in `name`
+    split: usize
+}
+
+impl InstrumentName {
+    pub fn as_str(&self) -> &str {
+        &self.name
+    }
+
+    /// The part of the name before the “_”, a currency for currency pairs
+    /// and metals or the underlying for CFDs, e.g. “SPX500”
+    pub fn base(&self) -> &str {
+        &self.name[..self.split]
+    }
+
+    /// The base currency, only present if the base is a currency
+    pub fn base_currency(&self) -> Option<Currency> {
+        self.base().parse().ok()
+    }
+
+    /// The currency the Instrument is priced in
+    pub fn quote_currency(&self) -> Currency {
+        self.name[self.split + 1..].parse().expect("the quote currency was checked when parsing")
+    }
+}
+
+impl FromStr for InstrumentName {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<InstrumentName, ParseError> {
+        let error = || ParseError { kind: "instrument name", value: s.to_string() };
+        let split = s.find('_').ok_or_else(error)?;
+        let (base, quote) = (&s[..split], &s[split + 1..]);
+        let valid_base = !base.is_empty() &&
+            base.chars().all(|c| c.is_ascii_uppercase() || c.is_ascii_digit());
+
+        if !valid_base || quote.parse::<Currency>().is_err() {
+            return Err(error())
+        }
+
+        Ok(InstrumentName { name: s.to_string(), split: split })
+    }
+}
+
+impl fmt::Display for InstrumentName {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(&self.name)
+    }
+}
+
+impl fmt::Debug for InstrumentName {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "InstrumentName({})", self.name)
+    }
+}
+
+impl PartialEq for InstrumentName {
+    fn eq(&self, other: &InstrumentName) -> bool {
+        self.name == other.name
+    }
+}
+
+impl Eq for InstrumentName {}
+
+impl<'a> PartialEq<&'a str> for InstrumentName {
+    fn eq(&self, other: &&'a str) -> bool {
+        self.name == *other
+    }
+}
+
+impl PartialEq<str> for InstrumentName {
+    fn eq(&self, other: &str) -> bool {
+        self.name == other
+    }
+}
+
+impl PartialOrd for InstrumentName {
+    fn partial_cmp(&self, other: &InstrumentName) -> Option<::std::cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for InstrumentName {
+    fn cmp(&self, other: &InstrumentName) -> ::std::cmp::Ordering {
+        self.name.cmp(&other.name)
+    }
+}
+
+// hashed as the name alone so maps keyed by InstrumentName can be read by &str
+impl Hash for InstrumentName {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.name.hash(state)
+    }
+}
+
+impl Borrow<str> for InstrumentName {
+    fn borrow(&self) -> &str {
+        &self.name
+    }
+}
+
+/// Defines serde support for a primitive written as a string and read with
+/// its `FromStr` implementation
+macro_rules! string_primitive {
+    ($name:ident) => {
+        impl Serialize for $name {
+            fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+                where S: Serializer
+            {
+                serializer.serialize_str(self.as_str())
+            }
+        }
+
+        impl Deserialize for $name {
+            fn deserialize<D>(deserializer: D) -> Result<$name, D::Error>
+                where D: Deserializer
+            {
+                let value = String::deserialize(deserializer)?;
+
+                value.parse().map_err(|err: ParseError| de::Error::custom(err.to_string()))
+            }
+        }
+    }
+}
+
+string_primitive!(Currency);
+string_primitive!(InstrumentName);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json;
+
+    #[test]
+    fn it_round_trips_decimal_strings() {
+        let price: PriceValue = serde_json::from_str("\"112.05200\"").unwrap();
+        let balance: AccountUnits = serde_json::from_str("\"123456789.0123\"").unwrap();
+
+        assert_eq!(price.to_string(), "112.05200");
+        assert_eq!(serde_json::to_string(&price).unwrap(), "\"112.05200\"");
+        assert_eq!(serde_json::to_string(&balance).unwrap(), "\"123456789.0123\"");
+        assert_eq!(balance + AccountUnits::from_str("0.0077").unwrap(), AccountUnits::from_str("123456789.02").unwrap());
+    }
+
+    #[test]
+    fn it_reads_json_numbers() {
+        let units: DecimalNumber = serde_json::from_str("100").unwrap();
+        let rate: DecimalNumber = serde_json::from_str("0.02").unwrap();
+
+        assert_eq!(units.to_string(), "100");
+        assert_eq!(rate.to_string(), "0.02");
+        assert!(serde_json::from_str::<DecimalNumber>("\"abc\"").is_err());
+    }
+
+    #[test]
+    fn it_parses_currencies() {
+        let usd: Currency = "USD".parse().unwrap();
+
+        assert_eq!(usd.to_string(), "USD");
+        assert!("usd".parse::<Currency>().is_err());
+        assert!("ABC".parse::<Currency>().is_err());
+        assert!("USDX".parse::<Currency>().is_err());
+    }
+
+    #[test]
+    fn it_parses_instrument_names() {
+        let eur_usd: InstrumentName = "EUR_USD".parse().unwrap();
+        let spx: InstrumentName = serde_json::from_str("\"SPX500_USD\"").unwrap();
+
+        assert_eq!(eur_usd.base_currency(), Some("EUR".parse().unwrap()));
+        assert_eq!(eur_usd.quote_currency(), "USD".parse().unwrap());
+        assert_eq!(spx.base(), "SPX500");
+        assert_eq!(spx.base_currency(), None);
+        assert_eq!(serde_json::to_string(&spx).unwrap(), "\"SPX500_USD\"");
+
+        for name in &["EURUSD", "EUR_", "_USD", "eur_usd", "EUR_USD_JPY", "EUR_ABC"] {
+            assert!(name.parse::<InstrumentName>().is_err(), "{} is not valid", name);
+        }
+    }
+}
@@ -0,0 +1,149 @@
+use std::collections::BTreeMap;
+
+use chrono::datetime::DateTime;
+use chrono::UTC;
+
+use instrument::alignment::Alignment;
+use instrument::candlestick::Candlestick;
+use instrument::candlestick_data::CandlestickData;
+use instrument::candlestick_granularity::CandlestickGranularity;
+use instrument::price::ClientPrice;
+use instrument::resample::Resampler;
+use primitives::{Decimal, InstrumentName, PriceValue};
+
+/// Builds candlesticks with bid, ask and mid prices out of a stream of
+/// Prices. Each candlestick's volume is the number of Prices in it.
+///
+/// Candlesticks are aligned like those Oanda serves. Monthly candlesticks are
+/// not supported, they are built 31 days long.
+pub struct CandleBuilder {
+    resampler: Resampler,
+    /// The candlestick being built for each instrument and when it ends
+    building: BTreeMap<InstrumentName, (DateTime<UTC>, Candlestick)>
+}
+
+impl CandleBuilder {
+    pub fn new(granularity: CandlestickGranularity) -> CandleBuilder {
+        CandleBuilder {
+            // every granularity is a whole number of 5 seconds, and of days
+            // once it is a day or longer
+            resampler: Resampler::new(CandlestickGranularity::S5, granularity.duration())
+                .expect("granularities can be resampled from S5"),
+            building: BTreeMap::new()
+        }
+    }
+
+    pub fn with_alignment(&mut self, alignment: Alignment) -> &mut CandleBuilder {
+        self.resampler.with_alignment(alignment);
+        self
+    }
+
+    /// Add a Price to the candlestick of its instrument. Returns the previous
+    /// candlestick of the instrument if the Price is past its end.
+    pub fn push(&mut self, price: &ClientPrice) -> Option<Candlestick> {
+        let bid = price.bids.first().map_or(price.closeout_bid, |b| b.price);
+        let ask = price.asks.first().map_or(price.closeout_ask, |a| a.price);
+        let mid = PriceValue::from((bid.value() + ask.value()) / Decimal::from(2));
+
+        let closed = match self.building.get(&price.instrument) {
+            Some(&(end, _)) if price.time >= end => self.building.remove(&price.instrument).map(|(_, candle)| candle),
+            _ => None
+        };
+
+        match self.building.get_mut(&price.instrument) {
+            Some(&mut (_, ref mut candle)) => {
+                extend(&mut candle.bid, bid);
+                extend(&mut candle.ask, ask);
+                extend(&mut candle.mid, mid);
+                candle.volume += 1;
+            },
+            None => {
+                let (start, end) = self.resampler.bucket(price.time);
+                let candle = Candlestick {
+                    time: start,
+                    bid: Some(CandlestickData { o: bid, h: bid, l: bid, c: bid }),
+                    ask: Some(CandlestickData { o: ask, h: ask, l: ask, c: ask }),
+                    mid: Some(CandlestickData { o: mid, h: mid, l: mid, c: mid }),
+                    volume: 1,
+                    complete: false
+                };
+
+                self.building.insert(price.instrument.clone(), (end, candle));
+            }
+        }
+
+        closed.map(complete)
+    }
+
+    /// Close every candlestick that ends at or before `time`, e.g. when no
+    /// Price has arrived since it ended
+    pub fn close(&mut self, time: DateTime<UTC>) -> Vec<(InstrumentName, Candlestick)> {
+        let ended: Vec<_> = self.building.iter()
+            .filter(|&(_, &(end, _))| end <= time)
+            .map(|(instrument, _)| instrument.clone())
+            .collect();
+
+        ended.into_iter()
+            .filter_map(|instrument| {
+                self.building.remove(&instrument).map(|(_, candle)| (instrument, complete(candle)))
+            })
+            .collect()
+    }
+
+    /// When the first candlestick being built ends
+    pub fn next_close(&self) -> Option<DateTime<UTC>> {
+        self.building.values().map(|&(end, _)| end).min()
+    }
+}
+
+fn extend(data: &mut Option<CandlestickData>, price: PriceValue) {
+    if let Some(ref mut data) = *data {
+        data.h = data.h.max(price);
+        data.l = data.l.min(price);
+        data.c = price;
+    }
+}
+
+fn complete(candle: Candlestick) -> Candlestick {
+    Candlestick { complete: true, ..candle }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+    use serde_json;
+
+    fn price(minute: u32, second: u32, bid: &str, ask: &str) -> ClientPrice {
+        serde_json::from_str(&format!(
+            r#"{{"instrument": "EUR_USD", "time": "{}", "tradeable": true,
+                "bids": [{{"price": "{}", "liquidity": 1000000}}], "asks": [{{"price": "{}", "liquidity": 1000000}}],
+                "closeoutBid": "{}", "closeoutAsk": "{}"}}"#,
+            UTC.ymd(2017, 6, 21).and_hms(12, minute, second).to_rfc3339(), bid, ask, bid, ask
+        )).unwrap()
+    }
+
+    #[test]
+    fn it_builds_candles_from_prices() {
+        let mut builder = CandleBuilder::new(CandlestickGranularity::M1);
+
+        assert!(builder.push(&price(0, 5, "1.11000", "1.11020")).is_none());
+        assert!(builder.push(&price(0, 20, "1.11050", "1.11070")).is_none());
+        assert!(builder.push(&price(0, 40, "1.10980", "1.11000")).is_none());
+        assert_eq!(builder.next_close(), Some(UTC.ymd(2017, 6, 21).and_hms(12, 1, 0)));
+
+        let candle = builder.push(&price(1, 2, "1.11010", "1.11030")).unwrap();
+        assert_eq!(candle.time, UTC.ymd(2017, 6, 21).and_hms(12, 0, 0));
+        assert_eq!(candle.bid.unwrap().h, "1.11050".parse().unwrap());
+        assert_eq!(candle.ask.unwrap().l, "1.11000".parse().unwrap());
+        assert_eq!(candle.mid.unwrap().c, "1.10990".parse().unwrap());
+        assert_eq!(candle.volume, 3);
+        assert!(candle.complete);
+
+        assert!(builder.close(UTC.ymd(2017, 6, 21).and_hms(12, 1, 59)).is_empty());
+        let closed = builder.close(UTC.ymd(2017, 6, 21).and_hms(12, 2, 0));
+        assert_eq!(closed.len(), 1);
+        assert_eq!(closed[0].1.time, UTC.ymd(2017, 6, 21).and_hms(12, 1, 0));
+        assert_eq!(builder.next_close(), None);
+    }
+}
@@ -0,0 +1,172 @@
+use std::cell::RefCell;
+use std::collections::HashMap;
+
+use account::Account;
+use account::details::{Details, Position, PositionSide};
+use account::orders::{CancelOrderResponse, CreateOrderRequest, CreateOrderResponse, OrderRequest};
+use account::positions::{ClosePositionRequest, ClosePositionResponse};
+use account::summary::Summary;
+use account::trades::{CloseTradeRequest, CloseTradeResponse};
+use account::trading::TradingAccount;
+use account::transactions::Transaction;
+use error::Result;
+use instrument::price::ClientPrice;
+use primitives::{AccountUnits, DecimalNumber, InstrumentName, PriceValue};
+
+/// A copy of an Account's details that a `Runtime` keeps up to date, so a
+/// strategy can read them without a request to Oanda.
+///
+/// The details are reloaded after every Transaction and every Order sent
+/// through the mirror. In between, open Trades and the NAV are revalued with
+/// each Price, while the margin stays as of the last reload.
+pub struct AccountMirror<'a> {
+    account: &'a Account<'a>,
+    details: RefCell<Details>,
+    prices: RefCell<HashMap<InstrumentName, ClientPrice>>
+}
+
+impl<'a> AccountMirror<'a> {
+    pub fn new(account: &'a Account<'a>) -> Result<AccountMirror<'a>> {
+        Ok(AccountMirror {
+            account: account,
+            details: RefCell::new(account.try_details()?),
+            prices: RefCell::new(HashMap::new())
+        })
+    }
+
+    /// The latest Price of `instrument`
+    pub fn price(&self, instrument: &InstrumentName) -> Option<ClientPrice> {
+        self.prices.borrow().get(instrument).cloned()
+    }
+
+    /// Load the details from Oanda again
+    pub fn reload(&self) -> Result<()> {
+        let mut details = self.account.try_details()?;
+
+        for price in self.prices.borrow().values() {
+            revalue(&mut details, price);
+        }
+        *self.details.borrow_mut() = details;
+
+        Ok(())
+    }
+
+    /// Value the open Trades of the Price's instrument at it
+    pub fn update_price(&self, price: &ClientPrice) {
+        revalue(&mut self.details.borrow_mut(), price);
+        self.prices.borrow_mut().insert(price.instrument.clone(), price.clone());
+    }
+
+    /// Reload the details unless they already include `transaction`
+    pub fn update_transaction(&self, transaction: &Transaction) -> Result<()> {
+        let loaded = self.details.borrow().last_transaction_id.parse::<u64>().unwrap_or(0);
+
+        match transaction.id.parse::<u64>() {
+            Ok(id) if id <= loaded => Ok(()),
+            _ => self.reload()
+        }
+    }
+
+    /// Reload the details once `result` succeeded, as it changed the Account
+    fn reloaded<T>(&self, result: Result<T>) -> Result<T> {
+        let value = result?;
+        self.reload()?;
+
+        Ok(value)
+    }
+}
+
+/// Revalue the open Trades of the Price's instrument, and the positions and
+/// NAV with them. Trades whose profit can not be converted into the
+/// Account's currency keep their value.
+fn revalue(details: &mut Details, price: &ClientPrice) {
+    let currency = details.currency;
+    let mut change = AccountUnits::default();
+
+    for trade in details.trades.iter_mut().filter(|t| t.instrument == price.instrument) {
+        let pl = price.unrealized_pl(trade.current_units, trade.price);
+        let pl = match price.quote_home_conversion_factors {
+            Some(ref factors) => factors.to_home(pl),
+            None if trade.instrument.quote_currency() == currency => pl,
+            None => continue
+        };
+        let pl = AccountUnits::from(pl.value().round_dp(4));
+
+        change = change + pl - trade.unrealized_pl;
+        trade.unrealized_pl = pl;
+    }
+
+    let trades = &details.trades;
+    for position in details.positions.iter_mut().filter(|p| p.instrument == price.instrument) {
+        for side in vec![&mut position.long, &mut position.short] {
+            side.unrealized_pl = trades.iter()
+                .filter(|t| side.trade_ids.contains(&t.id))
+                .fold(AccountUnits::default(), |pl, t| pl + t.unrealized_pl);
+        }
+        position.unrealized_pl = position.long.unrealized_pl + position.short.unrealized_pl;
+    }
+
+    details.unrealized_pl = details.unrealized_pl + change;
+    details.nav = details.nav + change;
+    details.margin_closeout_unrealized_pl = details.unrealized_pl;
+    details.margin_closeout_nav = details.nav;
+}
+
+impl<'a> TradingAccount for AccountMirror<'a> {
+    fn id(&self) -> &str {
+        &self.account.id
+    }
+
+    fn try_summary(&self) -> Result<Summary> {
+        Ok(self.details.borrow().summary())
+    }
+
+    fn try_details(&self) -> Result<Details> {
+        Ok(self.details.borrow().clone())
+    }
+
+    /// The Position for `instrument`, with no units if it was never traded
+    fn try_position(&self, instrument: &InstrumentName) -> Result<Position> {
+        let position = self.details.borrow().positions.iter().find(|p| p.instrument == *instrument).cloned();
+
+        Ok(position.unwrap_or_else(|| {
+            let side = PositionSide {
+                units: DecimalNumber::default(),
+                average_price: PriceValue::default(),
+                trade_ids: Vec::new(),
+                pl: AccountUnits::default(),
+                unrealized_pl: AccountUnits::default(),
+                resettable_pl: AccountUnits::default()
+            };
+
+            Position {
+                instrument: instrument.clone(),
+                pl: AccountUnits::default(),
+                unrealized_pl: AccountUnits::default(),
+                resettable_pl: AccountUnits::default(),
+                long: side.clone(),
+                short: side
+            }
+        }))
+    }
+
+    fn try_create_order<O: OrderRequest>(&self, request: &CreateOrderRequest<O>) -> Result<CreateOrderResponse> {
+        self.reloaded(self.account.try_create_order(request))
+    }
+
+    fn try_cancel_order(&self, order_id: &str) -> Result<CancelOrderResponse> {
+        self.reloaded(self.account.try_cancel_order(order_id))
+    }
+
+    fn try_close_trade(&self, trade_id: &str, request: &CloseTradeRequest) -> Result<CloseTradeResponse> {
+        self.reloaded(self.account.try_close_trade(trade_id, request))
+    }
+
+    fn try_close_position(
+        &self,
+        instrument: &InstrumentName,
+        request: &ClosePositionRequest
+    ) -> Result<ClosePositionResponse> {
+        self.reloaded(self.account.try_close_position(instrument, request))
+    }
+}
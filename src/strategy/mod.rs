@@ -0,0 +1,65 @@
+//! Trading strategies that run unchanged in a backtest and live.
+//!
+//! A `Strategy` reacts to prices, closed candlesticks, Transactions and timer
+//! ticks, and trades through the `TradingAccount` it is handed. A `Backtest`
+//! hands it a `PaperAccount`. A `Runtime` hands it an `AccountMirror`, which
+//! answers from a copy of the Account kept up to date by the pricing and
+//! transaction streams and sends Orders to Oanda:
+//!
+//! ```no_run
+//! # use oandars::account::trading::TradingAccount;
+//! # use oandars::client::Client;
+//! # use oandars::instrument::candlestick::Candlestick;
+//! # use oandars::instrument::candlestick_granularity::CandlestickGranularity;
+//! # use oandars::primitives::InstrumentName;
+//! # use oandars::strategy::{Runtime, Strategy};
+//! struct Logger;
+//!
+//! impl Strategy for Logger {
+//!     fn on_candle_close<A: TradingAccount>(&mut self, account: &A, instrument: &InstrumentName, candle: &Candlestick) {
+//!         println!("{} {:?} NAV {}", instrument, candle.mid, account.try_summary().unwrap().nav);
+//!     }
+//! }
+//!
+//! let client = Client::new("https://api-fxpractice.oanda.com/v3", "key");
+//! let accounts = client.accounts();
+//! let mut runtime = Runtime::new(&accounts[0], Logger, vec!["EUR_USD".parse().unwrap()]).unwrap();
+//! runtime.with_candles(CandlestickGranularity::M1);
+//! runtime.run().unwrap();
+//! ```
+
+mod candles;
+mod mirror;
+mod runtime;
+
+use chrono::datetime::DateTime;
+use chrono::UTC;
+
+use account::trading::TradingAccount;
+use account::transactions::Transaction;
+use instrument::candlestick::Candlestick;
+use instrument::price::ClientPrice;
+use primitives::InstrumentName;
+
+pub use self::candles::CandleBuilder;
+pub use self::mirror::AccountMirror;
+pub use self::runtime::Runtime;
+
+/// A trading strategy. Every callback does nothing unless implemented.
+#[allow(unused_variables)]
+pub trait Strategy {
+    /// Called with every Price of the instruments traded. Backtests replay
+    /// candlesticks, so only call this live.
+    fn on_price<A: TradingAccount>(&mut self, account: &A, price: &ClientPrice) {}
+
+    /// Called when a candlestick of `instrument` closes, after the Orders it
+    /// reached were filled
+    fn on_candle_close<A: TradingAccount>(&mut self, account: &A, instrument: &InstrumentName, candle: &Candlestick) {}
+
+    /// Called with every Transaction of the Account, including those created
+    /// by the strategy's own Orders
+    fn on_transaction<A: TradingAccount>(&mut self, account: &A, transaction: &Transaction) {}
+
+    /// Called every time the timer interval passes, with the time it was due
+    fn on_timer<A: TradingAccount>(&mut self, account: &A, time: DateTime<UTC>) {}
+}
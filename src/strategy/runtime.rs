@@ -0,0 +1,297 @@
+use std::io;
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError, Sender};
+use std::thread;
+use std::time;
+
+use chrono::datetime::DateTime;
+use chrono::{Duration, UTC};
+
+use account::Account;
+use account::transactions::Transaction;
+use error::{Error, Result};
+use instrument::candlestick_granularity::CandlestickGranularity;
+use instrument::price::ClientPrice;
+use primitives::InstrumentName;
+use stream::{PriceMessage, TransactionMessage};
+use super::{AccountMirror, CandleBuilder, Strategy};
+
+/// What the stream threads send to the runtime
+enum Event {
+    Price(ClientPrice),
+    /// A price stream heartbeat, with Oanda's time
+    Heartbeat(DateTime<UTC>),
+    Transaction(Transaction)
+}
+
+/// Runs a strategy live on an Account.
+///
+/// The runtime streams the Prices of its instruments and the Account's
+/// Transactions, keeps an `AccountMirror` of the Account up to date with
+/// them and calls the strategy with each one. Orders the strategy creates on
+/// the mirror are sent to Oanda. Candlesticks are built from the Prices and
+/// closed by the first Price or heartbeat after their end.
+pub struct Runtime<'a, S> {
+    account: &'a Account<'a>,
+    mirror: AccountMirror<'a>,
+    strategy: S,
+    instruments: Vec<InstrumentName>,
+    candles: Option<CandleBuilder>,
+    /// The timer interval and when the timer is next due
+    timer: Option<(Duration, DateTime<UTC>)>
+}
+
+impl<'a, S: Strategy> Runtime<'a, S> {
+    /// A runtime for `strategy` trading `instruments` on `account`. Loads the
+    /// Account's details.
+    pub fn new(account: &'a Account<'a>, strategy: S, instruments: Vec<InstrumentName>) -> Result<Runtime<'a, S>> {
+        Ok(Runtime {
+            account: account,
+            mirror: AccountMirror::new(account)?,
+            strategy: strategy,
+            instruments: instruments,
+            candles: None,
+            timer: None
+        })
+    }
+
+    /// Build candlesticks of `granularity` from the Prices, and call
+    /// `Strategy::on_candle_close` as each one closes
+    pub fn with_candles(&mut self, granularity: CandlestickGranularity) -> &mut Runtime<'a, S> {
+        self.candles = Some(CandleBuilder::new(granularity));
+        self
+    }
+
+    /// Call `Strategy::on_timer` every `interval`, starting `interval` from
+    /// now
+    pub fn with_timer(&mut self, interval: Duration) -> &mut Runtime<'a, S> {
+        self.timer = Some((interval, UTC::now() + interval));
+        self
+    }
+
+    pub fn mirror(&self) -> &AccountMirror<'a> {
+        &self.mirror
+    }
+
+    pub fn strategy(&self) -> &S {
+        &self.strategy
+    }
+
+    /// Run the strategy until a stream fails or ends, or the mirror can not be
+    /// reloaded
+    pub fn run(&mut self) -> Result<()> {
+        let (sender, receiver) = mpsc::channel();
+
+        stream_transactions(self.account, sender.clone())?;
+        if !self.instruments.is_empty() {
+            stream_prices(self.account, &self.instruments, sender)?;
+        }
+
+        loop {
+            match self.next_event(&receiver)? {
+                Some(event) => self.handle(event)?,
+                None => self.fire_timer(UTC::now())
+            }
+        }
+    }
+
+    /// The next event, or None if the timer is due first
+    fn next_event(&self, receiver: &Receiver<Result<Event>>) -> Result<Option<Event>> {
+        let closed = || Error::Io(io::Error::new(io::ErrorKind::UnexpectedEof, "the streams were closed"));
+
+        let event = match self.timer {
+            Some((_, due)) => {
+                let wait = due.signed_duration_since(UTC::now()).to_std().unwrap_or(time::Duration::from_secs(0));
+
+                match receiver.recv_timeout(wait) {
+                    Ok(event) => event,
+                    Err(RecvTimeoutError::Timeout) => return Ok(None),
+                    Err(RecvTimeoutError::Disconnected) => return Err(closed())
+                }
+            },
+            None => receiver.recv().map_err(|_| closed())?
+        };
+
+        event.map(Some)
+    }
+
+    fn handle(&mut self, event: Event) -> Result<()> {
+        match event {
+            Event::Price(price) => {
+                self.mirror.update_price(&price);
+
+                if let Some(candle) = self.candles.as_mut().and_then(|candles| candles.push(&price)) {
+                    self.strategy.on_candle_close(&self.mirror, &price.instrument, &candle);
+                }
+                self.strategy.on_price(&self.mirror, &price);
+            },
+            Event::Heartbeat(time) => {
+                let closed = self.candles.as_mut().map_or_else(Vec::new, |candles| candles.close(time));
+
+                for (instrument, candle) in closed {
+                    self.strategy.on_candle_close(&self.mirror, &instrument, &candle);
+                }
+            },
+            Event::Transaction(transaction) => {
+                self.mirror.update_transaction(&transaction)?;
+                self.strategy.on_transaction(&self.mirror, &transaction);
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Call the strategy with the time the timer was due, and schedule the
+    /// next interval after `now`
+    fn fire_timer(&mut self, now: DateTime<UTC>) {
+        if let Some((interval, due)) = self.timer {
+            self.strategy.on_timer(&self.mirror, due);
+
+            let mut next = due + interval;
+            while next <= now {
+                next = next + interval;
+            }
+            self.timer = Some((interval, next));
+        }
+    }
+}
+
+fn ended(stream: &str) -> Error {
+    Error::Io(io::Error::new(io::ErrorKind::UnexpectedEof, format!("the {} stream ended", stream)))
+}
+
+fn stream_transactions(account: &Account, sender: Sender<Result<Event>>) -> Result<()> {
+    let stream = account.transaction_stream()?;
+
+    thread::spawn(move || {
+        for message in stream {
+            let event = match message {
+                Ok(TransactionMessage::Transaction(transaction)) => Ok(Event::Transaction(transaction)),
+                Ok(TransactionMessage::Heartbeat { .. }) => continue,
+                Err(err) => Err(err)
+            };
+
+            if sender.send(event).is_err() {
+                return
+            }
+        }
+
+        sender.send(Err(ended("transaction"))).ok();
+    });
+
+    Ok(())
+}
+
+fn stream_prices(account: &Account, instruments: &[InstrumentName], sender: Sender<Result<Event>>) -> Result<()> {
+    let stream = account.price_stream(instruments)?;
+
+    thread::spawn(move || {
+        for message in stream {
+            let event = match message {
+                Ok(PriceMessage::Price(price)) => Ok(Event::Price(price)),
+                Ok(PriceMessage::Heartbeat(time)) => Ok(Event::Heartbeat(time)),
+                Err(err) => Err(err)
+            };
+
+            if sender.send(event).is_err() {
+                return
+            }
+        }
+
+        sender.send(Err(ended("price"))).ok();
+    });
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+    use serde_json;
+    use account::orders::{CreateOrderRequest, MarketOrderRequest};
+    use account::trading::TradingAccount;
+    use client::Client;
+    use instrument::candlestick::Candlestick;
+    use account::instruments::Instrument;
+    use mock::{MockServer, MockState};
+
+    /// Buys on the first Price, and records what it is called with
+    #[derive(Default)]
+    struct Recorder {
+        prices: usize,
+        candles: Vec<Candlestick>,
+        transactions: Vec<String>,
+        timers: Vec<DateTime<UTC>>
+    }
+
+    impl Strategy for Recorder {
+        fn on_price<A: TradingAccount>(&mut self, account: &A, price: &ClientPrice) {
+            if self.prices == 0 {
+                let order = MarketOrderRequest::new(price.instrument.clone(), "10000".parse().unwrap());
+                account.try_create_order(&CreateOrderRequest { order: order }).unwrap();
+            }
+            self.prices += 1;
+        }
+
+        fn on_candle_close<A: TradingAccount>(&mut self, _: &A, _: &InstrumentName, candle: &Candlestick) {
+            self.candles.push(candle.clone());
+        }
+
+        fn on_transaction<A: TradingAccount>(&mut self, _: &A, transaction: &Transaction) {
+            self.transactions.push(transaction.id.clone());
+        }
+
+        fn on_timer<A: TradingAccount>(&mut self, _: &A, time: DateTime<UTC>) {
+            self.timers.push(time);
+        }
+    }
+
+    fn price(second: u32, bid: &str, ask: &str) -> ClientPrice {
+        serde_json::from_str(&format!(
+            r#"{{"instrument": "EUR_USD", "time": "{}", "tradeable": true,
+                "bids": [{{"price": "{}", "liquidity": 1000000}}], "asks": [{{"price": "{}", "liquidity": 1000000}}],
+                "closeoutBid": "{}", "closeoutAsk": "{}"}}"#,
+            UTC.ymd(2017, 6, 21).and_hms(12, 0, second).to_rfc3339(), bid, ask, bid, ask
+        )).unwrap()
+    }
+
+    #[test]
+    fn it_routes_events_to_the_strategy_and_orders_to_the_account() {
+        let mut state = MockState::new();
+        state
+            .with_account("101-001-1-001", "USD".parse().unwrap(), "100000".parse().unwrap())
+            .with_instrument(Instrument::currency_pair("EUR_USD".parse().unwrap()))
+            .with_price("EUR_USD".parse().unwrap(), "1.11000".parse().unwrap(), "1.11020".parse().unwrap());
+        let server = MockServer::start(state).unwrap();
+        let client = Client::new(server.url(), "key");
+        let accounts = client.accounts();
+
+        let mut runtime = Runtime::new(&accounts[0], Recorder::default(), vec!["EUR_USD".parse().unwrap()]).unwrap();
+        runtime.with_candles(CandlestickGranularity::S5);
+
+        runtime.handle(Event::Price(price(1, "1.11000", "1.11020"))).unwrap();
+        let details = runtime.mirror().try_details().unwrap();
+        assert_eq!(details.trades.len(), 1);
+        assert_eq!(details.trades[0].price, "1.11020".parse().unwrap());
+
+        // The mirror already has the fill, so it is not reloaded
+        let fill = server.state().transactions()[1].clone();
+        runtime.handle(Event::Transaction(fill)).unwrap();
+        assert_eq!(runtime.strategy().transactions, vec!["2".to_string()]);
+
+        runtime.handle(Event::Price(price(3, "1.11120", "1.11140"))).unwrap();
+        let summary = runtime.mirror().try_summary().unwrap();
+        assert_eq!(summary.unrealized_pl, "10.0000".parse().unwrap());
+        assert_eq!(summary.nav, summary.balance + summary.unrealized_pl);
+
+        runtime.handle(Event::Heartbeat(UTC.ymd(2017, 6, 21).and_hms(12, 0, 5))).unwrap();
+        assert_eq!(runtime.strategy().candles.len(), 1);
+        assert_eq!(runtime.strategy().candles[0].volume, 2);
+
+        let due = UTC::now();
+        runtime.timer = Some((Duration::seconds(10), due));
+        runtime.fire_timer(due + Duration::seconds(25));
+        assert_eq!(runtime.strategy().timers, vec![due]);
+        assert_eq!(runtime.timer.map(|(_, next)| next), Some(due + Duration::seconds(30)));
+    }
+}
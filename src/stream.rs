@@ -0,0 +1,185 @@
+//! Oanda's pricing and transaction streams. A stream is a long lived response
+//! with one JSON message per line; heartbeats are sent every 5 seconds when
+//! there is nothing else to send.
+
+use std::io::{BufRead, BufReader, Lines, Read};
+
+use chrono::datetime::DateTime;
+use chrono::UTC;
+use serde_json;
+
+use account::transactions::Transaction;
+use error::Result;
+use instrument::price::ClientPrice;
+
+/// A message of the pricing stream
+#[derive(Clone, Debug, PartialEq)]
+pub enum PriceMessage {
+    /// The latest Price of an instrument
+    Price(ClientPrice),
+    /// Sent when no Price has been sent for 5 seconds
+    Heartbeat(DateTime<UTC>)
+}
+
+/// A message of the transaction stream
+#[derive(Clone, Debug, PartialEq)]
+pub enum TransactionMessage {
+    /// A Transaction created for the Account
+    Transaction(Transaction),
+    /// Sent when no Transaction has been sent for 5 seconds
+    Heartbeat {
+        /// The ID of the most recent Transaction created for the Account
+        last_transaction_id: String,
+        /// The date/time when the heartbeat was created.
+        time: DateTime<UTC>
+    }
+}
+
+/// The Prices of the instruments requested with `Account::price_stream`, as
+/// they change
+pub struct PriceStream {
+    lines: Lines<BufReader<Box<dyn Read + Send>>>
+}
+
+impl PriceStream {
+    /// Read a pricing stream from `reader`, e.g. a recorded one
+    pub fn new<R: Read + Send + 'static>(reader: R) -> PriceStream {
+        PriceStream { lines: BufReader::new(Box::new(reader) as Box<dyn Read + Send>).lines() }
+    }
+}
+
+impl Iterator for PriceStream {
+    type Item = Result<PriceMessage>;
+
+    fn next(&mut self) -> Option<Result<PriceMessage>> {
+        next_message(&mut self.lines, |message_type, line| match message_type {
+            "HEARTBEAT" => Ok(PriceMessage::Heartbeat(serde_json::from_str::<Heartbeat>(line)?.time)),
+            _ => Ok(PriceMessage::Price(serde_json::from_str(line)?))
+        })
+    }
+}
+
+/// The Transactions of an Account as they are created, see
+/// `Account::transaction_stream`
+pub struct TransactionStream {
+    lines: Lines<BufReader<Box<dyn Read + Send>>>
+}
+
+impl TransactionStream {
+    /// Read a transaction stream from `reader`, e.g. a recorded one
+    pub fn new<R: Read + Send + 'static>(reader: R) -> TransactionStream {
+        TransactionStream { lines: BufReader::new(Box::new(reader) as Box<dyn Read + Send>).lines() }
+    }
+}
+
+impl Iterator for TransactionStream {
+    type Item = Result<TransactionMessage>;
+
+    fn next(&mut self) -> Option<Result<TransactionMessage>> {
+        next_message(&mut self.lines, |message_type, line| match message_type {
+            "HEARTBEAT" => {
+                let heartbeat: Heartbeat = serde_json::from_str(line)?;
+
+                Ok(TransactionMessage::Heartbeat {
+                    last_transaction_id: heartbeat.last_transaction_id.unwrap_or_default(),
+                    time: heartbeat.time
+                })
+            },
+            _ => Ok(TransactionMessage::Transaction(serde_json::from_str(line)?))
+        })
+    }
+}
+
+/// The fields every message has
+#[derive(Deserialize)]
+struct MessageType {
+    #[serde(rename = "type")]
+    message_type: String
+}
+
+#[derive(Deserialize)]
+struct Heartbeat {
+    time: DateTime<UTC>,
+    #[serde(rename = "lastTransactionID")]
+    #[serde(default)]
+    last_transaction_id: Option<String>
+}
+
+/// Parse the next non-empty line with `parse`, which is given the type of the
+/// message and the line
+fn next_message<R, T, F>(lines: &mut Lines<R>, parse: F) -> Option<Result<T>>
+    where R: BufRead, F: Fn(&str, &str) -> Result<T>
+{
+    for line in lines {
+        let line = match line {
+            Ok(line) => line,
+            Err(err) => return Some(Err(err.into()))
+        };
+
+        if line.trim().is_empty() {
+            continue
+        }
+
+        return Some(
+            serde_json::from_str::<MessageType>(&line)
+                .map_err(|err| err.into())
+                .and_then(|message| parse(&message.message_type, &line))
+        )
+    }
+
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Cursor;
+    use account::transactions::TransactionType;
+    use chrono::TimeZone;
+
+    #[test]
+    fn it_reads_prices_and_heartbeats() {
+        let stream = PriceStream::new(Cursor::new(concat!(
+            r#"{"type":"PRICE","instrument":"EUR_USD","time":"2017-06-21T12:00:00.000000000Z","tradeable":true,"#,
+            r#""bids":[{"price":"1.11430","liquidity":10000000}],"asks":[{"price":"1.11450","liquidity":10000000}],"#,
+            r#""closeoutBid":"1.11430","closeoutAsk":"1.11450"}"#, "\n",
+            "\n",
+            r#"{"type":"HEARTBEAT","time":"2017-06-21T12:00:05.000000000Z"}"#, "\n"
+        )));
+        let messages = stream.collect::<Result<Vec<_>>>().unwrap();
+
+        match messages[0] {
+            PriceMessage::Price(ref price) => assert_eq!(price.closeout_bid, "1.11430".parse().unwrap()),
+            ref message => panic!("expected a price, got {:?}", message)
+        }
+        assert_eq!(messages[1], PriceMessage::Heartbeat(UTC.ymd(2017, 6, 21).and_hms(12, 0, 5)));
+        assert_eq!(messages.len(), 2);
+    }
+
+    #[test]
+    fn it_reads_transactions_and_heartbeats() {
+        let mut stream = TransactionStream::new(Cursor::new(concat!(
+            r#"{"type":"HEARTBEAT","lastTransactionID":"6","time":"2017-06-21T12:00:05.000000000Z"}"#, "\n",
+            r#"{"id":"7","time":"2017-06-21T12:00:06.000000000Z","userID":1435156,"#,
+            r#""accountID":"101-004-1435156-001","batchID":"7","type":"ORDER_CANCEL","orderID":"6","#,
+            r#""reason":"CLIENT_REQUEST"}"#, "\n",
+            "not json\n"
+        )));
+
+        assert_eq!(
+            stream.next().unwrap().unwrap(),
+            TransactionMessage::Heartbeat {
+                last_transaction_id: "6".to_string(),
+                time: UTC.ymd(2017, 6, 21).and_hms(12, 0, 5)
+            }
+        );
+        match stream.next().unwrap().unwrap() {
+            TransactionMessage::Transaction(transaction) => {
+                assert_eq!(transaction.transaction_type, TransactionType::ORDER_CANCEL)
+            },
+            message => panic!("expected a transaction, got {:?}", message)
+        }
+        assert!(stream.next().unwrap().is_err());
+        assert!(stream.next().is_none());
+    }
+}
@@ -0,0 +1,107 @@
+extern crate chrono;
+extern crate oandars;
+
+use chrono::TimeZone;
+use chrono::UTC;
+
+use oandars::account::orders::{CreateOrderRequest, MarketOrderRequest};
+use oandars::account::positions::ClosePositionRequest;
+use oandars::account::trades::{CloseTradeRequest, CloseUnits};
+use oandars::client::Client;
+use oandars::error::Error;
+use oandars::instrument::candlestick::Candlestick;
+use oandars::instrument::candlestick_data::CandlestickData;
+use oandars::instrument::candlestick_granularity::CandlestickGranularity;
+use oandars::account::instruments::Instrument;
+use oandars::mock::{MockServer, MockState};
+
+const ACCOUNT_ID: &'static str = "101-001-1-001";
+
+fn server() -> MockServer {
+    let candles = (0..90).map(|minute| {
+        let close = format!("1.1{:04}", 1000 + minute);
+
+        Candlestick {
+            time: UTC.ymd(2017, 6, 21).and_hms(12, 0, 0) + chrono::Duration::minutes(minute),
+            bid: None,
+            ask: None,
+            mid: Some(CandlestickData {
+                o: "1.11000".parse().unwrap(),
+                h: close.parse().unwrap(),
+                l: "1.11000".parse().unwrap(),
+                c: close.parse().unwrap()
+            }),
+            volume: 10,
+            complete: true
+        }
+    }).collect();
+
+    let mut state = MockState::new();
+    state
+        .with_account(ACCOUNT_ID, "USD".parse().unwrap(), "100000".parse().unwrap())
+        .with_instrument(Instrument::currency_pair("EUR_USD".parse().unwrap()))
+        .with_candles("EUR_USD".parse().unwrap(), CandlestickGranularity::M1, candles);
+
+    MockServer::start(state).unwrap()
+}
+
+#[test]
+fn it_reads_accounts_and_candles() {
+    let server = server();
+    let client = Client::new(server.url(), "key");
+    let accounts = client.accounts();
+    let account = accounts.first().unwrap();
+
+    assert_eq!(account.id, ACCOUNT_ID);
+    assert_eq!(account.summary().balance, "100000".parse().unwrap());
+    assert_eq!(account.instruments()[0].name, "EUR_USD");
+
+    let from = UTC.ymd(2017, 6, 21).and_hms(12, 0, 0);
+    let candles = client
+        .pricing_for("EUR_USD".parse().unwrap(), from)
+        .with_granularity(CandlestickGranularity::M1)
+        .candles_between(from, from + chrono::Duration::hours(1))
+        .collect::<Result<Vec<_>, _>>()
+        .unwrap();
+
+    assert_eq!(candles.len(), 60);
+    assert_eq!(candles[59].mid.as_ref().unwrap().c, "1.11059".parse().unwrap());
+}
+
+#[test]
+fn it_trades() {
+    let server = server();
+    let client = Client::new(server.url(), "key");
+    let accounts = client.accounts();
+    let account = accounts.first().unwrap();
+
+    let mut order = MarketOrderRequest::new("EUR_USD".parse().unwrap(), "10000".parse().unwrap());
+    order.with_take_profit("1.12000".parse().unwrap());
+    let created = account.create_order(&CreateOrderRequest { order: order });
+    let trade_id = created.order_fill_transaction.unwrap().trade_opened.unwrap().trade_id;
+
+    assert_eq!(account.details().orders.len(), 1);
+
+    server.state().with_price("EUR_USD".parse().unwrap(), "1.11189".parse().unwrap(), "1.11191".parse().unwrap());
+    account.close_trade(&trade_id, &CloseTradeRequest { units: "4000".parse().unwrap() });
+
+    let position = account.position(&"EUR_USD".parse().unwrap());
+    assert_eq!(position.long.units, "6000".parse().unwrap());
+    assert_eq!(position.pl, "4.0000".parse().unwrap());
+
+    account.close_position(&"EUR_USD".parse().unwrap(), &ClosePositionRequest::all_of(&position));
+    let details = account.details();
+
+    assert!(details.trades.is_empty());
+    assert!(details.orders.is_empty());
+    assert_eq!(details.balance, "100010.0000".parse().unwrap());
+
+    match account.try_cancel_order("1") {
+        Err(Error::Api { status: 404, .. }) => (),
+        result => panic!("expected the order not to exist, got {:?}", result.map(|_| ()))
+    }
+    match account.try_close_trade(&trade_id, &CloseTradeRequest { units: CloseUnits::All }) {
+        Err(Error::Api { status: 404, .. }) => (),
+        result => panic!("expected the trade to be closed, got {:?}", result.map(|_| ()))
+    }
+}